@@ -0,0 +1,167 @@
+#[path = "../src/async_db/mod.rs"]
+#[allow(dead_code, unused_imports)]
+mod async_db;
+
+use std::collections::BTreeSet;
+
+use async_db::{CellView, Column, ColumnView, Join, JoinStrategy, Key, DEFAULT_SHARDS};
+use async_std::task;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use futures::{future::join_all, FutureExt, StreamExt};
+
+const KEYS: usize = 256;
+const TASKS: usize = 8;
+const OPS: usize = 64;
+
+/// Interleaves writers inserting and removing cells with readers holding cells across yields
+async fn mixed_load(column: &Column<i32>) {
+    let writers = (0..TASKS).map(|task| {
+        async move {
+            for op in 0..OPS {
+                let key = Key::new((task * OPS + op) % KEYS);
+                column.insert(key, op as i32).await.unwrap();
+                if op % 4 == 0 {
+                    column.remove(&key).await;
+                }
+            }
+        }
+        .boxed_local()
+    });
+
+    let readers = (0..TASKS).map(|task| {
+        async move {
+            for op in 0..OPS {
+                let index = (task * 31 + op * 7) % KEYS;
+                if column.contains_key(&Key::new(index)).await {
+                    let _cell = CellView::new(column, Key::new(index)).await;
+                    task::yield_now().await;
+                }
+            }
+        }
+        .boxed_local()
+    });
+
+    join_all(writers.chain(readers)).await;
+}
+
+fn sharded_column(count: usize) -> Column<i32> {
+    let column = Column::with_shards(count);
+    task::block_on(async {
+        for key in 0..KEYS {
+            column.insert(Key::new(key), key as i32).await.unwrap();
+        }
+    });
+    column
+}
+
+/// Three columns whose sizes differ by a factor of `skew`, sharing every key of the smallest
+fn skewed_columns(skew: usize) -> (Column<i32>, Column<f32>, Column<char>) {
+    let (ints, floats, chars): (Column<i32>, Column<f32>, Column<char>) = Default::default();
+    task::block_on(async {
+        for key in 0..KEYS * skew * skew {
+            ints.insert(Key::new(key), key as i32).await.unwrap();
+        }
+        for key in (0..KEYS * skew).map(|key| key * skew) {
+            floats.insert(Key::new(key), key as f32).await.unwrap();
+        }
+        for key in (0..KEYS).map(|key| key * skew * skew) {
+            chars.insert(Key::new(key), 'a').await.unwrap();
+        }
+    });
+    (ints, floats, chars)
+}
+
+/// The union-then-filter approach the planner replaces
+async fn materialised_common_keys(
+    ints: &Column<i32>,
+    floats: &Column<f32>,
+    chars: &Column<char>,
+) -> BTreeSet<usize> {
+    let ints = ColumnView::new(ints).await;
+    let floats = ColumnView::new(floats).await;
+    let chars = ColumnView::new(chars).await;
+
+    ints.keys()
+        .map(|key| key.index())
+        .chain(floats.keys().map(|key| key.index()))
+        .chain(chars.keys().map(|key| key.index()))
+        .filter(|&index| {
+            ints.contains_key(&Key::new(index))
+                && floats.contains_key(&Key::new(index))
+                && chars.contains_key(&Key::new(index))
+        })
+        .collect()
+}
+
+async fn joined_common_keys(
+    ints: &Column<i32>,
+    floats: &Column<f32>,
+    chars: &Column<char>,
+    strategy: JoinStrategy,
+) -> Vec<usize> {
+    Join::new()
+        .with(ints)
+        .with(floats)
+        .with(chars)
+        .with_strategy(strategy)
+        .keys::<()>()
+        .map(|key| key.index())
+        .collect()
+        .await
+}
+
+fn join_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Common keys");
+
+    for &skew in &[1, 4, 16] {
+        let (ints, floats, chars) = skewed_columns(skew);
+
+        group.bench_with_input(BenchmarkId::new("Materialised", skew), &skew, |b, _| {
+            b.iter(|| task::block_on(materialised_common_keys(&ints, &floats, &chars)))
+        });
+        for &strategy in &[JoinStrategy::Probe, JoinStrategy::Merge] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", strategy), skew),
+                &skew,
+                |b, _| {
+                    b.iter(|| task::block_on(joined_common_keys(&ints, &floats, &chars, strategy)))
+                },
+            );
+        }
+    }
+
+    // Equal sizes, but the columns only share their last few keys, which merging skips straight to
+    let (ints, floats, chars) = skewed_columns(1);
+    task::block_on(async {
+        for key in 0..KEYS - 8 {
+            chars.remove(&Key::new(key)).await;
+            chars.insert(Key::new(KEYS * 2 + key), 'b').await.unwrap();
+        }
+    });
+    for &strategy in &[JoinStrategy::Probe, JoinStrategy::Merge] {
+        group.bench_function(BenchmarkId::new(format!("{:?}", strategy), "sparse"), |b| {
+            b.iter(|| task::block_on(joined_common_keys(&ints, &floats, &chars, strategy)))
+        });
+    }
+
+    group.finish();
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Column mixed load");
+
+    for &count in &[1, DEFAULT_SHARDS] {
+        group.bench_with_input(BenchmarkId::new("Shards", count), &count, |b, &count| {
+            b.iter_batched(
+                || sharded_column(count),
+                |column| task::block_on(mixed_load(&column)),
+                criterion::BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, join_benchmark);
+criterion_main!(benches);
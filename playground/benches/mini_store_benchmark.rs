@@ -0,0 +1,61 @@
+#[path = "../src/cons/mod.rs"]
+#[allow(dead_code, clippy::from_over_into)]
+mod cons;
+
+#[path = "../src/mini_store/mod.rs"]
+#[allow(dead_code, unused_imports)]
+mod mini_store;
+
+use std::collections::{BTreeMap, HashMap};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fnv::FnvHashMap;
+use mini_store::{MiniStore, Storage, Tracked, VecMap};
+
+const KEYS: i32 = 10_000;
+
+fn filled_store<S>(storage: S) -> MiniStore<i32>
+where
+    S: Storage<i32, Tracked<u64>> + 'static,
+{
+    let mut store = MiniStore::default();
+    store.register::<u64, _>(storage);
+    // Scattered insertion order, so sorted backends can't just append
+    for i in 0..KEYS {
+        let key = i.wrapping_mul(7919) % KEYS;
+        store.set(key, key as u64);
+    }
+    store
+}
+
+fn sum(store: &MiniStore<i32>) -> u64 {
+    (0..KEYS).map(|key| *store.get::<u64>(&key).unwrap()).sum()
+}
+
+fn bench_backend<S>(c: &mut Criterion, name: &str, storage: impl Fn() -> S)
+where
+    S: Storage<i32, Tracked<u64>> + 'static,
+{
+    let mut group = c.benchmark_group("MiniStore backends");
+
+    group.bench_function(BenchmarkId::new("Set", name), |b| {
+        b.iter(|| filled_store(storage()))
+    });
+
+    let store = filled_store(storage());
+    group.bench_function(BenchmarkId::new("Get", name), |b| {
+        b.iter(|| sum(black_box(&store)))
+    });
+
+    group.finish();
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    bench_backend(c, "BTreeMap", BTreeMap::new);
+    bench_backend(c, "HashMap", HashMap::new);
+    bench_backend(c, "FnvHashMap", FnvHashMap::default);
+    bench_backend(c, "VecMap", VecMap::new);
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
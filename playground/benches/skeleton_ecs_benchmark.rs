@@ -0,0 +1,125 @@
+#[path = "../src/skeleton_ecs/mod.rs"]
+#[allow(dead_code, unused_imports)]
+mod skeleton_ecs;
+
+use std::{
+    cell::{Ref, RefMut},
+    collections::HashMap,
+};
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion,
+};
+use skeleton_ecs::store::{
+    ArchetypeStorage, ComponentLayout, IterArchetypes, IterStoreFields, SparseSet, Storable, Store,
+};
+
+const ENTITIES: usize = 100_000;
+
+type ArchetypeFields<'a> = (Ref<'a, ArchetypeVelocity>, RefMut<'a, ArchetypePosition>);
+
+/// Declares a position and velocity pair kept in `$storage`
+macro_rules! components {
+    ($position:ident, $velocity:ident, $storage:ty) => {
+        struct $position {
+            x: i64,
+            y: i64,
+        }
+
+        impl Storable for $position {
+            type Storage = $storage;
+        }
+
+        struct $velocity {
+            x: i64,
+            y: i64,
+        }
+
+        impl Storable for $velocity {
+            type Storage = $storage;
+        }
+
+        impl Integrate<$position> for $velocity {
+            fn new(i: i64) -> ($position, Self) {
+                ($position { x: i, y: -i }, $velocity { x: i % 7 - 3, y: 1 })
+            }
+
+            fn integrate(&self, position: &mut $position) {
+                position.x += self.x;
+                position.y += self.y;
+            }
+        }
+    };
+}
+
+/// A velocity that moves positions of type `P`
+trait Integrate<P> {
+    /// The components of the `i`th entity
+    fn new(i: i64) -> (P, Self);
+    fn integrate(&self, position: &mut P);
+}
+
+components!(HashPosition, HashVelocity, HashMap<usize, Self>);
+components!(SparsePosition, SparseVelocity, SparseSet<usize, Self>);
+components!(
+    ArchetypePosition,
+    ArchetypeVelocity,
+    ArchetypeStorage<usize>
+);
+
+fn filled_store<P, V>() -> Store
+where
+    P: Storable,
+    P::Storage: ComponentLayout<P, Key = usize>,
+    V: Storable + Integrate<P>,
+    V::Storage: ComponentLayout<V, Key = usize>,
+{
+    let mut store = Store::default();
+    store.add_storage_for::<P>();
+    store.add_storage_for::<V>();
+    for key in 0..ENTITIES {
+        let (position, velocity) = V::new(key as i64);
+        store.insert(key, position);
+        store.insert(key, velocity);
+    }
+    store
+}
+
+/// Integrates by looking each entity's components up by key
+fn bench_keyed<P, V>(group: &mut BenchmarkGroup<WallTime>, name: &str)
+where
+    P: Storable,
+    P::Storage: ComponentLayout<P, Key = usize>,
+    V: Storable + Integrate<P>,
+    V::Storage: ComponentLayout<V, Key = usize>,
+    for<'a> Store: IterStoreFields<'a, usize, (Ref<'a, V>, RefMut<'a, P>)>,
+{
+    let store = filled_store::<P, V>();
+    group.bench_function(BenchmarkId::new("Keyed", name), |b| {
+        b.iter(|| {
+            IterStoreFields::<usize, (Ref<V>, RefMut<P>)>::iter(&store)
+                .for_each(|(_, (velocity, mut position))| velocity.integrate(&mut position))
+        })
+    });
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("skeleton_ecs integrator over 100k entities");
+    bench_keyed::<HashPosition, HashVelocity>(&mut group, "HashMap");
+    bench_keyed::<SparsePosition, SparseVelocity>(&mut group, "SparseSet");
+    bench_keyed::<ArchetypePosition, ArchetypeVelocity>(&mut group, "Archetype");
+
+    let store = filled_store::<ArchetypePosition, ArchetypeVelocity>();
+    // Walks the columns of each archetype instead of looking entities up
+    group.bench_function(BenchmarkId::new("Linear", "Archetype"), |b| {
+        b.iter(|| {
+            IterArchetypes::<usize, ArchetypeFields>::iter_archetypes(&store)
+                .for_each(|(_, (velocity, mut position))| velocity.integrate(&mut position))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,79 +1,75 @@
 use std::ops::Deref;
-use std::{marker::PhantomPinned, pin::Pin, ptr::NonNull};
 
-use super::{BorrowColumn, ColumnCollection, ColumnView, Key};
+use super::{BorrowColumn, CellLock, Column, Key, ReadCell, ReadColumn, ShardCollection};
 
-/// A view into one of the [`Cell`]s of a [`Column`]
+/// A view into one of the [`Cell`]s of a [`Column`].
+/// Holds read locks on the cell and on the shard containing it.
 #[derive(Debug)]
-pub struct CellView<'a, T>(Pin<Box<CellViewInner<'a, T>>>);
+pub struct CellView<'a, T> {
+    // Declared first so the cell guard is dropped before the shard guard it borrows from
+    cell_guard: ReadCell<'a, T>,
+    shard_guard: ReadColumn<'a, T>,
+    source: &'a Column<T>,
+    key: Key,
+}
 
 impl<'a, T> CellView<'a, T> {
-    pub async fn new<DB>(db: &'a DB, index: Key) -> CellView<'a, T>
+    pub async fn new<DB>(db: &'a DB, key: Key<DB>) -> CellView<'a, T>
     where
         T: 'a,
         DB: BorrowColumn<T>,
     {
-        CellView(CellViewInner::new(db, index).await)
-    }
-
-    pub fn cell(&'a self) -> &'a T {
-        self.0.deref().deref()
-    }
-
-    #[allow(dead_code)]
-    pub fn column(&'a self) -> &'a ColumnCollection<T> {
-        self.0.deref().column_guard.deref()
-    }
-}
-
-impl<'a, T> Deref for CellView<'a, T> {
-    type Target = T;
-
-    fn deref(&self) -> &Self::Target {
-        self.cell()
+        Self::try_new(db, key).await.unwrap()
     }
-}
-
-/// Inner workings of [`CellView`].
-/// Self-referential struct that holds both the column and cell read guards
-#[derive(Debug)]
-struct CellViewInner<'a, T> {
-    column_guard: ColumnView<'a, T>,
-    item_guard: NonNull<T>,
-    _pin: PhantomPinned,
-}
 
-impl<'a, T> CellViewInner<'a, T> {
-    pub async fn new<DB>(db: &'a DB, index: Key) -> Pin<Box<CellViewInner<'a, T>>>
+    /// Creates a view of the cell `key`, or returns `None` if the column doesn't contain it
+    pub async fn try_new<DB>(db: &'a DB, key: Key<DB>) -> Option<CellView<'a, T>>
     where
         T: 'a,
         DB: BorrowColumn<T>,
     {
-        let column_guard = ColumnView::new(db).await;
+        let source = db.borrow();
+        let key = key.untagged();
+        source.refresh().await;
 
-        let guard = CellViewInner {
-            column_guard,
-            item_guard: NonNull::dangling(),
-            _pin: PhantomPinned,
-        };
-        let mut boxed = Box::pin(guard);
+        let shard_guard = source.shards().shard(&key).read().await;
 
-        let item_guard =
-            NonNull::from(boxed.column_guard.get(&index).unwrap().read().await.deref());
+        // The shard can't be modified while its read guard is held,
+        // so the cell lock outlives any borrow taken here
+        let cell: &'a CellLock<T> = unsafe { &*(shard_guard.get(&key)? as *const CellLock<T>) };
+        let cell_guard = cell.read().await;
 
-        unsafe {
-            let mut_ref: Pin<&mut Self> = Pin::as_mut(&mut boxed);
-            Pin::get_unchecked_mut(mut_ref).item_guard = item_guard;
-        }
+        Some(CellView {
+            cell_guard,
+            shard_guard,
+            source,
+            key,
+        })
+    }
+
+    pub fn cell(&self) -> &T {
+        self.cell_guard.deref()
+    }
 
-        boxed
+    /// The shard of the column holding this cell, which the view keeps read-locked
+    pub fn shard(&self) -> &ShardCollection<T> {
+        &self.shard_guard
+    }
+
+    pub fn key(&self) -> Key {
+        self.key
+    }
+
+    /// The [`Column`] this view was created from
+    pub fn source(&self) -> &'a Column<T> {
+        self.source
     }
 }
 
-impl<'a, T> Deref for CellViewInner<'a, T> {
+impl<'a, T> Deref for CellView<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { self.item_guard.as_ref() }
+        self.cell()
     }
 }
@@ -1,105 +1,109 @@
-use std::ops::{Deref, DerefMut};
-use std::{marker::PhantomPinned, pin::Pin, ptr::NonNull};
-
-use super::{BorrowColumn, ColumnCollection, ColumnView, Key};
-
-/// A view into one of the [`Cell`]s of a [`Column`]
+use std::{
+    any::type_name,
+    ops::{Deref, DerefMut},
+};
+
+use super::{
+    BorrowColumn, CellLock, Column, ColumnEvent, Key, ReadColumn, ShardCollection, WriteCell,
+};
+
+/// A mutable view into one of the [`Cell`]s of a [`Column`].
+/// Holds a write lock on the cell and a read lock on the shard containing it.
+///
+/// Edits made in place can't be rejected, so columns with constraints can't be viewed mutably;
+/// write to them with [`Column::update`] instead.
 #[derive(Debug)]
-pub struct CellViewMut<'a, T>(Pin<Box<CellViewMutInner<'a, T>>>);
+pub struct CellViewMut<'a, T> {
+    // Declared first so the cell guard is dropped before the shard guard it borrows from
+    cell_guard: WriteCell<'a, T>,
+    shard_guard: ReadColumn<'a, T>,
+    source: &'a Column<T>,
+    key: Key,
+}
 
 impl<'a, T> CellViewMut<'a, T> {
-    pub async fn new<DB>(db: &'a DB, index: Key) -> CellViewMut<'a, T>
+    pub async fn new<DB>(db: &'a DB, key: Key<DB>) -> CellViewMut<'a, T>
     where
         T: 'a,
         DB: BorrowColumn<T>,
     {
-        CellViewMut(CellViewMutInner::new(db, index).await)
+        Self::try_new(db, key).await.unwrap()
+    }
+
+    /// Creates a view of the cell `key`, or returns `None` if the column doesn't contain it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column has constraints.
+    pub async fn try_new<DB>(db: &'a DB, key: Key<DB>) -> Option<CellViewMut<'a, T>>
+    where
+        T: 'a,
+        DB: BorrowColumn<T>,
+    {
+        let source: &Column<T> = db.borrow();
+        assert!(
+            !source.has_constraints(),
+            "column {} has constraints, so can't be edited through a CellViewMut",
+            type_name::<T>()
+        );
+        let key = key.untagged();
+        source.refresh().await;
+
+        let shard_guard = source.shards().shard(&key).read().await;
+
+        // The shard can't be modified while its read guard is held,
+        // so the cell lock outlives any borrow taken here
+        let cell: &'a CellLock<T> = unsafe { &*(shard_guard.get(&key)? as *const CellLock<T>) };
+        let cell_guard = cell.write().await;
+
+        Some(CellViewMut {
+            cell_guard,
+            shard_guard,
+            source,
+            key,
+        })
     }
 
     pub fn cell(&self) -> &T {
-        self.0.deref().deref()
+        self.cell_guard.deref()
     }
 
     pub fn cell_mut(&mut self) -> &mut T {
-        unsafe {
-            let mut_ref = Pin::as_mut(&mut self.0);
-            Pin::get_unchecked_mut(mut_ref).deref_mut()
-        }
+        self.cell_guard.deref_mut()
     }
 
-    #[allow(dead_code)]
-    pub fn column(&'a self) -> &'a ColumnCollection<T> {
-        self.0.deref().column_guard.deref()
+    /// The shard of the column holding this cell, which the view keeps read-locked
+    pub fn shard(&self) -> &ShardCollection<T> {
+        &self.shard_guard
     }
-}
-
-impl<'a, T> Deref for CellViewMut<'a, T> {
-    type Target = T;
 
-    fn deref(&self) -> &Self::Target {
-        self.cell()
+    pub fn key(&self) -> Key {
+        self.key
     }
-}
 
-impl<'a, T> DerefMut for CellViewMut<'a, T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        self.cell_mut()
+    /// The [`Column`] this view was created from
+    pub fn source(&self) -> &'a Column<T> {
+        self.source
     }
 }
 
-/// Inner workings of [`CellView`].
-/// Self-referential struct that holds both the column and cell read guards
-#[derive(Debug)]
-struct CellViewMutInner<'a, T> {
-    column_guard: ColumnView<'a, T>,
-    item_guard: NonNull<T>,
-    _pin: PhantomPinned,
-}
-
-impl<'a, T> CellViewMutInner<'a, T> {
-    pub async fn new<DB>(db: &'a DB, index: Key) -> Pin<Box<CellViewMutInner<'a, T>>>
-    where
-        T: 'a,
-        DB: BorrowColumn<T>,
-    {
-        let column_guard = ColumnView::new(db).await;
-
-        let guard = CellViewMutInner {
-            column_guard,
-            item_guard: NonNull::dangling(),
-            _pin: PhantomPinned,
-        };
-        let mut boxed = Box::pin(guard);
-
-        let item_guard = NonNull::from(
-            boxed
-                .column_guard
-                .get(&index)
-                .unwrap()
-                .write()
-                .await
-                .deref(),
-        );
-
-        unsafe {
-            let mut_ref: Pin<&mut Self> = Pin::as_mut(&mut boxed);
-            Pin::get_unchecked_mut(mut_ref).item_guard = item_guard;
-        }
-
-        boxed
+// Subscribers are notified once the mutable borrow ends
+impl<'a, T> Drop for CellViewMut<'a, T> {
+    fn drop(&mut self) {
+        self.source.events().emit(ColumnEvent::Update(self.key));
     }
 }
 
-impl<'a, T> Deref for CellViewMutInner<'a, T> {
+impl<'a, T> Deref for CellViewMut<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { self.item_guard.as_ref() }
+        self.cell()
     }
 }
 
-impl<'a, T> DerefMut for CellViewMutInner<'a, T> {
+impl<'a, T> DerefMut for CellViewMut<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { self.item_guard.as_mut() }
+        self.cell_mut()
     }
 }
@@ -1,21 +1,296 @@
-use super::ColumnLock;
-use std::{borrow::{Borrow, BorrowMut}, ops::{Deref, DerefMut}};
+use super::{
+    ColumnCollection, ColumnEvent, ColumnEvents, Constraint, DatabaseError, Derivation, Key,
+    ShardCollection, Shards, WriteColumn, DEFAULT_SHARDS,
+};
+use async_std::channel::Receiver;
+use std::{
+    any::type_name,
+    borrow::{Borrow, BorrowMut},
+    collections::BTreeSet,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
 
 /// A collection of row structs
-#[derive(Debug, Default)]
-pub struct Column<T>(ColumnLock<T>);
+pub struct Column<T> {
+    shards: Arc<Shards<T>>,
+    events: Arc<ColumnEvents>,
+    derivation: Option<Box<dyn Derivation<T>>>,
+    // Derived keys whose sources have changed, kept until they're written so neither a cancelled
+    // refresh nor a viewed shard loses them
+    pending: Mutex<BTreeSet<Key>>,
+    constraints: Vec<Box<dyn Constraint<T>>>,
+}
+
+impl<T> Column<T> {
+    /// Creates a column split across `count` independently-locked shards
+    pub fn with_shards(count: usize) -> Self {
+        Column {
+            shards: Arc::new(Shards::new(count)),
+            events: Default::default(),
+            derivation: None,
+            pending: Default::default(),
+            constraints: vec![],
+        }
+    }
+
+    /// Creates a column whose cells are computed by `derivation` instead of being inserted directly
+    pub fn from_derivation(derivation: Box<dyn Derivation<T>>) -> Self {
+        Column {
+            derivation: Some(derivation),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a constraint that cells must satisfy when inserted
+    pub fn with_constraint<C>(mut self, constraint: C) -> Self
+    where
+        C: Constraint<T> + 'static,
+    {
+        self.constraints.push(Box::new(constraint));
+        self
+    }
+
+    pub fn shards(&self) -> &Shards<T> {
+        &self.shards
+    }
+
+    /// Shared handle to the shards, used by derived columns to read their sources
+    pub(crate) fn shards_handle(&self) -> Arc<Shards<T>> {
+        self.shards.clone()
+    }
+
+    pub fn events(&self) -> &ColumnEvents {
+        &self.events
+    }
+
+    /// Shared handle to the event subscribers, used by derived columns to watch their sources
+    pub(crate) fn events_handle(&self) -> Arc<ColumnEvents> {
+        self.events.clone()
+    }
+
+    pub fn subscribe(&self) -> Receiver<ColumnEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn has_constraints(&self) -> bool {
+        !self.constraints.is_empty()
+    }
+
+    pub fn is_derived(&self) -> bool {
+        self.derivation.is_some()
+    }
+
+    /// Subscribes to this column's events, and to those of its sources if it's derived
+    pub(crate) fn subscribe_all(&self) -> Vec<Receiver<ColumnEvent>> {
+        let mut receivers = vec![self.subscribe()];
+        if let Some(derivation) = &self.derivation {
+            receivers.extend(derivation.subscribe());
+        }
+        receivers
+    }
+
+    pub async fn contains_key(&self, key: &Key) -> bool {
+        self.refresh().await;
+        self.shards.shard(key).read().await.contains_key(key)
+    }
+
+    /// Inserts a cell if it satisfies the column's constraints, locking only the shard that holds it
+    /// unless the constraints need to see the other cells.
+    /// Returns the previous value of the cell if there was one.
+    pub async fn insert(&self, key: Key, value: T) -> Result<Option<T>, DatabaseError> {
+        self.write_checked(key, value, |_| Ok(())).await
+    }
+
+    /// Inserts a cell that doesn't exist yet, failing if it does
+    pub async fn insert_new(&self, key: Key, value: T) -> Result<(), DatabaseError> {
+        self.write_checked(key, value, |shard| match shard.contains_key(&key) {
+            true => Err(DatabaseError::DuplicateCell {
+                column: type_name::<T>(),
+                key,
+            }),
+            false => Ok(()),
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Overwrites a cell that already exists, returning its previous value
+    pub async fn update(&self, key: Key, value: T) -> Result<T, DatabaseError> {
+        self.write_checked(key, value, |shard| match shard.contains_key(&key) {
+            true => Ok(()),
+            false => Err(DatabaseError::MissingCell {
+                column: type_name::<T>(),
+                key,
+            }),
+        })
+        .await
+        .map(|prev| prev.unwrap())
+    }
+
+    /// Removes a cell, locking only the shard that holds it
+    pub async fn remove(&self, key: &Key) -> Option<T> {
+        let mut shard = self.shards.shard(key).write().await;
+        self.remove_locked(&mut shard, key)
+    }
+
+    /// Puts back the value returned by a previous insert or remove, without checking constraints
+    pub async fn restore(&self, key: Key, prev: Option<T>) {
+        let mut shard = self.shards.shard(&key).write().await;
+        self.restore_locked(&mut shard, key, prev)
+    }
+
+    /// Writes a cell if `precondition` accepts the shard holding it and the value satisfies
+    /// the column's constraints.
+    ///
+    /// Columns with constraints lock every shard so that checks can compare against any cell,
+    /// and so that checks and their writes can't interleave with those of other keys.
+    async fn write_checked<F>(
+        &self,
+        key: Key,
+        value: T,
+        precondition: F,
+    ) -> Result<Option<T>, DatabaseError>
+    where
+        F: FnOnce(&ShardCollection<T>) -> Result<(), DatabaseError>,
+    {
+        if self.has_constraints() {
+            let mut column = self.shards.write_all().await;
+            precondition(column.shard(&key))?;
+            self.check(&column, key, &value)?;
+            Ok(self.insert_locked(column.shard_mut(&key), key, value))
+        } else {
+            let mut shard = self.shards.shard(&key).write().await;
+            precondition(&shard)?;
+            Ok(self.insert_locked(&mut shard, key, value))
+        }
+    }
+
+    /// Writes a cell into its already-locked shard without checking constraints,
+    /// notifying them and subscribers
+    pub(crate) fn insert_locked(
+        &self,
+        shard: &mut ShardCollection<T>,
+        key: Key,
+        value: T,
+    ) -> Option<T> {
+        self.written(key, Some(&value));
+        let prev = shard.insert(key, value.into());
+
+        self.events.emit(match prev {
+            Some(_) => ColumnEvent::Update(key),
+            None => ColumnEvent::Insert(key),
+        });
+
+        prev.map(|cell| cell.into_inner())
+    }
+
+    pub(crate) fn remove_locked(&self, shard: &mut ShardCollection<T>, key: &Key) -> Option<T> {
+        let prev = shard.remove(key);
 
-impl<T> Deref for Column<T> {
-    type Target = ColumnLock<T>;
+        if prev.is_some() {
+            self.written(*key, None);
+            self.events.emit(ColumnEvent::Remove(*key));
+        }
+
+        prev.map(|cell| cell.into_inner())
+    }
+
+    pub(crate) fn restore_locked(&self, shard: &mut ShardCollection<T>, key: Key, prev: Option<T>) {
+        match prev {
+            Some(value) => {
+                self.insert_locked(shard, key, value);
+            }
+            None => {
+                self.remove_locked(shard, &key);
+            }
+        }
+    }
+
+    /// Checks `value` against each of the column's constraints
+    pub(crate) fn check(
+        &self,
+        column: &ColumnCollection<WriteColumn<'_, T>>,
+        key: Key,
+        value: &T,
+    ) -> Result<(), DatabaseError> {
+        match self
+            .constraints
+            .iter()
+            .find(|constraint| !constraint.check(column, key, value))
+        {
+            Some(constraint) => Err(DatabaseError::ConstraintViolation {
+                column: type_name::<T>(),
+                key,
+                constraint: constraint.describe(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Notifies constraints that the cell `key` has been written or removed
+    fn written(&self, key: Key, value: Option<&T>) {
+        for constraint in &self.constraints {
+            constraint.written(key, value);
+        }
+    }
+
+    /// Recomputes any derived cells whose sources have changed.
+    ///
+    /// Cells in shards that are being viewed, possibly by the calling task, are left for a later
+    /// refresh rather than waited for, so views taken meanwhile keep seeing their old values.
+    pub async fn refresh(&self) {
+        let derivation = match &self.derivation {
+            Some(derivation) => derivation,
+            None => return,
+        };
+
+        let dirty = derivation.dirty().await;
+        let pending = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.extend(dirty);
+            pending.clone()
+        };
+
+        for key in pending {
+            let value = derivation.compute(key).await;
+
+            let mut shard = match self.shards.shard(&key).try_write() {
+                Some(shard) => shard,
+                None => continue,
+            };
+            // A concurrent refresh may have written the cell while this one was computing it
+            if !self.pending.lock().unwrap().remove(&key) {
+                continue;
+            }
+            match value {
+                Some(value) => {
+                    self.insert_locked(&mut shard, key, value);
+                }
+                None => {
+                    self.remove_locked(&mut shard, &key);
+                }
+            }
+        }
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl<T> Default for Column<T> {
+    fn default() -> Self {
+        Column::with_shards(DEFAULT_SHARDS)
     }
 }
 
-impl<T> DerefMut for Column<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl<T> Debug for Column<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Column")
+            .field("shards", &self.shards)
+            .field("derived", &self.is_derived())
+            .field("constraints", &self.constraints.len())
+            .finish()
     }
 }
 
@@ -0,0 +1,64 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex, Weak},
+};
+
+use async_std::channel::{unbounded, Receiver, Sender};
+
+use super::Key;
+
+/// A change to one of the cells of a [`Column`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ColumnEvent {
+    Insert(Key),
+    Update(Key),
+    Remove(Key),
+}
+
+impl ColumnEvent {
+    pub fn key(&self) -> Key {
+        match self {
+            ColumnEvent::Insert(key) | ColumnEvent::Update(key) | ColumnEvent::Remove(key) => *key,
+        }
+    }
+}
+
+/// The set of subscribers listening for changes to a [`Column`]
+#[derive(Debug, Default)]
+pub struct ColumnEvents {
+    subscribers: Mutex<Vec<Sender<ColumnEvent>>>,
+    watchers: Mutex<Vec<Weak<Mutex<BTreeSet<Key>>>>>,
+}
+
+impl ColumnEvents {
+    /// Returns a receiver that will be sent every subsequent event
+    pub fn subscribe(&self) -> Receiver<ColumnEvent> {
+        let (sender, receiver) = unbounded();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Adds the key of every subsequent event to `keys`, for as long as it's kept alive elsewhere.
+    /// Unlike a subscription, a key that changes many times is only held once.
+    pub(crate) fn watch(&self, keys: &Arc<Mutex<BTreeSet<Key>>>) {
+        self.watchers.lock().unwrap().push(Arc::downgrade(keys));
+    }
+
+    /// Sends `event` to every subscriber, dropping any whose receiver has gone away
+    pub fn emit(&self, event: ColumnEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.try_send(event).is_ok());
+        self.watchers
+            .lock()
+            .unwrap()
+            .retain(|keys| match keys.upgrade() {
+                Some(keys) => {
+                    keys.lock().unwrap().insert(event.key());
+                    true
+                }
+                None => false,
+            });
+    }
+}
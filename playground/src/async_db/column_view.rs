@@ -1,33 +1,52 @@
-use std::ops::Deref;
+use std::{marker::PhantomData, ops::Deref};
 
-use super::{BorrowColumn, ColumnCollection, ReadColumn};
+use super::{BorrowColumn, CellLock, Column, ColumnCollection, Key, ReadColumn};
 
-/// A view into one a [`Column`]
+/// A view into one a [`Column`] of the table `DB`.
+/// Holds a read lock on every shard, in ascending key order.
 #[derive(Debug)]
-pub struct ColumnView<'a, T> {
-    column_guard: ReadColumn<'a, T>,
+pub struct ColumnView<'a, T, DB> {
+    source: &'a Column<T>,
+    column: ColumnCollection<ReadColumn<'a, T>>,
+    _table: PhantomData<fn() -> DB>,
 }
 
-impl<'a, T> ColumnView<'a, T> {
-    pub async fn new<DB>(db: &'a DB) -> ColumnView<'a, T>
+impl<'a, T, DB> ColumnView<'a, T, DB> {
+    pub async fn new(db: &'a DB) -> ColumnView<'a, T, DB>
     where
         T: 'a,
         DB: BorrowColumn<T>,
     {
-        let column = db.borrow();
-        let column_guard = column.read().await;
-        ColumnView { column_guard }
+        Self::of(db.borrow()).await
     }
 
-    pub fn column(&'a self) -> &'a ColumnCollection<T> {
-        self.column_guard.deref()
+    /// Creates a view of `source` as a column of the table `DB`
+    pub(crate) async fn of(source: &'a Column<T>) -> ColumnView<'a, T, DB> {
+        source.refresh().await;
+        let column = source.shards().read_all().await;
+        ColumnView {
+            source,
+            column,
+            _table: PhantomData,
+        }
+    }
+
+    pub fn column(&self) -> &ColumnCollection<ReadColumn<'a, T>> {
+        &self.column
+    }
+
+    /// The [`Column`] this view was created from
+    pub fn source(&self) -> &'a Column<T> {
+        self.source
     }
 }
 
-impl<'a, T> Deref for ColumnView<'a, T> {
-    type Target = ColumnCollection<T>;
+impl_shard_reads!(ColumnView);
+
+impl<'a, T, DB> Deref for ColumnView<'a, T, DB> {
+    type Target = ColumnCollection<ReadColumn<'a, T>>;
 
     fn deref(&self) -> &Self::Target {
         self.column()
     }
-}
\ No newline at end of file
+}
@@ -1,43 +1,90 @@
-use std::ops::{Deref, DerefMut};
+use std::{marker::PhantomData, ops::Deref};
 
-use super::{BorrowColumn, ColumnCollection, WriteColumn};
+use super::{BorrowColumn, CellLock, Column, ColumnCollection, DatabaseError, Key, WriteColumn};
 
-/// A view into one a [`Column`]
+/// A mutable view into one a [`Column`] of the table `DB`.
+/// Holds a write lock on every shard; use [`Column::insert`] and [`Column::remove`]
+/// to write single cells without blocking the other shards.
 #[derive(Debug)]
-pub struct ColumnViewMut<'a, T> {
-    column_guard: WriteColumn<'a, T>,
+pub struct ColumnViewMut<'a, T, DB> {
+    source: &'a Column<T>,
+    column: ColumnCollection<WriteColumn<'a, T>>,
+    _table: PhantomData<fn() -> DB>,
 }
 
-impl<'a, T> ColumnViewMut<'a, T> {
-    pub async fn new<DB>(db: &'a DB) -> ColumnViewMut<'a, T>
+impl<'a, T, DB> ColumnViewMut<'a, T, DB> {
+    pub async fn new(db: &'a DB) -> ColumnViewMut<'a, T, DB>
     where
         T: 'a,
         DB: BorrowColumn<T>,
     {
-        let column = db.borrow();
-        let column_guard = column.write().await;
-        ColumnViewMut { column_guard }
+        let source = db.borrow();
+        source.refresh().await;
+        let column = source.shards().write_all().await;
+        ColumnViewMut {
+            source,
+            column,
+            _table: PhantomData,
+        }
     }
 
-    pub fn column(&self) -> &ColumnCollection<T> {
-        self.column_guard.deref()
+    /// Like [`ColumnViewMut::new`], but returns `None` instead of waiting for another view
+    /// of the column to be dropped
+    pub async fn try_new(db: &'a DB) -> Option<ColumnViewMut<'a, T, DB>>
+    where
+        T: 'a,
+        DB: BorrowColumn<T>,
+    {
+        let source = db.borrow();
+        source.refresh().await;
+        let column = source.shards().try_write_all()?;
+        Some(ColumnViewMut {
+            source,
+            column,
+            _table: PhantomData,
+        })
+    }
+
+    pub fn column(&self) -> &ColumnCollection<WriteColumn<'a, T>> {
+        &self.column
+    }
+
+    /// The [`Column`] this view was created from
+    pub fn source(&self) -> &'a Column<T> {
+        self.source
     }
 
-    pub fn column_mut(&mut self) -> &mut ColumnCollection<T> {
-        self.column_guard.deref_mut()
+    /// Inserts a cell if it satisfies the column's constraints, notifying subscribers of the column.
+    /// Returns the previous value of the cell if there was one.
+    pub fn insert(&mut self, key: Key<DB>, value: T) -> Result<Option<T>, DatabaseError> {
+        let key = key.untagged();
+        self.source.check(&self.column, key, &value)?;
+        Ok(self
+            .source
+            .insert_locked(self.column.shard_mut(&key), key, value))
+    }
+
+    /// Removes a cell, notifying subscribers of the column.
+    pub fn remove(&mut self, key: &Key<DB>) -> Option<T> {
+        let key = key.untagged();
+        self.source.remove_locked(self.column.shard_mut(&key), &key)
+    }
+
+    /// Puts back the value returned by a previous [`insert`](Self::insert) or [`remove`](Self::remove),
+    /// undoing it without checking constraints
+    pub fn restore(&mut self, key: Key<DB>, prev: Option<T>) {
+        let key = key.untagged();
+        self.source
+            .restore_locked(self.column.shard_mut(&key), key, prev)
     }
 }
 
-impl<'a, T> Deref for ColumnViewMut<'a, T> {
-    type Target = ColumnCollection<T>;
+impl_shard_reads!(ColumnViewMut);
+
+impl<'a, T, DB> Deref for ColumnViewMut<'a, T, DB> {
+    type Target = ColumnCollection<WriteColumn<'a, T>>;
 
     fn deref(&self) -> &Self::Target {
         self.column()
     }
 }
-
-impl<'a, T> DerefMut for ColumnViewMut<'a, T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        self.column_mut()
-    }
-}
\ No newline at end of file
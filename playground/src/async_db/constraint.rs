@@ -0,0 +1,297 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use super::{ColumnCollection, Key, WriteColumn};
+
+/// A rule that every cell of a [`Column`] must satisfy.
+///
+/// Checked by [`Column::insert`] and [`ColumnViewMut::insert`] before a cell is written.
+/// Columns with constraints can't be viewed through a [`CellViewMut`], whose edits couldn't be rejected.
+pub trait Constraint<T>: Send + Sync {
+    /// Human-readable description used in error messages
+    fn describe(&self) -> String;
+
+    /// Returns true if `value` may be written to the cell `key` of `column`
+    fn check(&self, column: &ColumnCollection<WriteColumn<'_, T>>, key: Key, value: &T) -> bool;
+
+    /// Called after the cell `key` has been written, or removed if `value` is `None`
+    fn written(&self, _key: Key, _value: Option<&T>) {}
+}
+
+/// Disallows two cells of the same column from holding equal values.
+///
+/// Keeps an index from value hashes to keys, so should be added before the column is populated.
+/// Cells with a matching hash are then compared by value.
+#[derive(Debug, Default)]
+pub struct Unique {
+    index: Mutex<UniqueIndex>,
+}
+
+#[derive(Debug, Default)]
+struct UniqueIndex {
+    by_hash: HashMap<u64, BTreeSet<Key>>,
+    by_key: HashMap<Key, u64>,
+}
+
+impl Unique {
+    fn hash<T>(value: &T) -> u64
+    where
+        T: Hash,
+    {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<T> Constraint<T> for Unique
+where
+    T: Hash + Eq,
+{
+    fn describe(&self) -> String {
+        "unique".into()
+    }
+
+    fn check(&self, column: &ColumnCollection<WriteColumn<'_, T>>, key: Key, value: &T) -> bool {
+        let index = self.index.lock().unwrap();
+        let candidates = match index.by_hash.get(&Self::hash(value)) {
+            Some(candidates) => candidates,
+            None => return true,
+        };
+
+        // Hash collisions are resolved by comparing against the stored value.
+        // A cell that's currently locked is conservatively treated as equal.
+        !candidates
+            .iter()
+            .filter(|candidate| **candidate != key)
+            .any(|candidate| match column.get(candidate) {
+                Some(cell) => cell.try_read().is_none_or(|cell| *cell == *value),
+                None => false,
+            })
+    }
+
+    fn written(&self, key: Key, value: Option<&T>) {
+        let mut index = self.index.lock().unwrap();
+
+        if let Some(hash) = index.by_key.remove(&key) {
+            if let Some(keys) = index.by_hash.get_mut(&hash) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    index.by_hash.remove(&hash);
+                }
+            }
+        }
+
+        if let Some(value) = value {
+            let hash = Self::hash(value);
+            index.by_key.insert(key, hash);
+            index.by_hash.entry(hash).or_default().insert(key);
+        }
+    }
+}
+
+/// Disallows `None` in a column of optional values
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NotNull;
+
+impl<T> Constraint<Option<T>> for NotNull {
+    fn describe(&self) -> String {
+        "not null".into()
+    }
+
+    fn check(
+        &self,
+        _: &ColumnCollection<WriteColumn<'_, Option<T>>>,
+        _: Key,
+        value: &Option<T>,
+    ) -> bool {
+        value.is_some()
+    }
+}
+
+/// Rejects every checked write, for columns that are only written by replication
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ReadOnly;
+
+impl<T> Constraint<T> for ReadOnly {
+    fn describe(&self) -> String {
+        "read only".into()
+    }
+
+    fn check(&self, _: &ColumnCollection<WriteColumn<'_, T>>, _: Key, _: &T) -> bool {
+        false
+    }
+}
+
+/// An arbitrary named predicate over cell values
+pub struct Check<F> {
+    name: String,
+    predicate: F,
+}
+
+impl<F> Check<F> {
+    pub fn new(name: impl Into<String>, predicate: F) -> Self {
+        Check {
+            name: name.into(),
+            predicate,
+        }
+    }
+}
+
+impl<T, F> Constraint<T> for Check<F>
+where
+    F: Fn(&T) -> bool + Send + Sync,
+{
+    fn describe(&self) -> String {
+        format!("check '{}'", self.name)
+    }
+
+    fn check(&self, _: &ColumnCollection<WriteColumn<'_, T>>, _: Key, value: &T) -> bool {
+        (self.predicate)(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use super::*;
+    use crate::async_db::{
+        CellView, CellViewMut, Column, ColumnView, ColumnViewMut, DatabaseError, IntFloatCharRow,
+        Row,
+    };
+
+    struct ConstrainedTable {
+        ints: Column<i32>,
+        floats: Column<f32>,
+        chars: Column<char>,
+        names: Column<Option<String>>,
+    }
+
+    impl ConstrainedTable {
+        fn new() -> Self {
+            ConstrainedTable {
+                ints: Column::default().with_constraint(Unique::default()),
+                floats: Column::default()
+                    .with_constraint(Check::new("positive", |float: &f32| *float > 0.0)),
+                chars: Column::default().with_constraint(Unique::default()),
+                names: Column::default().with_constraint(NotNull),
+            }
+        }
+    }
+
+    impl Borrow<Column<i32>> for ConstrainedTable {
+        fn borrow(&self) -> &Column<i32> {
+            &self.ints
+        }
+    }
+
+    impl Borrow<Column<f32>> for ConstrainedTable {
+        fn borrow(&self) -> &Column<f32> {
+            &self.floats
+        }
+    }
+
+    impl Borrow<Column<char>> for ConstrainedTable {
+        fn borrow(&self) -> &Column<char> {
+            &self.chars
+        }
+    }
+
+    impl Borrow<Column<Option<String>>> for ConstrainedTable {
+        fn borrow(&self) -> &Column<Option<String>> {
+            &self.names
+        }
+    }
+
+    #[async_std::test]
+    async fn unique() {
+        let table = ConstrainedTable::new();
+
+        IntFloatCharRow::insert(&table, Key::new(0), (1, 1.0, 'a'))
+            .await
+            .unwrap();
+
+        let result = IntFloatCharRow::insert(&table, Key::new(1), (1, 1.0, 'b')).await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ConstraintViolation { .. })
+        ));
+
+        // Overwriting a row with its own values doesn't conflict with itself
+        IntFloatCharRow::insert(&table, Key::new(0), (1, 2.0, 'a'))
+            .await
+            .unwrap();
+
+        // Freed values can be reused
+        IntFloatCharRow::remove(&table, Key::new(0)).await;
+        IntFloatCharRow::insert(&table, Key::new(1), (1, 1.0, 'b'))
+            .await
+            .unwrap();
+    }
+
+    #[async_std::test]
+    async fn unique_hash_collisions() {
+        // Every value hashes the same, so only equality tells them apart
+        #[derive(Debug, PartialEq, Eq)]
+        struct Collides(i32);
+
+        impl Hash for Collides {
+            fn hash<H: Hasher>(&self, _: &mut H) {}
+        }
+
+        let column = Column::default().with_constraint(Unique::default());
+        column.insert(Key::new(0), Collides(1)).await.unwrap();
+        column.insert(Key::new(1), Collides(2)).await.unwrap();
+        assert!(matches!(
+            column.insert(Key::new(2), Collides(1)).await,
+            Err(DatabaseError::ConstraintViolation { .. })
+        ));
+    }
+
+    #[async_std::test]
+    async fn failed_insert_rolls_back() {
+        let table = ConstrainedTable::new();
+
+        IntFloatCharRow::insert(&table, Key::new(0), (1, 1.0, 'a'))
+            .await
+            .unwrap();
+
+        // The char column fails after ints and floats have been written
+        let result = IntFloatCharRow::insert(&table, Key::new(1), (2, 2.0, 'a')).await;
+        assert!(result.is_err());
+        assert_eq!(IntFloatCharRow::keys(&table).await.len(), 1);
+
+        // Previous values are restored when overwriting an existing row fails
+        let result = IntFloatCharRow::insert(&table, Key::new(0), (3, -1.0, 'c')).await;
+        assert!(result.is_err());
+        assert_eq!(*CellView::<i32>::new(&table, Key::new(0)).await, 1);
+        assert_eq!(*CellView::<f32>::new(&table, Key::new(0)).await, 1.0);
+    }
+
+    #[async_std::test]
+    async fn not_null() {
+        let table = ConstrainedTable::new();
+
+        let mut names = ColumnViewMut::<Option<String>, _>::new(&table).await;
+        assert!(names.insert(Key::new(0), Some("Foo".into())).is_ok());
+        assert!(names.insert(Key::new(1), None).is_err());
+        drop(names);
+
+        assert_eq!(ColumnView::<Option<String>, _>::new(&table).await.len(), 1);
+    }
+
+    #[async_std::test]
+    #[should_panic(expected = "has constraints")]
+    async fn no_mutable_cells_on_constrained_columns() {
+        let table = ConstrainedTable::new();
+
+        IntFloatCharRow::insert(&table, Key::new(0), (1, 1.0, 'a'))
+            .await
+            .unwrap();
+        *CellViewMut::<i32>::new(&table, Key::new(0)).await = 2;
+    }
+}
@@ -0,0 +1,587 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+};
+
+use async_trait::async_trait;
+use futures::future::LocalBoxFuture;
+
+use super::{
+    ApplyPatch, BorrowColumn, CellViewMut, ColumnView, ColumnViewMut, DatabaseError, ForeignKey,
+    Key, PatchPolicy, Row, Table, TableOperation,
+};
+
+/// What to do with referencing rows when a referenced row is removed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OnDelete {
+    /// Remove the referencing rows too
+    Cascade,
+    /// Refuse to remove a row that is still referenced
+    Restrict,
+    /// Null out the foreign keys that point at the removed row
+    SetNull,
+}
+
+/// Object-safe [`Table`] that can be downcast back to its concrete type
+trait AnyTable: Table {
+    fn as_any(&self) -> &dyn Any;
+    fn name(&self) -> &'static str;
+}
+
+impl<T> AnyTable for T
+where
+    T: Table,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &'static str {
+        type_name::<T>()
+    }
+}
+
+/// A collection of named tables and the foreign key relations between them
+#[derive(Default)]
+pub struct Database {
+    tables: HashMap<TypeId, Box<dyn AnyTable>>,
+    names: HashMap<String, TypeId>,
+    relations: Vec<Box<dyn Relation>>,
+}
+
+impl std::fmt::Debug for Database {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Database")
+            .field("names", &self.names)
+            .field("relations", &self.relations.len())
+            .finish()
+    }
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `table` under `name`, replacing any existing table of the same type
+    pub fn add_table<T>(&mut self, name: impl Into<String>, table: T)
+    where
+        T: Table,
+    {
+        self.names
+            .retain(|_, type_id| *type_id != TypeId::of::<T>());
+        self.names.insert(name.into(), TypeId::of::<T>());
+        self.tables.insert(TypeId::of::<T>(), Box::new(table));
+    }
+
+    pub fn table<T>(&self) -> Option<&T>
+    where
+        T: Table,
+    {
+        self.tables
+            .get(&TypeId::of::<T>())
+            .and_then(|table| table.as_any().downcast_ref::<T>())
+    }
+
+    pub fn table_by_name<T>(&self, name: &str) -> Option<&T>
+    where
+        T: Table,
+    {
+        let type_id = self.names.get(name)?;
+        self.tables
+            .get(type_id)
+            .and_then(|table| table.as_any().downcast_ref::<T>())
+    }
+
+    pub fn table_names(&self) -> impl Iterator<Item = &str> {
+        self.names.keys().map(String::as_str)
+    }
+
+    /// Declares that the `Column<ForeignKey<To>>` of table `From` references rows of table `To`
+    pub fn add_foreign_key<From, To>(&mut self, on_delete: OnDelete)
+    where
+        From: Table + BorrowColumn<ForeignKey<To>>,
+        To: Table,
+    {
+        self.relations
+            .push(Box::new(ForeignKeyRelation::<From, To> {
+                on_delete,
+                _phantom: PhantomData,
+            }));
+    }
+
+    /// Inserts a row into table `T`, overwriting any cells that already exist.
+    ///
+    /// Foreign keys are checked against the incoming row, so nothing is written if it violates one.
+    pub async fn insert<'a, T, R>(
+        &'a self,
+        key: Key<T>,
+        row: R::Insert,
+    ) -> Result<(), DatabaseError>
+    where
+        T: Table,
+        R: Row<'a, T>,
+    {
+        let table = self.get_table::<T>()?;
+        let patch = R::Patch::from(row);
+
+        for relation in self.relations_from(TypeId::of::<T>()) {
+            match patch.field(relation.column()) {
+                Some(value) => {
+                    if let Some(target) = relation.target(value) {
+                        relation.check_target(self, key.untagged(), target).await?;
+                    }
+                }
+                // The row leaves the foreign key as it is
+                None => relation.check(self, key.untagged()).await?,
+            }
+        }
+
+        self.run_before(TypeId::of::<T>(), TableOperation::Insert, key.untagged())?;
+        patch.apply(table, key, PatchPolicy::CreateMissing).await?;
+        self.run_after(TypeId::of::<T>(), TableOperation::Insert, key.untagged());
+        Ok(())
+    }
+
+    /// Overwrites an existing cell of table `T`, subject to the column's constraints
+    pub async fn update<T, V>(&self, key: Key<T>, value: V) -> Result<Option<V>, DatabaseError>
+    where
+        T: Table + BorrowColumn<V>,
+    {
+        let table = self.get_table::<T>()?;
+        self.run_before(TypeId::of::<T>(), TableOperation::Update, key.untagged())?;
+
+        let prev = {
+            let mut column = ColumnViewMut::<V, T>::new(table).await;
+            if !column.contains_key(&key) {
+                return Err(DatabaseError::MissingRow {
+                    table: type_name::<T>(),
+                    key: key.untagged(),
+                });
+            }
+            column.insert(key, value)?
+        };
+
+        self.run_after(TypeId::of::<T>(), TableOperation::Update, key.untagged());
+        Ok(prev)
+    }
+
+    /// Removes the row `key` from table `T`, applying the `OnDelete` policy of each relation that references it.
+    ///
+    /// Restrictions are checked for the whole cascade before anything is written.
+    pub async fn remove<T>(&self, key: Key<T>) -> Result<(), DatabaseError>
+    where
+        T: Table,
+    {
+        self.get_table::<T>()?;
+
+        let mut plan = RemovePlan::default();
+        self.plan_remove(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            key.untagged(),
+            &mut plan,
+        )
+        .await?;
+
+        for (relation, key) in &plan.set_null {
+            let relation = &self.relations[*relation];
+            self.run_before(relation.referencing(), TableOperation::Update, *key)?;
+        }
+
+        for (type_id, key) in &plan.remove {
+            self.run_before(*type_id, TableOperation::Remove, *key)?;
+        }
+
+        for (relation, key) in plan.set_null {
+            let relation = &self.relations[relation];
+            relation.set_null(self, key).await;
+            self.run_after(relation.referencing(), TableOperation::Update, key);
+        }
+
+        for (type_id, key) in plan.remove {
+            self.tables[&type_id].remove_key(key).await;
+            self.run_after(type_id, TableOperation::Remove, key);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn run_before(
+        &self,
+        type_id: TypeId,
+        operation: TableOperation,
+        key: Key,
+    ) -> Result<(), DatabaseError> {
+        let table = &self.tables[&type_id];
+        match table.hooks() {
+            Some(hooks) => {
+                hooks
+                    .run_before(operation, key)
+                    .map_err(|message| DatabaseError::HookRejected {
+                        table: table.name(),
+                        key,
+                        operation,
+                        message,
+                    })
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn run_after(&self, type_id: TypeId, operation: TableOperation, key: Key) {
+        if let Some(hooks) = self.tables[&type_id].hooks() {
+            hooks.run_after(operation, key);
+        }
+    }
+
+    fn get_table<T>(&self) -> Result<&T, DatabaseError>
+    where
+        T: Table,
+    {
+        self.table::<T>()
+            .ok_or_else(|| DatabaseError::MissingTable(type_name::<T>()))
+    }
+
+    pub(crate) fn relations_from(&self, type_id: TypeId) -> impl Iterator<Item = &dyn Relation> {
+        self.relations
+            .iter()
+            .map(Box::as_ref)
+            .filter(move |relation| relation.referencing() == type_id)
+    }
+
+    /// Returns true if a foreign key of any table references table `type_id`
+    pub(crate) fn is_referenced(&self, type_id: TypeId) -> bool {
+        self.relations
+            .iter()
+            .any(|relation| relation.referenced() == type_id)
+    }
+
+    fn plan_remove<'a>(
+        &'a self,
+        type_id: TypeId,
+        name: &'static str,
+        key: Key,
+        plan: &'a mut RemovePlan,
+    ) -> LocalBoxFuture<'a, Result<(), DatabaseError>> {
+        Box::pin(async move {
+            if plan.remove.contains(&(type_id, key)) {
+                return Ok(());
+            }
+            plan.remove.push((type_id, key));
+
+            for (i, relation) in self.relations.iter().enumerate() {
+                if relation.referenced() != type_id {
+                    continue;
+                }
+
+                for referencing_key in relation.referencing_keys(self, key).await {
+                    match relation.on_delete() {
+                        OnDelete::Restrict => {
+                            return Err(DatabaseError::RestrictedRemove {
+                                table: name,
+                                key,
+                                referencing_table: relation.referencing_name(),
+                                referencing_key,
+                            })
+                        }
+                        OnDelete::Cascade => {
+                            self.plan_remove(
+                                relation.referencing(),
+                                relation.referencing_name(),
+                                referencing_key,
+                                plan,
+                            )
+                            .await?
+                        }
+                        OnDelete::SetNull => plan.set_null.push((i, referencing_key)),
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// The set of writes a [`Database::remove`] call will perform
+#[derive(Debug, Default)]
+struct RemovePlan {
+    remove: Vec<(TypeId, Key)>,
+    set_null: Vec<(usize, Key)>,
+}
+
+/// Type-erased foreign key relation between two tables
+#[async_trait(?Send)]
+pub(crate) trait Relation: Send + Sync {
+    fn referencing(&self) -> TypeId;
+    fn referencing_name(&self) -> &'static str;
+    fn referenced(&self) -> TypeId;
+    fn on_delete(&self) -> OnDelete;
+
+    /// The `TypeId` of the foreign key column, `ForeignKey<To>`
+    fn column(&self) -> TypeId;
+
+    /// Returns the non-null target of a foreign key value, given as a `ForeignKey<To>`
+    fn target(&self, value: &dyn Any) -> Option<Key>;
+
+    /// Checks that row `key` of the referencing table points at an existing row
+    async fn check(&self, db: &Database, key: Key) -> Result<(), DatabaseError>;
+
+    /// Checks that `target`, the foreign key of referencing row `key`, is a row of the referenced table
+    async fn check_target(&self, db: &Database, key: Key, target: Key)
+        -> Result<(), DatabaseError>;
+
+    /// Returns the referencing keys and non-null targets of pending writes to the foreign key
+    /// column, given as a `BTreeMap<Key<From>, Option<ForeignKey<To>>>`.
+    /// Writes to any other column have no targets.
+    fn written_targets(&self, writes: &dyn Any) -> Vec<(Key, Key)>;
+
+    /// Returns the keys of referencing rows that point at row `key` of the referenced table
+    async fn referencing_keys(&self, db: &Database, key: Key) -> Vec<Key>;
+
+    /// Nulls out the foreign key of referencing row `key`
+    async fn set_null(&self, db: &Database, key: Key);
+}
+
+struct ForeignKeyRelation<From, To> {
+    on_delete: OnDelete,
+    _phantom: PhantomData<fn() -> (From, To)>,
+}
+
+#[async_trait(?Send)]
+impl<From, To> Relation for ForeignKeyRelation<From, To>
+where
+    From: Table + BorrowColumn<ForeignKey<To>>,
+    To: Table,
+{
+    fn referencing(&self) -> TypeId {
+        TypeId::of::<From>()
+    }
+
+    fn referencing_name(&self) -> &'static str {
+        type_name::<From>()
+    }
+
+    fn referenced(&self) -> TypeId {
+        TypeId::of::<To>()
+    }
+
+    fn on_delete(&self) -> OnDelete {
+        self.on_delete
+    }
+
+    fn column(&self) -> TypeId {
+        TypeId::of::<ForeignKey<To>>()
+    }
+
+    fn target(&self, value: &dyn Any) -> Option<Key> {
+        let value = value.downcast_ref::<ForeignKey<To>>()?;
+        value.key().map(Key::untagged)
+    }
+
+    async fn check(&self, db: &Database, key: Key) -> Result<(), DatabaseError> {
+        let from = db.get_table::<From>()?;
+
+        let target_key = {
+            let column = ColumnView::<ForeignKey<To>, From>::new(from).await;
+            match column.get(&key.cast()) {
+                Some(cell) => cell.read().await.key(),
+                None => None,
+            }
+        };
+
+        match target_key {
+            Some(target_key) => self.check_target(db, key, target_key.untagged()).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn check_target(
+        &self,
+        db: &Database,
+        key: Key,
+        target: Key,
+    ) -> Result<(), DatabaseError> {
+        if db.get_table::<To>()?.contains_key(target).await {
+            Ok(())
+        } else {
+            Err(DatabaseError::ForeignKeyViolation {
+                table: type_name::<From>(),
+                key,
+                target_table: type_name::<To>(),
+                target_key: target,
+            })
+        }
+    }
+
+    fn written_targets(&self, writes: &dyn Any) -> Vec<(Key, Key)> {
+        match writes.downcast_ref::<BTreeMap<Key<From>, Option<ForeignKey<To>>>>() {
+            Some(writes) => writes
+                .iter()
+                .filter_map(|(key, write)| {
+                    let target = write.as_ref()?.key()?;
+                    Some((key.untagged(), target.untagged()))
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    async fn referencing_keys(&self, db: &Database, key: Key) -> Vec<Key> {
+        let from = match db.table::<From>() {
+            Some(from) => from,
+            None => return vec![],
+        };
+
+        let column = ColumnView::<ForeignKey<To>, From>::new(from).await;
+        let mut keys = vec![];
+        for (referencing_key, cell) in column.iter() {
+            if cell.read().await.key() == Some(key.cast()) {
+                keys.push(referencing_key.untagged());
+            }
+        }
+        keys
+    }
+
+    async fn set_null(&self, db: &Database, key: Key) {
+        if let Some(from) = db.table::<From>() {
+            if ColumnView::<ForeignKey<To>, From>::new(from)
+                .await
+                .contains_key(&key.cast())
+            {
+                CellViewMut::<ForeignKey<To>>::new(from, key.cast())
+                    .await
+                    .set_null();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::async_db::{IntFloatCharRow, MyRefTable, MyTable, NamedRefRow};
+
+    async fn database(on_delete: OnDelete) -> Database {
+        let mut db = Database::new();
+        db.add_table("my_table", MyTable::new().await);
+        db.add_table("my_ref_table", MyRefTable::default());
+        db.add_foreign_key::<MyRefTable, MyTable>(on_delete);
+
+        db.insert::<MyRefTable, NamedRefRow>(Key::new(0), ("Zero", ForeignKey::new(Key::new(0))))
+            .await
+            .unwrap();
+        db.insert::<MyRefTable, NamedRefRow>(Key::new(1), ("Three", ForeignKey::new(Key::new(3))))
+            .await
+            .unwrap();
+
+        db
+    }
+
+    #[async_std::test]
+    async fn tables_by_name_and_type() {
+        let db = database(OnDelete::Restrict).await;
+        assert!(db.table::<MyTable>().is_some());
+        assert!(db.table_by_name::<MyRefTable>("my_ref_table").is_some());
+        assert!(db.table_by_name::<MyTable>("my_ref_table").is_none());
+    }
+
+    #[async_std::test]
+    async fn insert_checks_foreign_keys() {
+        let db = database(OnDelete::Restrict).await;
+
+        // Row 1 of MyTable was removed in MyTable::new
+        let result = db
+            .insert::<MyRefTable, NamedRefRow>(Key::new(2), ("One", ForeignKey::new(Key::new(1))))
+            .await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ForeignKeyViolation { .. })
+        ));
+        assert!(
+            !db.table::<MyRefTable>()
+                .unwrap()
+                .contains_key(Key::new(2))
+                .await
+        );
+
+        db.insert::<MyRefTable, NamedRefRow>(Key::new(2), ("Null", ForeignKey::null()))
+            .await
+            .unwrap();
+    }
+
+    #[async_std::test]
+    async fn failed_insert_keeps_existing_row() {
+        let db = database(OnDelete::Restrict).await;
+
+        let result = db
+            .insert::<MyRefTable, NamedRefRow>(Key::new(0), ("One", ForeignKey::new(Key::new(1))))
+            .await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ForeignKeyViolation { .. })
+        ));
+
+        let row = NamedRefRow::new(db.table::<MyRefTable>().unwrap(), Key::new(0)).await;
+        assert_eq!(*row.name, "Zero");
+        assert_eq!((*row.target).key(), Some(Key::new(0)));
+    }
+
+    #[async_std::test]
+    async fn remove_restrict() {
+        let db = database(OnDelete::Restrict).await;
+
+        let result = db.remove::<MyTable>(Key::new(0)).await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::RestrictedRemove { .. })
+        ));
+        assert!(
+            db.table::<MyTable>()
+                .unwrap()
+                .contains_key(Key::new(0))
+                .await
+        );
+
+        db.remove::<MyTable>(Key::new(2)).await.unwrap();
+        assert!(
+            !db.table::<MyTable>()
+                .unwrap()
+                .contains_key(Key::new(2))
+                .await
+        );
+    }
+
+    #[async_std::test]
+    async fn remove_cascade() {
+        let db = database(OnDelete::Cascade).await;
+
+        db.remove::<MyTable>(Key::new(3)).await.unwrap();
+
+        let refs = db.table::<MyRefTable>().unwrap();
+        assert!(refs.contains_key(Key::new(0)).await);
+        assert!(!refs.contains_key(Key::new(1)).await);
+        assert_eq!(
+            IntFloatCharRow::common_keys(db.table::<MyTable>().unwrap())
+                .collect::<Vec<_>>()
+                .await
+                .len(),
+            2
+        );
+    }
+
+    #[async_std::test]
+    async fn remove_set_null() {
+        let db = database(OnDelete::SetNull).await;
+
+        db.remove::<MyTable>(Key::new(3)).await.unwrap();
+
+        let refs = db.table::<MyRefTable>().unwrap();
+        let row = NamedRefRow::new(refs, Key::new(1)).await;
+        assert!(row.target.is_null());
+    }
+}
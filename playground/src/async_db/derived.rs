@@ -0,0 +1,267 @@
+use std::{
+    collections::BTreeSet,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_std::channel::Receiver;
+use async_trait::async_trait;
+
+use super::{Column, ColumnEvent, ColumnEvents, Key, Shards};
+
+/// A function from the cells of one or more source columns to the cell of a derived column
+#[async_trait(?Send)]
+pub trait Derivation<T>: Send + Sync {
+    /// Returns the keys whose sources have changed since the last call
+    async fn dirty(&self) -> BTreeSet<Key>;
+
+    /// Computes the derived cell for `key`, or `None` if any of its sources are missing
+    async fn compute(&self, key: Key) -> Option<T>;
+
+    /// Returns a receiver for the events of each source column
+    fn subscribe(&self) -> Vec<Receiver<ColumnEvent>>;
+}
+
+/// A tuple of source column references that can drive a [`Derivation`] using `F`
+pub trait DeriveSources<T, F> {
+    fn derivation(self, f: F) -> Box<dyn Derivation<T>>;
+}
+
+impl<T> Column<T> {
+    /// Creates a column whose cells are kept up to date as a function of `sources`.
+    ///
+    /// Cells are recomputed incrementally for each key inserted, updated or removed in a source,
+    /// the next time the derived column is viewed. See [`Column::refresh`].
+    pub fn derived<S, F>(sources: S, f: F) -> Self
+    where
+        S: DeriveSources<T, F>,
+    {
+        Column::from_derivation(sources.derivation(f))
+    }
+}
+
+/// A source column of a derivation
+struct Source<T> {
+    shards: Arc<Shards<T>>,
+    column_events: Arc<ColumnEvents>,
+}
+
+impl<T> Source<T> {
+    /// Watches `column`, adding the keys it changes to `dirty`
+    fn new(column: &Column<T>, dirty: &Arc<Mutex<BTreeSet<Key>>>) -> Self {
+        column.events().watch(dirty);
+        Source {
+            shards: column.shards_handle(),
+            column_events: column.events_handle(),
+        }
+    }
+
+    async fn keys(&self, dirty: &mut BTreeSet<Key>) {
+        for shard in self.shards.iter() {
+            dirty.extend(shard.read().await.keys().copied());
+        }
+    }
+}
+
+/// A derivation over a tuple of [`Source`]s
+struct Derived<S, F> {
+    sources: S,
+    f: F,
+    // Keys changed in any source since the last call to `dirty`
+    dirty: Arc<Mutex<BTreeSet<Key>>>,
+    // Cells that already exist in the sources need computing on the first refresh
+    initialized: AtomicBool,
+}
+
+macro_rules! impl_derive_sources {
+    ($(($t:ident, $source:ident, $value:ident)),*) => {
+        impl<'a, T, F, $($t),*> DeriveSources<T, F> for ($(&'a Column<$t>,)*)
+        where
+            T: 'static,
+            F: Fn($(&$t),*) -> T + Send + Sync + 'static,
+            $($t: Send + Sync + 'static),*
+        {
+            fn derivation(self, f: F) -> Box<dyn Derivation<T>> {
+                let ($($source,)*) = self;
+                let dirty = Arc::default();
+                Box::new(Derived {
+                    sources: ($(Source::new($source, &dirty),)*),
+                    f,
+                    dirty,
+                    initialized: AtomicBool::new(false),
+                })
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl<T, F, $($t),*> Derivation<T> for Derived<($(Source<$t>,)*), F>
+        where
+            F: Fn($(&$t),*) -> T + Send + Sync,
+            $($t: Send + Sync),*
+        {
+            async fn dirty(&self) -> BTreeSet<Key> {
+                let ($($source,)*) = &self.sources;
+
+                let mut dirty = mem::take(&mut *self.dirty.lock().unwrap());
+
+                if !self.initialized.swap(true, Ordering::AcqRel) {
+                    $($source.keys(&mut dirty).await;)*
+                }
+
+                dirty
+            }
+
+            async fn compute(&self, key: Key) -> Option<T> {
+                let ($($source,)*) = &self.sources;
+                $(let $source = $source.shards.shard(&key).read().await;)*
+                $(let $value = $source.get(&key)?.read().await;)*
+                Some((self.f)($(&*$value),*))
+            }
+
+            fn subscribe(&self) -> Vec<Receiver<ColumnEvent>> {
+                let ($($source,)*) = &self.sources;
+                vec![$($source.column_events.subscribe()),*]
+            }
+        }
+    };
+}
+
+impl_derive_sources!((A, a, a_value));
+impl_derive_sources!((A, a, a_value), (B, b, b_value));
+impl_derive_sources!((A, a, a_value), (B, b, b_value), (C, c, c_value));
+impl_derive_sources!(
+    (A, a, a_value),
+    (B, b, b_value),
+    (C, c, c_value),
+    (D, d, d_value)
+);
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use super::*;
+    use crate::async_db::{CellView, CellViewMut, ColumnView, ColumnViewMut};
+
+    struct DerivedTable {
+        ints: Column<i32>,
+        floats: Column<f32>,
+        chars: Column<char>,
+        strings: Column<String>,
+    }
+
+    impl DerivedTable {
+        fn new() -> Self {
+            let ints = Column::default();
+            let floats = Column::default();
+            let chars = Column::derived((&ints,), |int: &i32| (*int as u8).into());
+            let strings = Column::derived((&ints, &floats), |int: &i32, float: &f32| {
+                format!("{}, {}", int, float)
+            });
+
+            DerivedTable {
+                ints,
+                floats,
+                chars,
+                strings,
+            }
+        }
+    }
+
+    impl Borrow<Column<i32>> for DerivedTable {
+        fn borrow(&self) -> &Column<i32> {
+            &self.ints
+        }
+    }
+
+    impl Borrow<Column<f32>> for DerivedTable {
+        fn borrow(&self) -> &Column<f32> {
+            &self.floats
+        }
+    }
+
+    impl Borrow<Column<char>> for DerivedTable {
+        fn borrow(&self) -> &Column<char> {
+            &self.chars
+        }
+    }
+
+    impl Borrow<Column<String>> for DerivedTable {
+        fn borrow(&self) -> &Column<String> {
+            &self.strings
+        }
+    }
+
+    #[async_std::test]
+    async fn derived_from_one_column() {
+        let table = DerivedTable::new();
+
+        ColumnViewMut::<i32, _>::new(&table)
+            .await
+            .insert(Key::new(0), 65)
+            .unwrap();
+        assert_eq!(*CellView::<char>::new(&table, Key::new(0)).await, 'A');
+
+        *CellViewMut::<i32>::new(&table, Key::new(0)).await = 66;
+        assert_eq!(*CellView::<char>::new(&table, Key::new(0)).await, 'B');
+
+        ColumnViewMut::<i32, _>::new(&table)
+            .await
+            .remove(&Key::new(0));
+        assert!(!ColumnView::<char, _>::new(&table)
+            .await
+            .contains_key(&Key::new(0)));
+    }
+
+    #[async_std::test]
+    async fn refresh_skips_viewed_shards() {
+        let table = DerivedTable::new();
+
+        ColumnViewMut::<i32, _>::new(&table)
+            .await
+            .insert(Key::new(0), 65)
+            .unwrap();
+        let view = CellView::<char>::new(&table, Key::new(0)).await;
+        *CellViewMut::<i32>::new(&table, Key::new(0)).await = 66;
+
+        // Viewing the cell again doesn't wait for the view already held
+        let again = CellView::<char>::new(&table, Key::new(0)).await;
+        assert_eq!((*view, *again), ('A', 'A'));
+        drop((view, again));
+
+        assert_eq!(*CellView::<char>::new(&table, Key::new(0)).await, 'B');
+    }
+
+    #[async_std::test]
+    async fn derived_from_many_columns() {
+        let table = DerivedTable::new();
+
+        ColumnViewMut::<i32, _>::new(&table)
+            .await
+            .insert(Key::new(0), 1)
+            .unwrap();
+        assert!(ColumnView::<String, _>::new(&table).await.is_empty());
+
+        ColumnViewMut::<f32, _>::new(&table)
+            .await
+            .insert(Key::new(0), 2.5)
+            .unwrap();
+        assert_eq!(
+            *CellView::<String>::new(&table, Key::new(0)).await,
+            "1, 2.5"
+        );
+    }
+
+    #[async_std::test]
+    async fn derived_from_existing_cells() {
+        let ints = Column::<i32>::default();
+        ints.insert(Key::new(0), 67).await.unwrap();
+
+        let chars = Column::<char>::derived((&ints,), |int: &i32| (*int as u8).into());
+        chars.refresh().await;
+        assert_eq!(*CellView::new(&chars, Key::new(0)).await, 'C');
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use super::{Key, TableOperation};
+
+/// Errors produced by [`Database`] operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatabaseError {
+    MissingTable(&'static str),
+    MissingRow {
+        table: &'static str,
+        key: Key,
+    },
+    MissingCell {
+        column: &'static str,
+        key: Key,
+    },
+    DuplicateCell {
+        column: &'static str,
+        key: Key,
+    },
+    ForeignKeyViolation {
+        table: &'static str,
+        key: Key,
+        target_table: &'static str,
+        target_key: Key,
+    },
+    ConstraintViolation {
+        column: &'static str,
+        key: Key,
+        constraint: String,
+    },
+    HookRejected {
+        table: &'static str,
+        key: Key,
+        operation: TableOperation,
+        message: String,
+    },
+    RestrictedRemove {
+        table: &'static str,
+        key: Key,
+        referencing_table: &'static str,
+        referencing_key: Key,
+    },
+    ReferencedRemove {
+        table: &'static str,
+        key: Key,
+    },
+    WaitTimeout {
+        waiting_for: &'static str,
+        key: Key,
+    },
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatabaseError::MissingTable(table) => write!(f, "No table of type {}", table),
+            DatabaseError::MissingRow { table, key } => {
+                write!(f, "No row {:?} in {}", key, table)
+            }
+            DatabaseError::MissingCell { column, key } => {
+                write!(f, "No cell {:?} in column {}", key, column)
+            }
+            DatabaseError::DuplicateCell { column, key } => {
+                write!(f, "Cell {:?} already exists in column {}", key, column)
+            }
+            DatabaseError::WaitTimeout { waiting_for, key } => {
+                write!(f, "Timed out waiting for {:?} of {}", key, waiting_for)
+            }
+            DatabaseError::ConstraintViolation {
+                column,
+                key,
+                constraint,
+            } => write!(
+                f,
+                "Cell {:?} of column {} violates {} constraint",
+                key, column, constraint
+            ),
+            DatabaseError::HookRejected {
+                table,
+                key,
+                operation,
+                message,
+            } => write!(
+                f,
+                "{:?} of row {:?} in {} rejected: {}",
+                operation, key, table, message
+            ),
+            DatabaseError::ForeignKeyViolation {
+                table,
+                key,
+                target_table,
+                target_key,
+            } => write!(
+                f,
+                "Row {:?} of {} references missing row {:?} of {}",
+                key, table, target_key, target_table
+            ),
+            DatabaseError::RestrictedRemove {
+                table,
+                key,
+                referencing_table,
+                referencing_key,
+            } => write!(
+                f,
+                "Can't remove row {:?} of {}: referenced by row {:?} of {}",
+                key, table, referencing_key, referencing_table
+            ),
+            DatabaseError::ReferencedRemove { table, key } => write!(
+                f,
+                "Can't remove row {:?} of {} in a transaction: {} is referenced by foreign keys",
+                key, table, table
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {}
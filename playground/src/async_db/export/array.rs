@@ -0,0 +1,206 @@
+use std::convert::TryFrom;
+
+use super::{Bitmap, DataType, ExportError, Exportable};
+
+/// A column of values packed into contiguous buffers, with a validity bitmap marking the
+/// slots that hold one.
+///
+/// Fixed-width values take one slot each of `values`, zeroed where null.
+/// Variable-width values are located by `len + 1` offsets into `values`, with null slots empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Array {
+    data_type: DataType,
+    validity: Bitmap,
+    offsets: Vec<u32>,
+    values: Vec<u8>,
+}
+
+impl Array {
+    pub fn new(data_type: DataType) -> Self {
+        Array {
+            data_type,
+            validity: Bitmap::new(),
+            offsets: match data_type.width() {
+                Some(_) => vec![],
+                None => vec![0],
+            },
+            values: vec![],
+        }
+    }
+
+    /// Assembles an array from its buffers, or `None` if their lengths don't agree
+    pub fn from_parts(
+        data_type: DataType,
+        validity: Bitmap,
+        offsets: Vec<u32>,
+        values: Vec<u8>,
+    ) -> Option<Self> {
+        let len = validity.len();
+        match data_type.width() {
+            Some(width) if offsets.is_empty() && values.len() == len * width => {}
+            None if offsets.len() == len + 1
+                && offsets.windows(2).all(|pair| pair[0] <= pair[1])
+                && offsets.last().map(|&end| end as usize) == Some(values.len()) => {}
+            _ => return None,
+        }
+
+        Some(Array {
+            data_type,
+            validity,
+            offsets,
+            values,
+        })
+    }
+
+    /// Appends a slot, null if `value` is `None`.
+    ///
+    /// Fails, leaving the array as it was, if a variable-width value would end past the
+    /// last byte a `u32` offset can locate.
+    pub fn push<T>(&mut self, value: Option<&T>) -> Result<(), ExportError>
+    where
+        T: Exportable,
+    {
+        assert_eq!(T::DATA_TYPE, self.data_type, "Wrong type pushed to array");
+
+        let start = self.values.len();
+        match (value, self.data_type.width()) {
+            (Some(value), _) => value.write(&mut self.values),
+            (None, Some(width)) => self.values.resize(self.values.len() + width, 0),
+            (None, None) => {}
+        }
+        if self.data_type.width().is_none() {
+            match u32::try_from(self.values.len()) {
+                Ok(end) => self.offsets.push(end),
+                Err(_) => {
+                    let len = self.values.len();
+                    self.values.truncate(start);
+                    return Err(ExportError::OffsetOverflow(len));
+                }
+            }
+        }
+        self.validity.push(value.is_some());
+        Ok(())
+    }
+
+    /// Reads back the value in slot `index`, or `None` if it's null.
+    /// Panics if `T` isn't the type the array holds.
+    pub fn get<T>(&self, index: usize) -> Option<T>
+    where
+        T: Exportable,
+    {
+        assert_eq!(T::DATA_TYPE, self.data_type, "Wrong type read from array");
+
+        if !self.is_valid(index) {
+            return None;
+        }
+        let bytes = match self.data_type.width() {
+            Some(width) => &self.values[index * width..(index + 1) * width],
+            None => &self.values[self.offsets[index] as usize..self.offsets[index + 1] as usize],
+        };
+        T::read(bytes)
+    }
+
+    pub fn iter<T>(&self) -> impl Iterator<Item = Option<T>> + '_
+    where
+        T: Exportable,
+    {
+        (0..self.len()).map(move |index| self.get(index))
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    pub fn len(&self) -> usize {
+        self.validity.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validity.is_empty()
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.len() - self.validity.count_ones()
+    }
+
+    pub fn is_valid(&self, index: usize) -> bool {
+        self.validity.get(index)
+    }
+
+    pub fn validity(&self) -> &Bitmap {
+        &self.validity
+    }
+
+    /// Offsets of each slot into [`Array::values`], empty for fixed-width types
+    pub fn offsets(&self) -> &[u32] {
+        &self.offsets
+    }
+
+    pub fn values(&self) -> &[u8] {
+        &self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_width_nulls_are_zeroed() {
+        let mut array = Array::new(DataType::Int32);
+        array.push(Some(&1)).unwrap();
+        array.push::<i32>(None).unwrap();
+        array.push(Some(&-3)).unwrap();
+
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.null_count(), 1);
+        assert_eq!(array.values().len(), 12);
+        assert_eq!(&array.values()[4..8], &[0; 4]);
+        assert_eq!(
+            array.iter().collect::<Vec<_>>(),
+            vec![Some(1), None, Some(-3)]
+        );
+    }
+
+    #[test]
+    fn variable_width_slots_are_located_by_offsets() {
+        let mut array = Array::new(DataType::Utf8);
+        array.push(Some(&"ab".to_string())).unwrap();
+        array.push::<String>(None).unwrap();
+        array.push(Some(&String::new())).unwrap();
+        array.push(Some(&"cde".to_string())).unwrap();
+
+        assert_eq!(array.offsets(), &[0, 2, 2, 2, 5]);
+        assert_eq!(array.values(), b"abcde");
+        assert_eq!(
+            array.iter().collect::<Vec<_>>(),
+            vec![
+                Some("ab".to_string()),
+                None,
+                Some(String::new()),
+                Some("cde".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn from_parts_checks_buffer_lengths() {
+        assert!(Bitmap::from_bytes(vec![0b111], 2).is_none());
+        let validity = Bitmap::from_bytes(vec![0b11], 2).unwrap();
+        assert!(Array::from_parts(DataType::Char, validity.clone(), vec![], vec![0; 8]).is_some());
+        assert!(Array::from_parts(DataType::Char, validity.clone(), vec![], vec![0; 7]).is_none());
+        assert!(
+            Array::from_parts(DataType::Utf8, validity.clone(), vec![0, 2, 1], vec![0; 1])
+                .is_none()
+        );
+        assert!(Array::from_parts(DataType::Utf8, validity, vec![0, 1, 3], vec![0; 3]).is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn reading_the_wrong_type_panics() {
+        let mut array = Array::new(DataType::Float32);
+        array.push(Some(&1.0f32)).unwrap();
+        array.get::<i32>(0);
+    }
+}
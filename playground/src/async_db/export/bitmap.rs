@@ -0,0 +1,72 @@
+/// One bit per slot, packed least significant bit first, as in Arrow validity buffers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bitmap {
+    bits: Vec<u8>,
+    len: usize,
+}
+
+impl Bitmap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Wraps `len` bits packed into `bits`, or `None` if `bits` is the wrong length
+    /// or has bits set past the end
+    pub fn from_bytes(bits: Vec<u8>, len: usize) -> Option<Self> {
+        if bits.len() != Self::byte_len(len) {
+            return None;
+        }
+        if let (Some(last), 1..=7) = (bits.last(), len % 8) {
+            if last >> (len % 8) != 0 {
+                return None;
+            }
+        }
+        Some(Bitmap { bits, len })
+    }
+
+    /// Number of bytes needed to hold `len` bits
+    pub fn byte_len(len: usize) -> usize {
+        len.div_ceil(8)
+    }
+
+    pub fn push(&mut self, set: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bits.push(0);
+        }
+        if set {
+            self.bits[self.len / 8] |= 1 << (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(
+            index < self.len,
+            "Bit {} out of range of {}",
+            index,
+            self.len
+        );
+        self.bits[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bits set
+    pub fn count_ones(&self) -> usize {
+        // Bits past the end are never set
+        self.bits
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+}
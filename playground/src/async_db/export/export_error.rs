@@ -0,0 +1,53 @@
+use std::{fmt::Display, io::ErrorKind};
+
+use super::DataType;
+
+/// Errors produced while reading back an export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportError {
+    Io(ErrorKind),
+    Malformed(&'static str),
+    UnsupportedVersion(u16),
+    MissingColumn(String),
+    TypeMismatch {
+        column: String,
+        expected: DataType,
+        found: DataType,
+    },
+    OffsetOverflow(usize),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Io(kind) => write!(f, "Export file access failed: {:?}", kind),
+            ExportError::Malformed(what) => write!(f, "Malformed export {}", what),
+            ExportError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported export format version {}", version)
+            }
+            ExportError::MissingColumn(column) => write!(f, "No exported column {}", column),
+            ExportError::TypeMismatch {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Exported column {} holds {:?}, not {:?}",
+                column, found, expected
+            ),
+            ExportError::OffsetOverflow(len) => write!(
+                f,
+                "Array values of {} bytes can't be located by 32-bit offsets",
+                len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e.kind())
+    }
+}
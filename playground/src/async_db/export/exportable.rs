@@ -0,0 +1,98 @@
+use std::convert::TryInto;
+
+/// The physical type of the values in an [`Array`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DataType {
+    Int32,
+    Float32,
+    Char,
+    Utf8,
+}
+
+impl DataType {
+    /// Bytes per value, or `None` if values vary in length and are located by offsets
+    pub fn width(self) -> Option<usize> {
+        match self {
+            DataType::Int32 | DataType::Float32 | DataType::Char => Some(4),
+            DataType::Utf8 => None,
+        }
+    }
+
+    pub(super) fn tag(self) -> u8 {
+        match self {
+            DataType::Int32 => 0,
+            DataType::Float32 => 1,
+            DataType::Char => 2,
+            DataType::Utf8 => 3,
+        }
+    }
+
+    pub(super) fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(DataType::Int32),
+            1 => Some(DataType::Float32),
+            2 => Some(DataType::Char),
+            3 => Some(DataType::Utf8),
+            _ => None,
+        }
+    }
+}
+
+/// A cell type that can be exported into an [`Array`]
+pub trait Exportable: Sized {
+    const DATA_TYPE: DataType;
+
+    /// Appends the value's little-endian bytes to a values buffer
+    fn write(&self, values: &mut Vec<u8>);
+
+    /// Reads a value from exactly the bytes of its slot
+    fn read(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Exportable for i32 {
+    const DATA_TYPE: DataType = DataType::Int32;
+
+    fn write(&self, values: &mut Vec<u8>) {
+        values.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read(bytes: &[u8]) -> Option<Self> {
+        Some(i32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl Exportable for f32 {
+    const DATA_TYPE: DataType = DataType::Float32;
+
+    fn write(&self, values: &mut Vec<u8>) {
+        values.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read(bytes: &[u8]) -> Option<Self> {
+        Some(f32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl Exportable for char {
+    const DATA_TYPE: DataType = DataType::Char;
+
+    fn write(&self, values: &mut Vec<u8>) {
+        values.extend_from_slice(&(*self as u32).to_le_bytes());
+    }
+
+    fn read(bytes: &[u8]) -> Option<Self> {
+        std::char::from_u32(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl Exportable for String {
+    const DATA_TYPE: DataType = DataType::Utf8;
+
+    fn write(&self, values: &mut Vec<u8>) {
+        values.extend_from_slice(self.as_bytes());
+    }
+
+    fn read(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use itertools::Itertools;
+
+use crate::async_db::{BorrowColumn, Column, ColumnView, Key};
+
+use super::{Array, ExportError, Exportable, RecordBatch};
+
+/// A column selected for export
+#[async_trait(?Send)]
+trait ExportColumn {
+    /// Read-locks the column for as long as the snapshot is held
+    async fn snapshot(&self) -> Box<dyn ColumnSnapshot + '_>;
+}
+
+#[async_trait(?Send)]
+trait ColumnSnapshot {
+    fn keys(&self) -> Vec<Key>;
+
+    /// Copies the cells at `keys` into an array, with a null slot for each missing cell
+    async fn array(&self, keys: &[Key]) -> Result<Array, ExportError>;
+}
+
+#[async_trait(?Send)]
+impl<T> ExportColumn for Column<T>
+where
+    T: Exportable,
+{
+    async fn snapshot(&self) -> Box<dyn ColumnSnapshot + '_> {
+        Box::new(ColumnView::<T, Column<T>>::new(self).await)
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, T> ColumnSnapshot for ColumnView<'a, T, Column<T>>
+where
+    T: Exportable,
+{
+    fn keys(&self) -> Vec<Key> {
+        ColumnView::keys(self).map(Key::untagged).collect()
+    }
+
+    async fn array(&self, keys: &[Key]) -> Result<Array, ExportError> {
+        let mut array = Array::new(T::DATA_TYPE);
+        for key in keys {
+            match self.get(&key.cast()) {
+                Some(cell) => array.push(Some(&*cell.read().await))?,
+                None => array.push::<T>(None)?,
+            }
+        }
+        Ok(array)
+    }
+}
+
+/// Exports a selection of columns to a [`RecordBatch`] for offline analysis
+#[derive(Default)]
+pub struct Exporter<'a> {
+    columns: Vec<(String, &'a dyn ExportColumn)>,
+}
+
+impl<'a> Exporter<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds the column of `T` in `db` to the export under `name`
+    pub fn column<T, DB>(&mut self, name: impl Into<String>, db: &'a DB) -> &mut Self
+    where
+        T: Exportable + 'a,
+        DB: BorrowColumn<T>,
+    {
+        let column: &'a Column<T> = db.borrow();
+        self.columns.push((name.into(), column));
+        self
+    }
+
+    /// Copies the selected columns into a batch keyed by every key present in any of them.
+    ///
+    /// All the columns are read-locked together, so the batch is a consistent snapshot.
+    /// Fails if a column's values are too large to be located by the batch's offsets.
+    pub async fn export(&self) -> Result<RecordBatch, ExportError> {
+        let mut snapshots = Vec::with_capacity(self.columns.len());
+        for (_, column) in &self.columns {
+            snapshots.push(column.snapshot().await);
+        }
+
+        let keys = snapshots
+            .iter()
+            .map(|snapshot| snapshot.keys())
+            .kmerge()
+            .dedup()
+            .collect::<Vec<_>>();
+
+        let mut batch = RecordBatch::new(keys);
+        for ((name, _), snapshot) in self.columns.iter().zip(&snapshots) {
+            let array = snapshot.array(batch.keys()).await?;
+            batch.push_column(name.clone(), array);
+        }
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_db::{ColumnViewMut, DataType, MyTable};
+
+    #[async_std::test]
+    async fn columns_are_aligned_on_shared_keys() {
+        let table = MyTable::new().await;
+        ColumnViewMut::<f32, _>::new(&table)
+            .await
+            .insert(Key::new(5), 9.0)
+            .unwrap();
+
+        let batch = Exporter::new()
+            .column::<i32, _>("ints", &table)
+            .column::<f32, _>("floats", &table)
+            .column::<String, _>("strings", &table)
+            .export()
+            .await
+            .unwrap();
+
+        assert!(batch.keys().iter().map(Key::index).eq(vec![0, 2, 3, 5]));
+        assert_eq!(
+            batch.values::<i32>("ints").unwrap(),
+            vec![Some(1), Some(2), Some(3), None]
+        );
+        assert_eq!(
+            batch.values::<f32>("floats").unwrap(),
+            vec![Some(4.0), Some(5.0), Some(6.0), Some(9.0)]
+        );
+        assert_eq!(
+            batch.values::<String>("strings").unwrap(),
+            vec![
+                Some("17".to_string()),
+                Some("28".to_string()),
+                Some("39".to_string()),
+                None
+            ]
+        );
+        assert_eq!(batch.column("ints").unwrap().data_type(), DataType::Int32);
+        assert_eq!(batch.column("strings").unwrap().null_count(), 1);
+    }
+
+    #[async_std::test]
+    async fn empty_selection_exports_nothing() {
+        let batch = Exporter::new().export().await.unwrap();
+        assert!(batch.is_empty());
+        assert_eq!(batch.columns().count(), 0);
+    }
+}
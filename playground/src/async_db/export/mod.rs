@@ -0,0 +1,13 @@
+mod array;
+mod bitmap;
+mod exportable;
+mod export_error;
+mod exporter;
+mod record_batch;
+
+pub use array::*;
+pub use bitmap::*;
+pub use exportable::*;
+pub use export_error::*;
+pub use exporter::*;
+pub use record_batch::*;
@@ -0,0 +1,275 @@
+use std::{convert::TryInto, path::Path};
+
+use crate::async_db::{Key, Replicate};
+
+use super::{Array, Bitmap, DataType, ExportError, Exportable};
+
+// File layout, all integers little-endian:
+//   magic "ADBX", version: u16, rows: u64, keys: rows * u64, columns: u32
+// then for each column:
+//   name length: u32, name: utf8, type tag: u8, validity: ceil(rows / 8) bytes,
+//   offsets: (rows + 1) * u32 for variable-width types only, values length: u64, values
+const MAGIC: &[u8; 4] = b"ADBX";
+const VERSION: u16 = 1;
+
+/// Exported columns of a table in struct-of-arrays layout, aligned on a shared array of keys.
+///
+/// Slot `i` of every column holds the cell at `keys()[i]`, or null if that column has none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordBatch {
+    keys: Vec<Key>,
+    columns: Vec<(String, Array)>,
+}
+
+impl RecordBatch {
+    pub fn new(keys: Vec<Key>) -> Self {
+        RecordBatch {
+            keys,
+            columns: vec![],
+        }
+    }
+
+    /// Adds a column, which must have a slot for every key
+    pub fn push_column(&mut self, name: impl Into<String>, array: Array) {
+        assert_eq!(
+            array.len(),
+            self.keys.len(),
+            "Column isn't aligned on the keys"
+        );
+        self.columns.push((name.into(), array));
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = (&str, &Array)> {
+        self.columns
+            .iter()
+            .map(|(name, array)| (name.as_str(), array))
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Array> {
+        self.columns()
+            .find(|(column, _)| *column == name)
+            .map(|(_, array)| array)
+    }
+
+    /// Copies out the values of the column `name`, checking that it holds `T`s
+    pub fn values<T>(&self, name: &str) -> Result<Vec<Option<T>>, ExportError>
+    where
+        T: Exportable,
+    {
+        let array = self
+            .column(name)
+            .ok_or_else(|| ExportError::MissingColumn(name.into()))?;
+        if array.data_type() != T::DATA_TYPE {
+            return Err(ExportError::TypeMismatch {
+                column: name.into(),
+                expected: T::DATA_TYPE,
+                found: array.data_type(),
+            });
+        }
+        Ok(array.iter().collect())
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(MAGIC);
+        VERSION.encode(buf);
+        (self.keys.len() as u64).encode(buf);
+        for key in &self.keys {
+            key.encode(buf);
+        }
+
+        (self.columns.len() as u32).encode(buf);
+        for (name, array) in &self.columns {
+            (name.len() as u32).encode(buf);
+            buf.extend_from_slice(name.as_bytes());
+            array.data_type().tag().encode(buf);
+            buf.extend_from_slice(array.validity().as_bytes());
+            for offset in array.offsets() {
+                offset.encode(buf);
+            }
+            (array.values().len() as u64).encode(buf);
+            buf.extend_from_slice(array.values());
+        }
+    }
+
+    pub fn decode(mut buf: &[u8]) -> Result<Self, ExportError> {
+        let buf = &mut buf;
+        if take(buf, MAGIC.len())? != MAGIC {
+            return Err(ExportError::Malformed("magic"));
+        }
+        let version = decode::<u16>(buf, "version")?;
+        if version != VERSION {
+            return Err(ExportError::UnsupportedVersion(version));
+        }
+
+        let rows = decode::<u64>(buf, "row count")?
+            .try_into()
+            .map_err(|_| ExportError::Malformed("row count"))?;
+        // Every key takes 8 bytes, so a corrupt count can't cause a huge allocation
+        if buf.len() / 8 < rows {
+            return Err(ExportError::Malformed("keys"));
+        }
+        let keys = (0..rows)
+            .map(|_| decode::<Key>(buf, "keys"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut batch = RecordBatch::new(keys);
+        let columns = decode::<u32>(buf, "column count")?;
+        for _ in 0..columns {
+            let name_len = decode::<u32>(buf, "column name")? as usize;
+            let name = std::str::from_utf8(take(buf, name_len)?)
+                .map_err(|_| ExportError::Malformed("column name"))?;
+            let data_type = DataType::from_tag(decode::<u8>(buf, "data type")?)
+                .ok_or(ExportError::Malformed("data type"))?;
+
+            let validity = Bitmap::from_bytes(take(buf, Bitmap::byte_len(rows))?.to_vec(), rows)
+                .ok_or(ExportError::Malformed("validity"))?;
+            let offsets = match data_type.width() {
+                Some(_) => vec![],
+                None => take(buf, (rows + 1) * 4)?
+                    .chunks_exact(4)
+                    .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect(),
+            };
+            let values_len = decode::<u64>(buf, "values")?
+                .try_into()
+                .map_err(|_| ExportError::Malformed("values"))?;
+            let values = take(buf, values_len)?.to_vec();
+
+            let array = Array::from_parts(data_type, validity, offsets, values)
+                .ok_or(ExportError::Malformed("column buffers"))?;
+            batch.push_column(name, array);
+        }
+
+        if !buf.is_empty() {
+            return Err(ExportError::Malformed("trailing bytes"));
+        }
+        Ok(batch)
+    }
+
+    /// Writes the batch to the file at `path`, replacing it if it exists
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), ExportError> {
+        let mut buf = vec![];
+        self.encode(&mut buf);
+        async_std::fs::write(path.as_ref(), buf).await?;
+        Ok(())
+    }
+
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, ExportError> {
+        RecordBatch::decode(&async_std::fs::read(path.as_ref()).await?)
+    }
+}
+
+/// Splits `len` bytes off the front of `buf`
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], ExportError> {
+    if buf.len() < len {
+        return Err(ExportError::Malformed("truncated file"));
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+fn decode<T>(buf: &mut &[u8], what: &'static str) -> Result<T, ExportError>
+where
+    T: Replicate,
+{
+    T::decode(buf).ok_or(ExportError::Malformed(what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let mut batch = RecordBatch::new(vec![Key::new(1), Key::new(4), Key::new(9)]);
+
+        let mut chars = Array::new(DataType::Char);
+        chars.push(Some(&'a')).unwrap();
+        chars.push::<char>(None).unwrap();
+        chars.push(Some(&'é')).unwrap();
+        batch.push_column("chars", chars);
+
+        let mut strings = Array::new(DataType::Utf8);
+        strings.push(Some(&"one".to_string())).unwrap();
+        strings.push(Some(&"four".to_string())).unwrap();
+        strings.push::<String>(None).unwrap();
+        batch.push_column("strings", strings);
+
+        batch
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        let mut buf = vec![];
+        batch().encode(&mut buf);
+        assert_eq!(&buf[..4], MAGIC);
+        assert_eq!(RecordBatch::decode(&buf), Ok(batch()));
+    }
+
+    #[test]
+    fn corrupt_files_are_rejected() {
+        let mut buf = vec![];
+        batch().encode(&mut buf);
+
+        assert_eq!(
+            RecordBatch::decode(&buf[..buf.len() - 1]),
+            Err(ExportError::Malformed("truncated file"))
+        );
+
+        let mut versioned = buf.clone();
+        versioned[4] = 2;
+        assert_eq!(
+            RecordBatch::decode(&versioned),
+            Err(ExportError::UnsupportedVersion(2))
+        );
+
+        buf.push(0);
+        assert_eq!(
+            RecordBatch::decode(&buf),
+            Err(ExportError::Malformed("trailing bytes"))
+        );
+    }
+
+    #[test]
+    fn values_check_the_column_type() {
+        let batch = batch();
+        assert_eq!(
+            batch.values::<char>("chars"),
+            Ok(vec![Some('a'), None, Some('é')])
+        );
+        assert_eq!(
+            batch.values::<i32>("chars"),
+            Err(ExportError::TypeMismatch {
+                column: "chars".into(),
+                expected: DataType::Int32,
+                found: DataType::Char,
+            })
+        );
+        assert_eq!(
+            batch.values::<i32>("ints"),
+            Err(ExportError::MissingColumn("ints".into()))
+        );
+    }
+
+    #[async_std::test]
+    async fn files_round_trip() {
+        let path = std::env::temp_dir().join(format!("async_db_export_{}", std::process::id()));
+        batch().save(&path).await.unwrap();
+        let loaded = RecordBatch::load(&path).await;
+        async_std::fs::remove_file(&path).await.unwrap();
+        assert_eq!(loaded, Ok(batch()));
+    }
+}
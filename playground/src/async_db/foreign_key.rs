@@ -0,0 +1,84 @@
+use std::{fmt::Debug, hash::Hash, marker::PhantomData};
+
+use super::Key;
+
+/// A nullable reference to a row in the table `T`.
+///
+/// Store these in a [`Column`] and register the relation with
+/// [`Database::add_foreign_key`] to have integrity checked on insert and remove.
+pub struct ForeignKey<T> {
+    key: Option<Key<T>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> ForeignKey<T> {
+    pub fn new(key: Key<T>) -> Self {
+        ForeignKey {
+            key: Some(key),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn null() -> Self {
+        ForeignKey {
+            key: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> Option<Key<T>> {
+        self.key
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.key.is_none()
+    }
+
+    pub fn set_null(&mut self) {
+        self.key = None;
+    }
+}
+
+impl<T> From<Key<T>> for ForeignKey<T> {
+    fn from(key: Key<T>) -> Self {
+        ForeignKey::new(key)
+    }
+}
+
+// Manual impls so that `T` doesn't need to implement any of these traits itself
+impl<T> Debug for ForeignKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ForeignKey")
+            .field(&std::any::type_name::<T>())
+            .field(&self.key)
+            .finish()
+    }
+}
+
+impl<T> Default for ForeignKey<T> {
+    fn default() -> Self {
+        ForeignKey::null()
+    }
+}
+
+impl<T> Clone for ForeignKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ForeignKey<T> {}
+
+impl<T> PartialEq for ForeignKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for ForeignKey<T> {}
+
+impl<T> Hash for ForeignKey<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state)
+    }
+}
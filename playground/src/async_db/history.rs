@@ -0,0 +1,452 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use async_std::channel::Receiver;
+use async_trait::async_trait;
+
+use super::{BorrowColumn, Column, ColumnEvent, ColumnView, ColumnViewMut, Key};
+
+/// Number of steps kept by [`History::new`]
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// Name of the step that changes made since the last [`History::commit`] are recorded under
+/// when undoing without committing them first
+pub const UNCOMMITTED_STEP: &str = "Uncommitted changes";
+
+/// A cell's value either side of a step
+struct CellChange<T> {
+    key: Key,
+    before: Option<T>,
+    after: Option<T>,
+}
+
+/// A column whose changes are recorded by a [`History`]
+#[async_trait(?Send)]
+trait TrackedColumn {
+    /// Records the cells changed since the last step as a new step, returning whether there were any
+    async fn record(&mut self) -> bool;
+
+    /// Reverts the last recorded step
+    async fn undo(&mut self);
+
+    /// Reapplies the last undone step
+    async fn redo(&mut self);
+
+    /// Drops the oldest recorded step
+    fn forget_oldest(&mut self);
+
+    /// Drops the last recorded step, without reverting it
+    fn forget_latest(&mut self);
+
+    /// Drops every undone step
+    fn forget_undone(&mut self);
+}
+
+struct HistoryColumn<'a, T> {
+    column: &'a Column<T>,
+    events: Receiver<ColumnEvent>,
+    // The column as of the last step, which changes are recorded against
+    copy: BTreeMap<Key, T>,
+    // Keys written since the last step, in the order they were first written
+    pending: Vec<Key>,
+    done: VecDeque<Vec<CellChange<T>>>,
+    undone: Vec<Vec<CellChange<T>>>,
+}
+
+impl<'a, T> HistoryColumn<'a, T>
+where
+    T: Clone,
+{
+    async fn new(column: &'a Column<T>) -> HistoryColumn<'a, T> {
+        // Subscribe before copying so no write is missed; any seen twice records an unchanged cell
+        let events = column.subscribe();
+
+        let view = ColumnView::<T, Column<T>>::new(column).await;
+        let mut copy = BTreeMap::new();
+        for (key, cell) in view.iter() {
+            copy.insert(key.untagged(), cell.read().await.clone());
+        }
+
+        HistoryColumn {
+            column,
+            events,
+            copy,
+            pending: vec![],
+            done: Default::default(),
+            undone: vec![],
+        }
+    }
+
+    fn drain_events(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            self.pending.push(event.key());
+        }
+    }
+
+    /// Writes the `before` or `after` side of `changes` in reverse or forward order respectively
+    async fn apply(&mut self, changes: &[CellChange<T>], forward: bool) {
+        let mut view = ColumnViewMut::<T, Column<T>>::new(self.column).await;
+
+        // Writes made before the lock was taken are someone else's, and belong to the next step
+        self.drain_events();
+
+        let ordered: Box<dyn Iterator<Item = &CellChange<T>>> = if forward {
+            Box::new(changes.iter())
+        } else {
+            Box::new(changes.iter().rev())
+        };
+        for change in ordered {
+            let value = if forward {
+                &change.after
+            } else {
+                &change.before
+            };
+            // Restoring skips constraints, as the value already passed them when first written
+            view.restore(change.key.cast(), value.clone());
+            match value {
+                Some(value) => self.copy.insert(change.key, value.clone()),
+                None => self.copy.remove(&change.key),
+            };
+        }
+
+        // Nobody else can write while the view is held, so these events are all our own
+        while self.events.try_recv().is_ok() {}
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, T> TrackedColumn for HistoryColumn<'a, T>
+where
+    T: Clone,
+{
+    async fn record(&mut self) -> bool {
+        self.drain_events();
+
+        let mut seen = BTreeSet::new();
+        let mut changes = vec![];
+        for key in std::mem::take(&mut self.pending) {
+            if !seen.insert(key) {
+                continue;
+            }
+
+            let after = {
+                let shard = self.column.shards().shard(&key).read().await;
+                match shard.get(&key) {
+                    Some(cell) => Some(cell.read().await.clone()),
+                    None => None,
+                }
+            };
+            let before = match &after {
+                Some(value) => self.copy.insert(key, value.clone()),
+                None => self.copy.remove(&key),
+            };
+
+            if before.is_some() || after.is_some() {
+                changes.push(CellChange { key, before, after });
+            }
+        }
+
+        let changed = !changes.is_empty();
+        self.done.push_back(changes);
+        changed
+    }
+
+    async fn undo(&mut self) {
+        if let Some(changes) = self.done.pop_back() {
+            self.apply(&changes, false).await;
+            self.undone.push(changes);
+        }
+    }
+
+    async fn redo(&mut self) {
+        if let Some(changes) = self.undone.pop() {
+            self.apply(&changes, true).await;
+            self.done.push_back(changes);
+        }
+    }
+
+    fn forget_oldest(&mut self) {
+        self.done.pop_front();
+    }
+
+    fn forget_latest(&mut self) {
+        self.done.pop_back();
+    }
+
+    fn forget_undone(&mut self) {
+        self.undone.clear();
+    }
+}
+
+/// Records changes to a set of columns of the table `DB` as named steps that can be undone and redone.
+///
+/// Changes are picked up from column events, so writes through any API are recorded,
+/// including [`CellViewMut`]s, even those that leave a cell as it was. Only [`Clone`] is needed
+/// of the column types, to keep the values each step overwrote.
+pub struct History<'a, DB> {
+    db: &'a DB,
+    columns: Vec<Box<dyn TrackedColumn + 'a>>,
+    done: VecDeque<String>,
+    undone: Vec<String>,
+    depth: usize,
+}
+
+impl<'a, DB> History<'a, DB> {
+    pub fn new(db: &'a DB) -> Self {
+        Self::with_depth(db, DEFAULT_HISTORY_DEPTH)
+    }
+
+    /// Creates a history that keeps at most `depth` steps, forgetting the oldest beyond that
+    pub fn with_depth(db: &'a DB, depth: usize) -> Self {
+        History {
+            db,
+            columns: vec![],
+            done: Default::default(),
+            undone: vec![],
+            depth,
+        }
+    }
+
+    /// Starts recording changes to the column of `T`.
+    ///
+    /// Derived columns can't be tracked, but are recomputed as their sources are undone.
+    pub async fn track<T>(&mut self) -> &mut Self
+    where
+        T: Clone + 'a,
+        DB: BorrowColumn<T>,
+    {
+        let column: &'a Column<T> = self.db.borrow();
+        assert!(!column.is_derived(), "Derived columns can't be tracked");
+
+        // The column had no part in the steps recorded so far
+        let mut tracked = HistoryColumn::new(column).await;
+        tracked.done.resize_with(self.done.len(), Vec::new);
+        tracked.undone.resize_with(self.undone.len(), Vec::new);
+        self.columns.push(Box::new(tracked));
+        self
+    }
+
+    /// Records the changes made since the last step as a step named `name`.
+    /// Returns `false`, recording nothing, if no tracked cell has changed.
+    pub async fn commit(&mut self, name: impl Into<String>) -> bool {
+        let mut changed = false;
+        for column in &mut self.columns {
+            changed |= column.record().await;
+        }
+
+        if !changed {
+            for column in &mut self.columns {
+                column.forget_latest();
+            }
+            return false;
+        }
+
+        for column in &mut self.columns {
+            column.forget_undone();
+        }
+        self.undone.clear();
+        self.done.push_back(name.into());
+        self.truncate();
+        true
+    }
+
+    /// Reverts the most recent step, returning its name.
+    ///
+    /// Uncommitted changes are committed as [`UNCOMMITTED_STEP`] first, so are the ones reverted.
+    pub async fn undo(&mut self) -> Option<String> {
+        self.commit(UNCOMMITTED_STEP).await;
+
+        let name = self.done.pop_back()?;
+        for column in self.columns.iter_mut().rev() {
+            column.undo().await;
+        }
+        self.undone.push(name.clone());
+        Some(name)
+    }
+
+    /// Reapplies the most recently undone step, returning its name.
+    /// Does nothing if any changes have been committed since it was undone.
+    pub async fn redo(&mut self) -> Option<String> {
+        if self.commit(UNCOMMITTED_STEP).await {
+            return None;
+        }
+
+        let name = self.undone.pop()?;
+        for column in &mut self.columns {
+            column.redo().await;
+        }
+        self.done.push_back(name.clone());
+        Some(name)
+    }
+
+    /// Names of the steps that can be undone, oldest first
+    pub fn undo_steps(&self) -> impl Iterator<Item = &str> {
+        self.done.iter().map(String::as_str)
+    }
+
+    /// Names of the steps that can be redone, next to be redone last
+    pub fn redo_steps(&self) -> impl Iterator<Item = &str> {
+        self.undone.iter().map(String::as_str)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Changes the number of steps kept, forgetting the oldest if there are now too many
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.truncate();
+    }
+
+    /// Forgets every step, without changing any cells
+    pub fn clear(&mut self) {
+        while self.done.pop_front().is_some() {
+            for column in &mut self.columns {
+                column.forget_oldest();
+            }
+        }
+        for column in &mut self.columns {
+            column.forget_undone();
+        }
+        self.undone.clear();
+    }
+
+    fn truncate(&mut self) {
+        while self.done.len() > self.depth {
+            self.done.pop_front();
+            for column in &mut self.columns {
+                column.forget_oldest();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use super::*;
+    use crate::async_db::{CellView, CellViewMut, IntFloatCharRow, MyTable, Row};
+
+    async fn ints(table: &MyTable) -> Vec<(usize, i32)> {
+        let view = ColumnView::<i32, _>::new(table).await;
+        let mut ints = vec![];
+        for (key, cell) in view.iter() {
+            ints.push((key.index(), *cell.read().await));
+        }
+        ints
+    }
+
+    async fn tracked(table: &MyTable) -> History<'_, MyTable> {
+        let mut history = History::new(table);
+        history
+            .track::<i32>()
+            .await
+            .track::<f32>()
+            .await
+            .track::<char>()
+            .await;
+        history
+    }
+
+    #[async_std::test]
+    async fn steps_are_undone_and_redone() {
+        let table = MyTable::new().await;
+        let mut history = tracked(&table).await;
+
+        *CellViewMut::<i32>::new(&table, Key::new(0)).await = 10;
+        assert!(history.commit("Edit").await);
+        IntFloatCharRow::remove(&table, Key::new(2)).await;
+        assert!(history.commit("Remove row").await);
+        assert!(history.undo_steps().eq(vec!["Edit", "Remove row"]));
+
+        assert_eq!(history.undo().await.as_deref(), Some("Remove row"));
+        assert_eq!(ints(&table).await, vec![(0, 10), (2, 2), (3, 3)]);
+        assert_eq!(*CellView::<char>::new(&table, Key::new(2)).await, '8');
+
+        assert_eq!(history.undo().await.as_deref(), Some("Edit"));
+        assert_eq!(ints(&table).await, vec![(0, 1), (2, 2), (3, 3)]);
+        assert_eq!(history.undo().await, None);
+
+        assert_eq!(history.redo().await.as_deref(), Some("Edit"));
+        assert_eq!(history.redo().await.as_deref(), Some("Remove row"));
+        assert_eq!(ints(&table).await, vec![(0, 10), (3, 3)]);
+        assert_eq!(history.redo().await, None);
+    }
+
+    #[async_std::test]
+    async fn derived_columns_follow_undone_sources() {
+        let table = MyTable::new().await;
+        let mut history = tracked(&table).await;
+
+        *CellViewMut::<char>::new(&table, Key::new(3)).await = 'x';
+        history.commit("Edit").await;
+        assert_eq!(*CellView::<String>::new(&table, Key::new(3)).await, "3x");
+
+        history.undo().await;
+        assert_eq!(*CellView::<String>::new(&table, Key::new(3)).await, "39");
+    }
+
+    #[async_std::test]
+    async fn uncommitted_changes_are_undone_first() {
+        let table = MyTable::new().await;
+        let mut history = tracked(&table).await;
+
+        IntFloatCharRow::insert(&table, Key::new(5), (5, 5.0, '5'))
+            .await
+            .unwrap();
+        history.commit("Insert").await;
+        Borrow::<Column<i32>>::borrow(&table)
+            .insert(Key::new(6), 6)
+            .await
+            .unwrap();
+
+        assert_eq!(history.undo().await.as_deref(), Some(UNCOMMITTED_STEP));
+        assert_eq!(ints(&table).await, vec![(0, 1), (2, 2), (3, 3), (5, 5)]);
+        assert!(history.redo_steps().eq(vec![UNCOMMITTED_STEP]));
+    }
+
+    #[async_std::test]
+    async fn committing_discards_undone_steps() {
+        let table = MyTable::new().await;
+        let mut history = tracked(&table).await;
+
+        assert!(!history.commit("Nothing").await);
+        Borrow::<Column<i32>>::borrow(&table)
+            .insert(Key::new(6), 6)
+            .await
+            .unwrap();
+        history.commit("Insert").await;
+        history.undo().await;
+
+        Borrow::<Column<i32>>::borrow(&table)
+            .insert(Key::new(7), 7)
+            .await
+            .unwrap();
+        assert_eq!(history.redo().await, None);
+        assert!(history.undo_steps().eq(vec![UNCOMMITTED_STEP]));
+        assert_eq!(history.redo_steps().count(), 0);
+    }
+
+    #[async_std::test]
+    async fn depth_limits_the_steps_kept() {
+        let table = MyTable::new().await;
+        let mut history = History::with_depth(&table, 2);
+        history.track::<i32>().await;
+
+        for value in 10..13 {
+            *CellViewMut::<i32>::new(&table, Key::new(0)).await = value;
+            history.commit(format!("Set {}", value)).await;
+        }
+        assert!(history.undo_steps().eq(vec!["Set 11", "Set 12"]));
+
+        history.undo().await;
+        history.undo().await;
+        assert_eq!(history.undo().await, None);
+        assert_eq!(*CellView::<i32>::new(&table, Key::new(0)).await, 10);
+
+        history.set_depth(1);
+        assert_eq!(history.undo_steps().count(), 0);
+        assert_eq!(history.redo_steps().count(), 2);
+    }
+}
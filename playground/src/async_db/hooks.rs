@@ -0,0 +1,140 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use super::Key;
+
+/// A kind of write that can be made to a row of a [`Table`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TableOperation {
+    Insert,
+    Update,
+    Remove,
+}
+
+/// Runs before a write, and can reject it with a message
+pub type BeforeHook = Box<dyn Fn(Key) -> Result<(), String> + Send + Sync>;
+
+/// Runs after a write has been made
+pub type AfterHook = Box<dyn Fn(Key) + Send + Sync>;
+
+/// Callbacks that [`Database`] runs around writes to a [`Table`]
+#[derive(Default)]
+pub struct TableHooks {
+    before: HashMap<TableOperation, Vec<BeforeHook>>,
+    after: HashMap<TableOperation, Vec<AfterHook>>,
+}
+
+impl TableHooks {
+    pub fn before<F>(&mut self, operation: TableOperation, hook: F) -> &mut Self
+    where
+        F: Fn(Key) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.before
+            .entry(operation)
+            .or_default()
+            .push(Box::new(hook));
+        self
+    }
+
+    pub fn after<F>(&mut self, operation: TableOperation, hook: F) -> &mut Self
+    where
+        F: Fn(Key) + Send + Sync + 'static,
+    {
+        self.after
+            .entry(operation)
+            .or_default()
+            .push(Box::new(hook));
+        self
+    }
+
+    /// Runs the before hooks for `operation`, stopping at the first rejection
+    pub fn run_before(&self, operation: TableOperation, key: Key) -> Result<(), String> {
+        self.before
+            .get(&operation)
+            .into_iter()
+            .flatten()
+            .try_for_each(|hook| hook(key))
+    }
+
+    pub fn run_after(&self, operation: TableOperation, key: Key) {
+        self.after
+            .get(&operation)
+            .into_iter()
+            .flatten()
+            .for_each(|hook| hook(key))
+    }
+}
+
+impl Debug for TableHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableHooks")
+            .field(
+                "before",
+                &self
+                    .before
+                    .iter()
+                    .map(|(op, hooks)| (op, hooks.len()))
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "after",
+                &self
+                    .after
+                    .iter()
+                    .map(|(op, hooks)| (op, hooks.len()))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_db::{Database, DatabaseError, IntFloatCharRow, MyTable};
+
+    #[async_std::test]
+    async fn hooks() {
+        let mut table = MyTable::new().await;
+        table
+            .hooks_mut()
+            .before(TableOperation::Remove, |key| match key.index() {
+                0 => Err("Row 0 is permanent".into()),
+                _ => Ok(()),
+            });
+
+        let mut db = Database::new();
+        db.add_table("my_table", table);
+
+        assert!(matches!(
+            db.remove::<MyTable>(Key::new(0)).await,
+            Err(DatabaseError::HookRejected { .. })
+        ));
+        db.remove::<MyTable>(Key::new(2)).await.unwrap();
+    }
+
+    #[async_std::test]
+    async fn after_hooks() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let inserts = Arc::new(AtomicUsize::new(0));
+
+        let mut table = MyTable::new().await;
+        table.hooks_mut().after(TableOperation::Insert, {
+            let inserts = inserts.clone();
+            move |_| {
+                inserts.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        let mut db = Database::new();
+        db.add_table("my_table", table);
+
+        db.insert::<MyTable, IntFloatCharRow>(Key::new(4), (4, 7.0, 'a'))
+            .await
+            .unwrap();
+        assert_eq!(inserts.load(Ordering::Relaxed), 1);
+    }
+}
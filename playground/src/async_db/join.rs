@@ -0,0 +1,264 @@
+use std::ops::Bound;
+
+use async_trait::async_trait;
+use futures::stream::{self, LocalBoxStream, StreamExt};
+
+use super::{Column, Key};
+
+/// A sorted set of keys that can be seeked and probed, such as a [`Column`]
+#[async_trait(?Send)]
+pub trait KeyIndex {
+    async fn len(&self) -> usize;
+
+    async fn contains_key(&self, key: &Key) -> bool;
+
+    /// The first key greater than or equal to `from`
+    async fn seek(&self, from: Key) -> Option<Key>;
+}
+
+#[async_trait(?Send)]
+impl<T> KeyIndex for Column<T> {
+    async fn len(&self) -> usize {
+        self.refresh().await;
+
+        let mut len = 0;
+        for shard in self.shards().iter() {
+            len += shard.read().await.len();
+        }
+        len
+    }
+
+    async fn contains_key(&self, key: &Key) -> bool {
+        Column::contains_key(self, key).await
+    }
+
+    async fn seek(&self, from: Key) -> Option<Key> {
+        self.refresh().await;
+
+        // Each shard is sorted, so the answer is the least of their first keys in range
+        let mut first = None;
+        for shard in self.shards().iter() {
+            let shard = shard.read().await;
+            let key = shard
+                .range((Bound::Included(from), Bound::Unbounded))
+                .next()
+                .map(|(key, _)| *key);
+            first = first.into_iter().chain(key).min();
+        }
+        first
+    }
+}
+
+/// How a [`Join`] finds the keys its indices have in common
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JoinStrategy {
+    /// Walks the smallest index and probes each of the others for its keys.
+    /// Costs a seek and a probe per key of the smallest index.
+    Probe,
+    /// Seeks each index past the largest key seen so far until they all agree.
+    /// Seeks are dearer than probes, but whole runs of keys that any one index lacks are skipped.
+    Merge,
+}
+
+/// Iterates the keys common to a set of [`KeyIndex`]es without materialising either of them.
+///
+/// The indices are only locked while a key is being found, never between the keys yielded,
+/// so rows can be viewed and written while the stream is consumed.
+#[derive(Default)]
+pub struct Join<'a> {
+    indices: Vec<&'a dyn KeyIndex>,
+    strategy: Option<JoinStrategy>,
+}
+
+impl<'a> Join<'a> {
+    /// Number of keys of the smallest index probed when planning
+    pub const SAMPLE: usize = 16;
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with<I>(mut self, index: &'a I) -> Self
+    where
+        I: KeyIndex,
+    {
+        self.indices.push(index);
+        self
+    }
+
+    /// Overrides the strategy the planner would choose
+    pub fn with_strategy(mut self, strategy: JoinStrategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Orders the indices smallest first, then probes the first [`Join::SAMPLE`] keys of the smallest.
+    /// Picks [`JoinStrategy::Merge`] if most of them are missing from the other indices,
+    /// since probing would mostly be wasted, and [`JoinStrategy::Probe`] otherwise.
+    pub async fn plan(&mut self) -> JoinStrategy {
+        let mut sized = Vec::with_capacity(self.indices.len());
+        for index in &self.indices {
+            sized.push((index.len().await, *index));
+        }
+        sized.sort_by_key(|(len, _)| *len);
+        self.indices = sized.iter().map(|(_, index)| *index).collect();
+
+        if let Some(strategy) = self.strategy {
+            return strategy;
+        }
+
+        let (driver, probed) = match self.indices.split_first() {
+            Some(split) => split,
+            None => return *self.strategy.insert(JoinStrategy::Probe),
+        };
+
+        let (mut sampled, mut hits) = (0, 0);
+        let mut cursor = Some(Key::default());
+        while let Some(key) = match cursor {
+            Some(cursor) if sampled < Self::SAMPLE => driver.seek(cursor).await,
+            _ => None,
+        } {
+            sampled += 1;
+            let mut hit = true;
+            for index in probed {
+                hit = hit && index.contains_key(&key).await;
+            }
+            hits += hit as usize;
+            cursor = key.index().checked_add(1).map(Key::new);
+        }
+
+        *self.strategy.insert(if hits * 2 < sampled {
+            JoinStrategy::Merge
+        } else {
+            JoinStrategy::Probe
+        })
+    }
+
+    /// Lazily yields the common keys in ascending order, planning when first polled.
+    /// The keys are tagged with `DB`, the table the joined columns belong to.
+    pub fn keys<DB>(mut self) -> LocalBoxStream<'a, Key<DB>> {
+        stream::once(async move {
+            let strategy = self.plan().await;
+            stream::unfold(Some(Key::default()), move |cursor| {
+                let indices = self.indices.clone();
+                async move {
+                    let key = match strategy {
+                        JoinStrategy::Probe => Self::probe(&indices, cursor?).await,
+                        JoinStrategy::Merge => Self::merge(&indices, cursor?).await,
+                    }?;
+                    Some((key.cast(), key.index().checked_add(1).map(Key::new)))
+                }
+            })
+        })
+        .flatten()
+        .boxed_local()
+    }
+
+    async fn probe(indices: &[&dyn KeyIndex], mut cursor: Key) -> Option<Key> {
+        let (driver, probed) = indices.split_first()?;
+
+        loop {
+            let key = driver.seek(cursor).await?;
+
+            let mut found = true;
+            for index in probed {
+                if !index.contains_key(&key).await {
+                    found = false;
+                    break;
+                }
+            }
+            if found {
+                return Some(key);
+            }
+
+            cursor = Key::new(key.index().checked_add(1)?);
+        }
+    }
+
+    async fn merge(indices: &[&dyn KeyIndex], mut candidate: Key) -> Option<Key> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let mut agreed = 0;
+        for index in indices.iter().cycle() {
+            let key = index.seek(candidate).await?;
+            if key == candidate {
+                agreed += 1;
+                if agreed == indices.len() {
+                    return Some(key);
+                }
+            } else {
+                candidate = key;
+                agreed = 1;
+            }
+        }
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    async fn columns(sizes: &[usize]) -> Vec<Column<usize>> {
+        let mut columns = vec![];
+        for (i, size) in sizes.iter().enumerate() {
+            let column = Column::default();
+            // Strides differ so that each pair of columns shares only some keys
+            for key in (0..*size).map(|n| n * (i + 1)) {
+                column.insert(Key::new(key), key).await.unwrap();
+            }
+            columns.push(column);
+        }
+        columns
+    }
+
+    async fn join(columns: &[Column<usize>], strategy: Option<JoinStrategy>) -> Vec<usize> {
+        let mut join = columns.iter().fold(Join::new(), Join::with);
+        if let Some(strategy) = strategy {
+            join = join.with_strategy(strategy);
+        }
+        join.keys::<()>().map(|key| key.index()).collect().await
+    }
+
+    #[async_std::test]
+    async fn strategies_agree() {
+        let columns = columns(&[1000, 400, 30]).await;
+
+        let expected = (0..90).step_by(6).collect::<Vec<_>>();
+        assert_eq!(join(&columns, Some(JoinStrategy::Probe)).await, expected);
+        assert_eq!(join(&columns, Some(JoinStrategy::Merge)).await, expected);
+    }
+
+    #[async_std::test]
+    async fn plan() {
+        // Every key of the smaller column is shared
+        let overlapping = columns(&[1000, 10]).await;
+        let mut planned = Join::new().with(&overlapping[0]).with(&overlapping[1]);
+        assert_eq!(planned.plan().await, JoinStrategy::Probe);
+
+        // Only one in five keys is shared
+        let sparse = columns(&[100, 100, 100, 100, 100]).await;
+        let mut planned = Join::new().with(&sparse[0]).with(&sparse[4]);
+        assert_eq!(planned.plan().await, JoinStrategy::Merge);
+
+        assert!(join(&[], None).await.is_empty());
+    }
+
+    #[async_std::test]
+    async fn lazy() {
+        let columns = columns(&[10, 10]).await;
+        let mut keys = Join::new().with(&columns[0]).with(&columns[1]).keys::<()>();
+
+        assert_eq!(keys.next().await, Some(Key::new(0)));
+        // Nothing is locked between keys, and later keys are found as they're reached
+        columns[0].insert(Key::new(3), 3).await.unwrap();
+        columns[1].insert(Key::new(3), 3).await.unwrap();
+        assert_eq!(keys.next().await, Some(Key::new(2)));
+        assert_eq!(keys.next().await, Some(Key::new(3)));
+        assert_eq!(keys.next().await, Some(Key::new(4)));
+    }
+}
@@ -1,30 +1,105 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Key(usize);
+/// Identifies a row of the table `T`.
+///
+/// The tag stops a key taken from one table being used to look up another;
+/// views and rows created from a table `DB` only accept a `Key<DB>`.
+/// [`Column`]s don't know which table they belong to, so work with untagged `Key<()>`s.
+/// Conversions to and from the raw index are explicit.
+pub struct Key<T = ()> {
+    index: usize,
+    _table: PhantomData<fn() -> T>,
+}
+
+impl<T> Key<T> {
+    pub fn new(index: usize) -> Self {
+        Key {
+            index,
+            _table: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Drops the table tag, for indexing a [`Column`]
+    pub fn untagged(self) -> Key {
+        Key::new(self.index)
+    }
 
-impl From<usize> for Key {
-    fn from(key: usize) -> Self {
-        Key(key)
+    /// Reinterprets the key as belonging to the table `U`
+    pub(crate) fn cast<U>(self) -> Key<U> {
+        Key::new(self.index)
     }
 }
 
-impl From<Key> for usize {
-    fn from(key: Key) -> Self {
-        key.0
+impl<T> Debug for Key<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Key").field(&self.index).finish()
     }
 }
 
-impl Deref for Key {
-    type Target = usize;
+impl<T> Default for Key<T> {
+    fn default() -> Self {
+        Key::new(0)
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
-impl DerefMut for Key {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl<T> Copy for Key<T> {}
+
+impl<T> PartialEq for Key<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Key<T> {}
+
+impl<T> PartialOrd for Key<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Key<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> Hash for Key<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::async_db::{CellView, IntFloatCharRow, Key, MyTable, Row};
+
+    #[async_std::test]
+    async fn tagged_keys_round_trip_through_rows() {
+        let table = MyTable::new().await;
+
+        let keys: BTreeSet<Key<MyTable>> = IntFloatCharRow::keys(&table).await;
+        assert!(keys.iter().map(Key::index).eq(vec![0, 2, 3]));
+
+        let key = Key::<MyTable>::new(2);
+        assert_eq!(key.untagged(), Key::new(2));
+        assert_eq!(*CellView::<i32>::new(&table, key).await, 2);
     }
 }
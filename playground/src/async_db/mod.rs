@@ -1,34 +1,158 @@
+#[macro_use]
+mod shards;
+
 mod cell_view;
 mod cell_view_mut;
 mod column;
+mod column_event;
 mod column_view;
 mod column_view_mut;
+mod constraint;
+mod database;
+mod derived;
+mod error;
+mod export;
+mod foreign_key;
+mod history;
+mod hooks;
+mod join;
 mod key;
+#[macro_use]
+mod patch;
+mod replication;
 mod row;
+mod table;
 mod test;
+mod transaction;
+mod wait;
 
 pub use cell_view::*;
 pub use cell_view_mut::*;
 pub use column::*;
+pub use column_event::*;
 pub use column_view::*;
 pub use column_view_mut::*;
+pub use constraint::*;
+pub use database::*;
+pub use derived::*;
+pub use error::*;
+pub use export::*;
+pub use foreign_key::*;
+pub use history::*;
+pub use hooks::*;
+pub use join::*;
 pub use key::*;
+pub use patch::*;
+pub use replication::*;
 pub use row::*;
+pub use shards::*;
+pub use table::*;
 pub use test::*;
+pub use transaction::*;
+
+use std::{borrow::Borrow, collections::BTreeMap};
 
-use std::collections::BTreeMap;
+use futures::StreamExt;
 
 use async_std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-pub type ColumnLock<T> = RwLock<ColumnCollection<T>>;
-pub type ReadColumn<'a, T> = RwLockReadGuard<'a, ColumnCollection<T>>;
-pub type WriteColumn<'a, T> = RwLockWriteGuard<'a, ColumnCollection<T>>;
+/// Lock over a single shard of a [`Column`]
+pub type ColumnLock<T> = RwLock<ShardCollection<T>>;
+pub type ReadColumn<'a, T> = RwLockReadGuard<'a, ShardCollection<T>>;
+pub type WriteColumn<'a, T> = RwLockWriteGuard<'a, ShardCollection<T>>;
 
-pub type ColumnCollection<T> = BTreeMap<Key, CellLock<T>>;
+/// The cells of a single shard of a [`Column`]
+pub type ShardCollection<T> = BTreeMap<Key, CellLock<T>>;
 
 pub type CellLock<T> = RwLock<T>;
+pub type ReadCell<'a, T> = RwLockReadGuard<'a, T>;
+pub type WriteCell<'a, T> = RwLockWriteGuard<'a, T>;
 
 pub async fn main() {
     let table = MyTable::new().await;
     print_system(&table).await;
+    print_strings_system(&table).await;
+
+    let mut db = Database::new();
+    db.add_table("my_table", table);
+    db.add_table("my_ref_table", MyRefTable::default());
+    db.add_foreign_key::<MyRefTable, MyTable>(OnDelete::Cascade);
+
+    db.insert::<MyRefTable, NamedRefRow>(Key::new(0), ("Zero", ForeignKey::new(Key::new(0))))
+        .await
+        .unwrap();
+    db.insert::<MyRefTable, NamedRefRow>(Key::new(1), ("Three", ForeignKey::new(Key::new(3))))
+        .await
+        .unwrap();
+
+    if let Err(e) = db
+        .insert::<MyRefTable, NamedRefRow>(Key::new(2), ("One", ForeignKey::new(Key::new(1))))
+        .await
+    {
+        println!("{}", e);
+    }
+
+    // Writes both cells or neither
+    db.transaction(|tx: Transaction| async move {
+        tx.insert::<MyRefTable, &'static str>(Key::new(2), "Two")?;
+        tx.insert::<MyRefTable, ForeignKey<MyTable>>(Key::new(2), ForeignKey::new(Key::new(2)))?;
+        Ok::<_, DatabaseError>(())
+    })
+    .await
+    .unwrap();
+
+    // Cascades to row 1 of MyRefTable
+    db.remove::<MyTable>(Key::new(3)).await.unwrap();
+    println!(
+        "Remaining references: {:?}",
+        NamedRefRow::common_keys(db.table::<MyRefTable>().unwrap())
+            .collect::<Vec<_>>()
+            .await
+    );
+
+    // Mirror MyTable into a read-only replica
+    let table = db.table::<MyTable>().unwrap();
+    let mut leader = Leader::new();
+    leader
+        .replicate::<i32>("my_table", "ints", table.borrow())
+        .replicate::<f32>("my_table", "floats", table.borrow())
+        .replicate::<char>("my_table", "chars", table.borrow());
+
+    let (leader_end, follower_end) = ChannelTransport::pair();
+    let mut follower = Follower::new(follower_end);
+    follower
+        .mirror::<i32>("my_table", "ints")
+        .mirror::<f32>("my_table", "floats")
+        .mirror::<char>("my_table", "chars");
+
+    leader.add_follower(leader_end).await;
+    follower.sync().await.unwrap();
+    let replica = follower.table("my_table").unwrap();
+    for (key, int) in replica.view::<i32>().await.unwrap().iter() {
+        println!("Replicated {:?}, Int: {}", key, *int.read().await);
+    }
+
+    // Revert an edit made through a CellViewMut
+    let mut history = History::new(table);
+    history.track::<i32>().await;
+    *CellViewMut::<i32>::new(table, Key::new(0)).await = 100;
+    history.commit("Set first int").await;
+    println!("Undid {:?}", history.undo().await);
+
+    // Export a snapshot of MyTable for offline analysis
+    let batch = Exporter::new()
+        .column::<i32, _>("ints", table)
+        .column::<String, _>("strings", table)
+        .export()
+        .await
+        .unwrap();
+    let mut buf = vec![];
+    batch.encode(&mut buf);
+    let batch = RecordBatch::decode(&buf).unwrap();
+    println!(
+        "Exported {} rows in {} bytes: {:?}",
+        batch.len(),
+        buf.len(),
+        batch.values::<String>("strings").unwrap()
+    );
 }
@@ -0,0 +1,238 @@
+use std::any::{Any, TypeId};
+
+use async_trait::async_trait;
+use futures::future::LocalBoxFuture;
+
+use super::{Column, DatabaseError, Key};
+
+/// How [`Row::patch`] treats fields whose cells don't exist yet
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PatchPolicy {
+    /// Inserts the missing cells
+    CreateMissing,
+    /// Fails with [`DatabaseError::MissingCell`], leaving the row untouched
+    RequireExisting,
+}
+
+/// A partial row that can be written over the cells of `key`.
+///
+/// Implemented by the structs declared with `row_patch!`.
+#[async_trait(?Send)]
+pub trait ApplyPatch<'a, DB> {
+    /// Writes each present field, rolling back the ones already written if any fails.
+    /// Returns a [`PatchUndo`] that puts back the cells the patch overwrote.
+    async fn apply_undoable(
+        self,
+        db: &'a DB,
+        key: Key<DB>,
+        policy: PatchPolicy,
+    ) -> Result<PatchUndo<'a>, DatabaseError>;
+
+    /// The value of the present field of type `type_id`, if any
+    fn field(&self, type_id: TypeId) -> Option<&dyn Any>;
+
+    /// Like [`ApplyPatch::apply_undoable`], but keeps the patch unconditionally
+    async fn apply(self, db: &'a DB, key: Key<DB>, policy: PatchPolicy) -> Result<(), DatabaseError>
+    where
+        Self: Sized,
+        DB: 'a,
+    {
+        self.apply_undoable(db, key, policy).await.map(|_| ())
+    }
+}
+
+/// The previous cells of a row overwritten by an [`ApplyPatch`]
+#[must_use = "dropping a PatchUndo keeps the patch"]
+pub struct PatchUndo<'a>(Vec<LocalBoxFuture<'a, ()>>);
+
+impl<'a> PatchUndo<'a> {
+    #[doc(hidden)]
+    pub fn new(restores: Vec<LocalBoxFuture<'a, ()>>) -> Self {
+        PatchUndo(restores)
+    }
+
+    /// Puts back each overwritten cell, in the reverse order they were written
+    pub async fn undo(self) {
+        for restore in self.0.into_iter().rev() {
+            restore.await;
+        }
+    }
+}
+
+impl<T> Column<T> {
+    /// Writes a single patched cell according to `policy`, returning its previous value
+    pub async fn patch(
+        &self,
+        key: Key,
+        value: T,
+        policy: PatchPolicy,
+    ) -> Result<Option<T>, DatabaseError> {
+        match policy {
+            PatchPolicy::CreateMissing => self.insert(key, value).await,
+            PatchPolicy::RequireExisting => self.update(key, value).await.map(Some),
+        }
+    }
+}
+
+/// Declares the `Patch` type of a [`Row`]: a struct with an `Option` for each of its columns.
+///
+/// Fields must be listed in the same order as the row's `Insert` tuple,
+/// which the patch can be converted from.
+macro_rules! row_patch {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident { $($field:ident: $t:ty),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, PartialEq)]
+        $vis struct $name {
+            $(pub $field: Option<$t>,)*
+        }
+
+        impl From<($($t,)*)> for $name {
+            fn from(($($field,)*): ($($t,)*)) -> Self {
+                $name {
+                    $($field: Some($field),)*
+                }
+            }
+        }
+
+        #[async_trait::async_trait(?Send)]
+        impl<'a, DB> $crate::async_db::ApplyPatch<'a, DB> for $name
+        where
+            DB: $($crate::async_db::BorrowColumn<$t> +)*,
+        {
+            async fn apply_undoable(
+                self,
+                db: &'a DB,
+                key: $crate::async_db::Key<DB>,
+                policy: $crate::async_db::PatchPolicy,
+            ) -> Result<$crate::async_db::PatchUndo<'a>, $crate::async_db::DatabaseError> {
+                let key = key.untagged();
+                let mut rollback: Vec<futures::future::LocalBoxFuture<'a, ()>> = vec![];
+
+                $(
+                    if let Some(value) = self.$field {
+                        let column: &'a $crate::async_db::Column<$t> = db.borrow();
+                        match column.patch(key, value, policy).await {
+                            Ok(prev) => rollback.push(Box::pin(column.restore(key, prev))),
+                            Err(e) => {
+                                $crate::async_db::PatchUndo::new(rollback).undo().await;
+                                return Err(e);
+                            }
+                        }
+                    }
+                )*
+
+                Ok($crate::async_db::PatchUndo::new(rollback))
+            }
+
+            fn field(&self, type_id: std::any::TypeId) -> Option<&dyn std::any::Any> {
+                $(
+                    if type_id == std::any::TypeId::of::<$t>() {
+                        return self.$field.as_ref().map(|value| value as &dyn std::any::Any);
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_db::{
+        CellView, ColumnView, ColumnViewMut, IntFloatCharPatch, IntFloatCharRow, MyTable, Row,
+    };
+
+    #[async_std::test]
+    async fn insert_and_upsert() {
+        let table = MyTable::new().await;
+
+        IntFloatCharRow::insert(&table, Key::new(0), (4, 4.0, 'w'))
+            .await
+            .unwrap();
+        assert_eq!(*CellView::<i32>::new(&table, Key::new(0)).await, 4);
+
+        IntFloatCharRow::upsert(&table, Key::new(0), (5, 5.0, 'x'))
+            .await
+            .unwrap();
+        IntFloatCharRow::upsert(&table, Key::new(1), (6, 6.0, 'y'))
+            .await
+            .unwrap();
+
+        let row = IntFloatCharRow::new(&table, Key::new(0)).await;
+        assert_eq!((*row.int, *row.float, *row.char), (5, 5.0, 'x'));
+        drop(row);
+        assert!(IntFloatCharRow::try_new(&table, Key::new(1))
+            .await
+            .is_some());
+    }
+
+    #[async_std::test]
+    async fn patch_existing() {
+        let table = MyTable::new().await;
+
+        let patch = IntFloatCharPatch {
+            float: Some(9.5),
+            ..Default::default()
+        };
+        IntFloatCharRow::patch(&table, Key::new(2), patch, PatchPolicy::RequireExisting)
+            .await
+            .unwrap();
+
+        let row = IntFloatCharRow::new(&table, Key::new(2)).await;
+        assert_eq!((*row.int, *row.float, *row.char), (2, 9.5, '8'));
+    }
+
+    #[async_std::test]
+    async fn patch_missing() {
+        let table = MyTable::new().await;
+
+        // The int is written before the float is found to be missing, so it's rolled back
+        let patch = IntFloatCharPatch {
+            int: Some(4),
+            float: Some(4.0),
+            char: None,
+        };
+        assert_eq!(
+            IntFloatCharRow::patch(
+                &table,
+                Key::new(4),
+                patch.clone(),
+                PatchPolicy::RequireExisting
+            )
+            .await,
+            Err(DatabaseError::MissingCell {
+                column: "i32",
+                key: Key::new(4)
+            })
+        );
+        IntFloatCharRow::insert(&table, Key::new(5), (5, 5.0, 'x'))
+            .await
+            .unwrap();
+        ColumnViewMut::<f32, _>::new(&table)
+            .await
+            .remove(&Key::new(5));
+        assert_eq!(
+            IntFloatCharRow::patch(
+                &table,
+                Key::new(5),
+                patch.clone(),
+                PatchPolicy::RequireExisting
+            )
+            .await,
+            Err(DatabaseError::MissingCell {
+                column: "f32",
+                key: Key::new(5)
+            })
+        );
+        assert_eq!(*CellView::<i32>::new(&table, Key::new(5)).await, 5);
+
+        IntFloatCharRow::patch(&table, Key::new(4), patch, PatchPolicy::CreateMissing)
+            .await
+            .unwrap();
+        assert_eq!(*CellView::<f32>::new(&table, Key::new(4)).await, 4.0);
+        assert!(!ColumnView::<char, _>::new(&table)
+            .await
+            .contains_key(&Key::new(4)));
+    }
+}
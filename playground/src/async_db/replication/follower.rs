@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+
+use super::{Message, Mutation, ReplicaTable, Replicate, ReplicationError, Transport};
+
+/// Mirrors a [`Leader`]'s columns into read-only [`ReplicaTable`]s by applying its log
+pub struct Follower {
+    transport: Box<dyn Transport>,
+    tables: BTreeMap<&'static str, ReplicaTable>,
+    // `None` until a snapshot has been applied
+    seq: Option<u64>,
+}
+
+impl Follower {
+    pub fn new<T>(transport: T) -> Self
+    where
+        T: Transport + 'static,
+    {
+        Follower {
+            transport: Box::new(transport),
+            tables: Default::default(),
+            seq: None,
+        }
+    }
+
+    /// Mirrors the leader's column named `name` in the table `table`, whose cells are of type `T`.
+    /// Mutations to columns that aren't mirrored are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table already mirrors a column of `T`.
+    pub fn mirror<T>(&mut self, table: &'static str, name: &'static str) -> &mut Self
+    where
+        T: Replicate + Send + Sync + 'static,
+    {
+        self.tables.entry(table).or_default().mirror::<T>(name);
+        self
+    }
+
+    pub fn table(&self, name: &str) -> Option<&ReplicaTable> {
+        self.tables.get(name)
+    }
+
+    /// Sequence number of the last log entry applied
+    pub fn seq(&self) -> Option<u64> {
+        self.seq
+    }
+
+    /// Applies messages from the leader until the end of its next batch.
+    ///
+    /// If an entry is missing, the rest of the batch is discarded and a new snapshot requested,
+    /// which the leader will send along with its following batch.
+    pub async fn sync(&mut self) -> Result<(), ReplicationError> {
+        loop {
+            match self.transport.recv().await? {
+                Message::Snapshot { seq, cells } => {
+                    for table in self.tables.values() {
+                        table.clear().await;
+                    }
+                    for mutation in &cells {
+                        self.apply(mutation).await?;
+                    }
+                    self.seq = Some(seq);
+                }
+                Message::Entry { seq, mutation } => match self.seq {
+                    Some(last) if seq == last + 1 => {
+                        self.apply(&mutation).await?;
+                        self.seq = Some(seq);
+                    }
+                    Some(_) => self.resync().await?,
+                    None => (),
+                },
+                Message::Synced { seq } => match self.seq {
+                    Some(last) if seq == last => return Ok(()),
+                    Some(_) => self.resync().await?,
+                    None => (),
+                },
+                Message::Resync => (),
+            }
+        }
+    }
+
+    async fn apply(&self, mutation: &Mutation) -> Result<(), ReplicationError> {
+        match self.tables.get(mutation.table.as_str()) {
+            Some(table) => {
+                table
+                    .apply(&mutation.column, mutation.key, mutation.value.as_deref())
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Entries are ignored until the requested snapshot arrives
+    async fn resync(&mut self) -> Result<(), ReplicationError> {
+        self.seq = None;
+        self.transport.send(&Message::Resync).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+
+    use async_std::task;
+    use async_trait::async_trait;
+    use futures::future;
+
+    use super::*;
+    use crate::async_db::{
+        CellViewMut, ChannelTransport, Column, IntFloatCharRow, Key, Leader, MyTable, Row,
+        UnixTransport,
+    };
+
+    async fn assert_mirrored(follower: &Follower, ints: &[(usize, i32)]) {
+        let table = follower.table("my_table").unwrap();
+        let view = table.view::<i32>().await.unwrap();
+        assert!(view
+            .iter()
+            .map(|(key, _)| key.index())
+            .eq(ints.iter().map(|(key, _)| *key)));
+        for (key, int) in ints {
+            assert_eq!(*table.cell::<i32>(Key::new(*key)).await.unwrap(), *int);
+        }
+    }
+
+    #[async_std::test]
+    async fn snapshot_and_entries() {
+        let table = MyTable::new().await;
+        let mut leader = Leader::new();
+        leader
+            .replicate::<i32>("my_table", "ints", table.borrow())
+            .replicate::<String>("my_table", "strings", table.borrow());
+
+        let (leader_end, follower_end) = ChannelTransport::pair();
+        let mut follower = Follower::new(follower_end);
+        follower
+            .mirror::<i32>("my_table", "ints")
+            .mirror::<String>("my_table", "strings");
+
+        leader.add_follower(leader_end).await;
+        follower.sync().await.unwrap();
+        assert_mirrored(&follower, &[(0, 1), (2, 2), (3, 3)]).await;
+
+        IntFloatCharRow::insert(&table, Key::new(4), (4, 7.0, 'a'))
+            .await
+            .unwrap();
+        *CellViewMut::<i32>::new(&table, Key::new(0)).await = 10;
+        IntFloatCharRow::remove(&table, Key::new(2)).await;
+
+        leader.sync().await;
+        follower.sync().await.unwrap();
+        assert_mirrored(&follower, &[(0, 10), (3, 3), (4, 4)]).await;
+        assert_eq!(follower.seq(), Some(leader.seq()));
+
+        let replica = follower.table("my_table").unwrap();
+        assert_eq!(*replica.cell::<String>(Key::new(4)).await.unwrap(), "4a");
+        assert!(replica.cell::<String>(Key::new(5)).await.is_none());
+        assert!(replica.view::<f32>().await.is_none());
+    }
+
+    #[async_std::test]
+    async fn columns_matched_by_name() {
+        let (ints, others) = (Column::<i32>::default(), Column::<i32>::default());
+        ints.insert(Key::new(0), 1).await.unwrap();
+        others.insert(Key::new(0), 2).await.unwrap();
+
+        let mut leader = Leader::new();
+        leader
+            .replicate("ints", "values", &ints)
+            .replicate("ints", "others", &others);
+
+        let (leader_end, follower_end) = ChannelTransport::pair();
+        let mut follower = Follower::new(follower_end);
+        follower.mirror::<i32>("ints", "others");
+        leader.add_follower(leader_end).await;
+        follower.sync().await.unwrap();
+
+        let replica = follower.table("ints").unwrap();
+        assert_eq!(*replica.cell::<i32>(Key::new(0)).await.unwrap(), 2);
+    }
+
+    /// Drops the first entry it's asked to deliver
+    struct LossyTransport {
+        inner: ChannelTransport,
+        dropped: bool,
+    }
+
+    #[async_trait(?Send)]
+    impl Transport for LossyTransport {
+        async fn send(&mut self, message: &Message) -> Result<(), ReplicationError> {
+            if let Message::Entry { .. } = message {
+                if !self.dropped {
+                    self.dropped = true;
+                    return Ok(());
+                }
+            }
+            self.inner.send(message).await
+        }
+
+        async fn recv(&mut self) -> Result<Message, ReplicationError> {
+            self.inner.recv().await
+        }
+
+        fn try_recv(&mut self) -> Result<Option<Message>, ReplicationError> {
+            self.inner.try_recv()
+        }
+    }
+
+    #[async_std::test]
+    async fn gap_triggers_resync() {
+        let ints = Column::<i32>::default();
+        let mut leader = Leader::new();
+        leader.replicate("ints", "values", &ints);
+
+        let (leader_end, follower_end) = ChannelTransport::pair();
+        let mut follower = Follower::new(follower_end);
+        follower.mirror::<i32>("ints", "values");
+        leader
+            .add_follower(LossyTransport {
+                inner: leader_end,
+                dropped: false,
+            })
+            .await;
+        follower.sync().await.unwrap();
+
+        ints.insert(Key::new(0), 0).await.unwrap();
+        ints.insert(Key::new(1), 1).await.unwrap();
+        leader.sync().await;
+
+        // The batch is missing its first entry, so the follower asks for a snapshot
+        // and waits for the leader's next batch to bring it
+        let (synced, _) = future::join(follower.sync(), async {
+            task::yield_now().await;
+            leader.sync().await;
+        })
+        .await;
+        synced.unwrap();
+
+        let replica = follower.table("ints").unwrap();
+        assert_eq!(replica.view::<i32>().await.unwrap().len(), 2);
+        assert_eq!(follower.seq(), Some(2));
+    }
+
+    #[async_std::test]
+    async fn unix_socket() {
+        let ints = Column::<i32>::default();
+        ints.insert(Key::new(0), 1).await.unwrap();
+
+        let mut leader = Leader::new();
+        leader.replicate("ints", "values", &ints);
+
+        let (leader_end, follower_end) = UnixTransport::pair().unwrap();
+        let mut follower = Follower::new(follower_end);
+        follower.mirror::<i32>("ints", "values");
+        leader.add_follower(leader_end).await;
+        follower.sync().await.unwrap();
+
+        ints.insert(Key::new(1), 2).await.unwrap();
+        leader.sync().await;
+        follower.sync().await.unwrap();
+
+        let replica = follower.table("ints").unwrap();
+        assert_eq!(*replica.cell::<i32>(Key::new(1)).await.unwrap(), 2);
+
+        drop(follower);
+        // Wait for the leader's socket to notice the follower hung up
+        while leader.follower_count() > 0 {
+            task::yield_now().await;
+            leader.sync().await;
+        }
+    }
+}
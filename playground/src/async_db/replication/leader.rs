@@ -0,0 +1,223 @@
+use std::collections::BTreeSet;
+
+use async_std::channel::Receiver;
+use async_trait::async_trait;
+
+use crate::async_db::{Column, ColumnEvent, Key};
+
+use super::{Message, Mutation, Replicate, Transport};
+
+/// A column whose mutations are shipped by a [`Leader`]
+#[async_trait(?Send)]
+trait ReplicatedColumn {
+    /// Mutations for each cell changed since the last call, in the order they were first changed
+    async fn changes(&self) -> Vec<Mutation>;
+
+    /// Mutations that recreate every cell of the column
+    async fn snapshot(&self) -> Vec<Mutation>;
+}
+
+struct LeaderColumn<'a, T> {
+    table: &'static str,
+    name: &'static str,
+    column: &'a Column<T>,
+    events: Receiver<ColumnEvent>,
+}
+
+impl<'a, T> LeaderColumn<'a, T>
+where
+    T: Replicate,
+{
+    // The log carries the state of the cell when it's shipped, so applying it is idempotent
+    async fn mutation(&self, key: Key) -> Mutation {
+        let shard = self.column.shards().shard(&key).read().await;
+        let value = match shard.get(&key) {
+            Some(cell) => {
+                let mut buf = vec![];
+                cell.read().await.encode(&mut buf);
+                Some(buf)
+            }
+            None => None,
+        };
+
+        Mutation {
+            table: self.table.into(),
+            column: self.name.into(),
+            key,
+            value,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, T> ReplicatedColumn for LeaderColumn<'a, T>
+where
+    T: Replicate,
+{
+    async fn changes(&self) -> Vec<Mutation> {
+        // Derived cells are only written, and their events sent, when the column is refreshed
+        self.column.refresh().await;
+
+        let mut seen = BTreeSet::new();
+        let mut keys = vec![];
+        while let Ok(event) = self.events.try_recv() {
+            if seen.insert(event.key()) {
+                keys.push(event.key());
+            }
+        }
+
+        let mut mutations = Vec::with_capacity(keys.len());
+        for key in keys {
+            mutations.push(self.mutation(key).await);
+        }
+        mutations
+    }
+
+    async fn snapshot(&self) -> Vec<Mutation> {
+        self.column.refresh().await;
+
+        let mut mutations = vec![];
+        for shard in self.column.shards().iter() {
+            let keys = shard.read().await.keys().copied().collect::<Vec<_>>();
+            for key in keys {
+                mutations.push(self.mutation(key).await);
+            }
+        }
+        mutations
+    }
+}
+
+/// Ships the ordered log of mutations to a set of columns out to [`Follower`]s.
+///
+/// Each entry of the log is numbered, so that followers can detect any they've missed
+/// and ask for a fresh snapshot.
+#[derive(Default)]
+pub struct Leader<'a> {
+    columns: Vec<Box<dyn ReplicatedColumn + 'a>>,
+    followers: Vec<Box<dyn Transport>>,
+    seq: u64,
+}
+
+impl<'a> Leader<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds `column` of the table `table` to the replicated set, under the name `name`.
+    ///
+    /// Columns are identified on the wire by table and column name,
+    /// which followers must [`mirror`](Follower::mirror) them under.
+    pub fn replicate<T>(
+        &mut self,
+        table: &'static str,
+        name: &'static str,
+        column: &'a Column<T>,
+    ) -> &mut Self
+    where
+        T: Replicate + 'a,
+    {
+        self.columns.push(Box::new(LeaderColumn {
+            table,
+            name,
+            column,
+            events: column.subscribe(),
+        }));
+        self
+    }
+
+    /// Sequence number of the last entry shipped
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn follower_count(&self) -> usize {
+        self.followers.len()
+    }
+
+    /// Starts shipping to a new follower, catching it up with a snapshot of the replicated columns
+    pub async fn add_follower<T>(&mut self, transport: T)
+    where
+        T: Transport + 'static,
+    {
+        // Existing followers are brought up to date first, so the snapshot starts at a clean seq
+        self.sync().await;
+
+        let mut transport: Box<dyn Transport> = Box::new(transport);
+        let snapshot = self.snapshot().await;
+        if transport.send(&snapshot).await.is_ok()
+            && transport
+                .send(&Message::Synced { seq: self.seq })
+                .await
+                .is_ok()
+        {
+            self.followers.push(transport);
+        }
+    }
+
+    /// Ships every mutation made since the last sync as a batch of log entries.
+    ///
+    /// Followers that asked for a resync are sent a snapshot, and disconnected followers are dropped.
+    pub async fn sync(&mut self) {
+        let mut resync = vec![false; self.followers.len()];
+        let mut connected = vec![true; self.followers.len()];
+        for (i, follower) in self.followers.iter_mut().enumerate() {
+            loop {
+                match follower.try_recv() {
+                    Ok(Some(Message::Resync)) => resync[i] = true,
+                    Ok(Some(_)) => (),
+                    Ok(None) => break,
+                    Err(_) => {
+                        connected[i] = false;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut entries = vec![];
+        for column in &self.columns {
+            for mutation in column.changes().await {
+                self.seq += 1;
+                entries.push(Message::Entry {
+                    seq: self.seq,
+                    mutation,
+                });
+            }
+        }
+
+        let snapshot = match resync.iter().any(|resync| *resync) {
+            true => Some(self.snapshot().await),
+            false => None,
+        };
+        let synced = Message::Synced { seq: self.seq };
+
+        for (i, follower) in self.followers.iter_mut().enumerate() {
+            let messages = entries
+                .iter()
+                .chain(snapshot.iter().filter(|_| resync[i]))
+                .chain(std::iter::once(&synced));
+
+            for message in messages {
+                if !connected[i] {
+                    break;
+                }
+                connected[i] = follower.send(message).await.is_ok();
+            }
+        }
+
+        let mut connected = connected.into_iter();
+        self.followers.retain(|_| connected.next().unwrap());
+    }
+
+    async fn snapshot(&self) -> Message {
+        let mut cells = vec![];
+        for column in &self.columns {
+            cells.extend(column.snapshot().await);
+        }
+
+        Message::Snapshot {
+            seq: self.seq,
+            cells,
+        }
+    }
+}
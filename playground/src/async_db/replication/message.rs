@@ -0,0 +1,100 @@
+use crate::async_db::Key;
+
+use super::{Replicate, ReplicationError};
+
+/// The new state of one cell of a replicated column, or `None` if it was removed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mutation {
+    pub table: String,
+    pub column: String,
+    pub key: Key,
+    pub value: Option<Vec<u8>>,
+}
+
+/// A frame of the replication protocol.
+///
+/// Everything but [`Message::Resync`] flows from the leader to its followers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// The full state of every replicated column, as of log entry `seq`
+    Snapshot { seq: u64, cells: Vec<Mutation> },
+    /// A single entry of the log
+    Entry { seq: u64, mutation: Mutation },
+    /// Marks the end of a batch of entries, the last of which was `seq`
+    Synced { seq: u64 },
+    /// Sent by a follower that detected a gap, asking for a new snapshot
+    Resync,
+}
+
+impl Message {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        self.encode(&mut buf);
+        buf
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ReplicationError> {
+        match Message::decode(&mut bytes) {
+            Some(message) if bytes.is_empty() => Ok(message),
+            _ => Err(ReplicationError::Malformed("message")),
+        }
+    }
+}
+
+impl Replicate for Mutation {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.table.encode(buf);
+        self.column.encode(buf);
+        self.key.encode(buf);
+        self.value.encode(buf);
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        Some(Mutation {
+            table: String::decode(buf)?,
+            column: String::decode(buf)?,
+            key: Key::decode(buf)?,
+            value: Option::decode(buf)?,
+        })
+    }
+}
+
+impl Replicate for Message {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Message::Snapshot { seq, cells } => {
+                0u8.encode(buf);
+                seq.encode(buf);
+                cells.encode(buf);
+            }
+            Message::Entry { seq, mutation } => {
+                1u8.encode(buf);
+                seq.encode(buf);
+                mutation.encode(buf);
+            }
+            Message::Synced { seq } => {
+                2u8.encode(buf);
+                seq.encode(buf);
+            }
+            Message::Resync => 3u8.encode(buf),
+        }
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        Some(match u8::decode(buf)? {
+            0 => Message::Snapshot {
+                seq: u64::decode(buf)?,
+                cells: Vec::decode(buf)?,
+            },
+            1 => Message::Entry {
+                seq: u64::decode(buf)?,
+                mutation: Mutation::decode(buf)?,
+            },
+            2 => Message::Synced {
+                seq: u64::decode(buf)?,
+            },
+            3 => Message::Resync,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,15 @@
+mod follower;
+mod leader;
+mod message;
+mod replica_table;
+mod replicate;
+mod replication_error;
+mod transport;
+
+pub use follower::*;
+pub use leader::*;
+pub use message::*;
+pub use replica_table::*;
+pub use replicate::*;
+pub use replication_error::*;
+pub use transport::*;
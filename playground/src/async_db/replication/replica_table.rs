@@ -0,0 +1,138 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+};
+
+use async_trait::async_trait;
+
+use crate::async_db::{CellView, Column, ColumnView, ColumnViewMut, Key, ReadOnly};
+
+use super::{Replicate, ReplicationError};
+
+/// A column that can be written by a [`Follower`] applying the replication log
+#[async_trait(?Send)]
+trait MirrorColumn: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    /// Sets the cell `key` to the encoded `value`, or removes it if `value` is `None`
+    async fn apply(&self, key: Key, value: Option<&[u8]>) -> Result<(), ReplicationError>;
+
+    async fn clear(&self);
+}
+
+#[async_trait(?Send)]
+impl<T> MirrorColumn for Column<T>
+where
+    T: Replicate + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn apply(&self, key: Key, value: Option<&[u8]>) -> Result<(), ReplicationError> {
+        let value = match value {
+            Some(mut bytes) => Some(
+                T::decode(&mut bytes)
+                    .filter(|_| bytes.is_empty())
+                    .ok_or(ReplicationError::Malformed("cell"))?,
+            ),
+            None => None,
+        };
+
+        // Restoring skips the read-only constraint
+        self.restore(key, value).await;
+        Ok(())
+    }
+
+    async fn clear(&self) {
+        let mut view = ColumnViewMut::new(self).await;
+        let keys = view.keys().collect::<Vec<_>>();
+        for key in keys {
+            view.remove(&key);
+        }
+    }
+}
+
+/// A read-only mirror of a leader's table, kept up to date by a [`Follower`].
+///
+/// Only hands out [`ColumnView`]s and [`CellView`]s of its mirrored columns, so the mirror
+/// can't diverge from the leader.
+#[derive(Default)]
+pub struct ReplicaTable {
+    columns: HashMap<TypeId, Box<dyn MirrorColumn>>,
+    names: HashMap<&'static str, TypeId>,
+}
+
+impl ReplicaTable {
+    /// Mirrors the leader's column named `name`, whose cells are of type `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column of `T` is already mirrored, as columns are viewed by cell type.
+    pub(crate) fn mirror<T>(&mut self, name: &'static str)
+    where
+        T: Replicate + Send + Sync + 'static,
+    {
+        assert!(
+            !self.contains_column::<T>(),
+            "a column of {} is already mirrored",
+            type_name::<T>()
+        );
+        let column = Column::<T>::default().with_constraint(ReadOnly);
+        self.names.insert(name, TypeId::of::<T>());
+        self.columns.insert(TypeId::of::<T>(), Box::new(column));
+    }
+
+    pub fn contains_column<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.columns.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Views the mirrored column of `T`, or returns `None` if it isn't mirrored
+    pub async fn view<T>(&self) -> Option<ColumnView<'_, T, ReplicaTable>>
+    where
+        T: 'static,
+    {
+        Some(ColumnView::of(self.column::<T>()?).await)
+    }
+
+    /// Views the cell `key` of the mirrored column of `T`,
+    /// or returns `None` if the column isn't mirrored or doesn't contain it
+    pub async fn cell<T>(&self, key: Key<ReplicaTable>) -> Option<CellView<'_, T>>
+    where
+        T: 'static,
+    {
+        let column = self.column::<T>()?;
+        CellView::try_new(column, key.cast()).await
+    }
+
+    fn column<T>(&self) -> Option<&Column<T>>
+    where
+        T: 'static,
+    {
+        self.columns
+            .get(&TypeId::of::<T>())
+            .and_then(|column| column.as_any().downcast_ref())
+    }
+
+    pub(crate) async fn apply(
+        &self,
+        column: &str,
+        key: Key,
+        value: Option<&[u8]>,
+    ) -> Result<(), ReplicationError> {
+        // Columns the follower doesn't mirror are skipped
+        match self.names.get(column) {
+            Some(type_id) => self.columns[type_id].apply(key, value).await,
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) async fn clear(&self) {
+        for column in self.columns.values() {
+            column.clear().await;
+        }
+    }
+}
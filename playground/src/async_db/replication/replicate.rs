@@ -0,0 +1,189 @@
+use std::convert::TryInto;
+
+use crate::async_db::{ForeignKey, Key};
+
+/// A cell type that can be written to and read back from the replication log
+pub trait Replicate: Sized {
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// Reads a value from the front of `buf`, advancing it past the bytes consumed
+    fn decode(buf: &mut &[u8]) -> Option<Self>;
+}
+
+/// Splits `len` bytes off the front of `buf`
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if buf.len() < len {
+        return None;
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Some(head)
+}
+
+macro_rules! impl_replicate_bytes {
+    ($($t:ty),*) => {
+        $(
+            impl Replicate for $t {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(buf: &mut &[u8]) -> Option<Self> {
+                    let bytes = take(buf, std::mem::size_of::<$t>())?;
+                    Some(<$t>::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_replicate_bytes!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Replicate for bool {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (*self as u8).encode(buf)
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        match u8::decode(buf)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Replicate for char {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (*self as u32).encode(buf)
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        std::char::from_u32(u32::decode(buf)?)
+    }
+}
+
+impl Replicate for usize {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (*self as u64).encode(buf)
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        u64::decode(buf)?.try_into().ok()
+    }
+}
+
+impl<T> Replicate for Key<T> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.index().encode(buf)
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        usize::decode(buf).map(Key::new)
+    }
+}
+
+impl Replicate for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.len().encode(buf);
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        let len = usize::decode(buf)?;
+        String::from_utf8(take(buf, len)?.to_vec()).ok()
+    }
+}
+
+impl<T> Replicate for Option<T>
+where
+    T: Replicate,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                true.encode(buf);
+                value.encode(buf);
+            }
+            None => false.encode(buf),
+        }
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        match bool::decode(buf)? {
+            true => T::decode(buf).map(Some),
+            false => Some(None),
+        }
+    }
+}
+
+impl<T> Replicate for Vec<T>
+where
+    T: Replicate,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.len().encode(buf);
+        for value in self {
+            value.encode(buf);
+        }
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        let len = usize::decode(buf)?;
+        // Don't trust the length for preallocation, it comes from the wire
+        let mut values = Vec::with_capacity(len.min(buf.len()));
+        for _ in 0..len {
+            values.push(T::decode(buf)?);
+        }
+        Some(values)
+    }
+}
+
+impl<T> Replicate for ForeignKey<T> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.key().encode(buf)
+    }
+
+    fn decode(buf: &mut &[u8]) -> Option<Self> {
+        Some(match Option::<Key<T>>::decode(buf)? {
+            Some(key) => ForeignKey::new(key),
+            None => ForeignKey::null(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T>(value: T)
+    where
+        T: Replicate + PartialEq + std::fmt::Debug,
+    {
+        let mut buf = vec![];
+        value.encode(&mut buf);
+
+        let mut bytes = &buf[..];
+        assert_eq!(T::decode(&mut bytes), Some(value));
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(-5i32);
+        round_trip(2.5f32);
+        round_trip('λ');
+        round_trip(String::from("hello"));
+        round_trip(Some(Key::<()>::new(3)));
+        round_trip(vec![Some(1u8), None]);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buf = vec![];
+        String::from("hello").encode(&mut buf);
+        buf.pop();
+
+        assert_eq!(String::decode(&mut &buf[..]), None);
+    }
+}
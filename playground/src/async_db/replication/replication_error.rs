@@ -0,0 +1,38 @@
+use std::{fmt::Display, io::ErrorKind};
+
+/// Errors produced while shipping or applying the replication log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicationError {
+    Disconnected,
+    Io(ErrorKind),
+    Malformed(&'static str),
+    FrameTooLarge { len: usize, max: usize },
+}
+
+impl Display for ReplicationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplicationError::Disconnected => write!(f, "Replication peer disconnected"),
+            ReplicationError::Io(kind) => write!(f, "Replication transport failed: {:?}", kind),
+            ReplicationError::Malformed(what) => write!(f, "Malformed replication {}", what),
+            ReplicationError::FrameTooLarge { len, max } => write!(
+                f,
+                "Replication frame of {} bytes exceeds the limit of {}",
+                len, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplicationError {}
+
+impl From<std::io::Error> for ReplicationError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            ErrorKind::UnexpectedEof | ErrorKind::BrokenPipe | ErrorKind::ConnectionReset => {
+                ReplicationError::Disconnected
+            }
+            kind => ReplicationError::Io(kind),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use async_std::{
+    channel::{unbounded, Receiver, Sender, TryRecvError},
+    io::prelude::{ReadExt, WriteExt},
+    os::unix::net::UnixStream,
+    task,
+};
+use async_trait::async_trait;
+
+use super::{Message, ReplicationError};
+
+/// One end of a connection between a replication leader and follower
+#[async_trait(?Send)]
+pub trait Transport {
+    async fn send(&mut self, message: &Message) -> Result<(), ReplicationError>;
+
+    /// Waits for the next message from the other end
+    async fn recv(&mut self) -> Result<Message, ReplicationError>;
+
+    /// Returns the next message if one has already arrived
+    fn try_recv(&mut self) -> Result<Option<Message>, ReplicationError>;
+}
+
+/// Largest frame a [`UnixTransport`] will send or accept, so a corrupt or hostile length prefix
+/// can't make the reader allocate without bound
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// A received frame, or the error that ended the connection it was read from
+type Frame = Result<Vec<u8>, ReplicationError>;
+
+/// Converts a closed or empty channel into the result of a [`Transport::try_recv`]
+fn try_recv_frame(incoming: &Receiver<Frame>) -> Result<Option<Message>, ReplicationError> {
+    match incoming.try_recv() {
+        Ok(frame) => Message::from_bytes(&frame?).map(Some),
+        Err(TryRecvError::Empty) => Ok(None),
+        Err(TryRecvError::Closed) => Err(ReplicationError::Disconnected),
+    }
+}
+
+/// An in-process transport, for replicating to a follower in the same process
+#[derive(Debug)]
+pub struct ChannelTransport {
+    outgoing: Sender<Frame>,
+    incoming: Receiver<Frame>,
+}
+
+impl ChannelTransport {
+    /// Creates a connected pair of transports
+    pub fn pair() -> (Self, Self) {
+        let (a_sender, a_receiver) = unbounded();
+        let (b_sender, b_receiver) = unbounded();
+
+        (
+            ChannelTransport {
+                outgoing: a_sender,
+                incoming: b_receiver,
+            },
+            ChannelTransport {
+                outgoing: b_sender,
+                incoming: a_receiver,
+            },
+        )
+    }
+}
+
+#[async_trait(?Send)]
+impl Transport for ChannelTransport {
+    async fn send(&mut self, message: &Message) -> Result<(), ReplicationError> {
+        self.outgoing
+            .send(Ok(message.to_bytes()))
+            .await
+            .map_err(|_| ReplicationError::Disconnected)
+    }
+
+    async fn recv(&mut self) -> Result<Message, ReplicationError> {
+        let frame = self
+            .incoming
+            .recv()
+            .await
+            .map_err(|_| ReplicationError::Disconnected)?;
+        Message::from_bytes(&frame?)
+    }
+
+    fn try_recv(&mut self) -> Result<Option<Message>, ReplicationError> {
+        try_recv_frame(&self.incoming)
+    }
+}
+
+/// A transport over a Unix domain socket, for replicating to another process.
+///
+/// Messages are framed with a little-endian `u32` length, of at most [`MAX_FRAME_LEN`].
+/// Incoming frames are read by a background task so that [`Transport::try_recv`] never blocks.
+#[derive(Debug)]
+pub struct UnixTransport {
+    stream: UnixStream,
+    incoming: Receiver<Frame>,
+}
+
+impl UnixTransport {
+    pub fn new(stream: UnixStream) -> Self {
+        let (sender, incoming) = unbounded();
+        task::spawn(Self::read_frames(stream.clone(), sender));
+        UnixTransport { stream, incoming }
+    }
+
+    pub async fn connect<P>(path: P) -> Result<Self, ReplicationError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(UnixTransport::new(
+            UnixStream::connect(path.as_ref()).await?,
+        ))
+    }
+
+    /// Creates a connected pair of transports over an unnamed socket
+    pub fn pair() -> Result<(Self, Self), ReplicationError> {
+        let (a, b) = UnixStream::pair()?;
+        Ok((UnixTransport::new(a), UnixTransport::new(b)))
+    }
+
+    // Runs until the socket or the transport is closed, or an oversized frame arrives
+    async fn read_frames(mut stream: UnixStream, sender: Sender<Frame>) {
+        loop {
+            let mut len = [0; 4];
+            if stream.read_exact(&mut len).await.is_err() {
+                return;
+            }
+
+            let len = u32::from_le_bytes(len) as usize;
+            if len > MAX_FRAME_LEN {
+                sender
+                    .send(Err(ReplicationError::FrameTooLarge {
+                        len,
+                        max: MAX_FRAME_LEN,
+                    }))
+                    .await
+                    .ok();
+                return;
+            }
+
+            let mut frame = vec![0; len];
+            if stream.read_exact(&mut frame).await.is_err() || sender.send(Ok(frame)).await.is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Transport for UnixTransport {
+    async fn send(&mut self, message: &Message) -> Result<(), ReplicationError> {
+        let frame = message.to_bytes();
+        if frame.len() > MAX_FRAME_LEN {
+            return Err(ReplicationError::FrameTooLarge {
+                len: frame.len(),
+                max: MAX_FRAME_LEN,
+            });
+        }
+        let len = frame.len() as u32;
+
+        self.stream.write_all(&len.to_le_bytes()).await?;
+        self.stream.write_all(&frame).await?;
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<Message, ReplicationError> {
+        let frame = self
+            .incoming
+            .recv()
+            .await
+            .map_err(|_| ReplicationError::Disconnected)?;
+        Message::from_bytes(&frame?)
+    }
+
+    fn try_recv(&mut self) -> Result<Option<Message>, ReplicationError> {
+        try_recv_frame(&self.incoming)
+    }
+}
+
+impl Drop for UnixTransport {
+    // Stops the reader task along with the peer's
+    fn drop(&mut self) {
+        self.stream.shutdown(std::net::Shutdown::Both).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn unix_round_trip() {
+        let (mut leader, mut follower) = UnixTransport::pair().unwrap();
+
+        assert_eq!(follower.try_recv(), Ok(None));
+        leader.send(&Message::Synced { seq: 3 }).await.unwrap();
+        assert_eq!(follower.recv().await, Ok(Message::Synced { seq: 3 }));
+
+        follower.send(&Message::Resync).await.unwrap();
+        assert_eq!(leader.recv().await, Ok(Message::Resync));
+
+        drop(leader);
+        assert_eq!(follower.recv().await, Err(ReplicationError::Disconnected));
+    }
+
+    #[async_std::test]
+    async fn oversized_frame() {
+        let (mut leader, stream) = UnixStream::pair().unwrap();
+        let mut follower = UnixTransport::new(stream);
+
+        leader.write_all(&u32::MAX.to_le_bytes()).await.unwrap();
+        assert_eq!(
+            follower.recv().await,
+            Err(ReplicationError::FrameTooLarge {
+                len: u32::MAX as usize,
+                max: MAX_FRAME_LEN
+            })
+        );
+        assert_eq!(follower.recv().await, Err(ReplicationError::Disconnected));
+    }
+}
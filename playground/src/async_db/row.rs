@@ -1,16 +1,71 @@
-use std::collections::BTreeSet;
+use std::{any::type_name, collections::BTreeSet, time::Duration};
 
-use crate::async_db::Key;
+use crate::async_db::{ApplyPatch, DatabaseError, Key, PatchPolicy};
 use async_trait::async_trait;
+use futures::stream::LocalBoxStream;
 
 /// A type that can act as a virtual table row, containing references to the underlying cell data.
+///
+/// Rows are keyed by `Key<DB>`, so a key can only address rows of the table it came from.
 #[async_trait(?Send)]
 pub trait Row<'a, DB> {
     type Insert;
+    /// The row with every field optional, declared with `row_patch!`
+    type Patch: ApplyPatch<'a, DB> + From<Self::Insert>;
 
-    async fn new(db: &'a DB, key: Key) -> Self;
-    async fn insert(db: &'a DB, key: Key, row: Self::Insert);
-    async fn remove(db: &'a DB, key: Key);
-    async fn keys(db: &'a DB) -> BTreeSet<Key>;
-    async fn common_keys(db: &'a DB) -> BTreeSet<Key>;
+    async fn new(db: &'a DB, key: Key<DB>) -> Self;
+    /// Like [`Row::new`], but returns `None` if any of the row's cells are missing
+    async fn try_new(db: &'a DB, key: Key<DB>) -> Option<Self>
+    where
+        Self: Sized;
+    /// Waits until every cell of the row exists, without holding any locks in the meantime
+    async fn wait_for(db: &'a DB, key: Key<DB>) -> Self;
+    /// Inserts each cell of the row, rolling back the ones already written if any fails.
+    /// Cells that already exist are overwritten, and restored if the row can't be written.
+    ///
+    /// Only column constraints are checked; table hooks and foreign keys are run by
+    /// [`Database::insert`].
+    async fn insert(db: &'a DB, key: Key<DB>, row: Self::Insert) -> Result<(), DatabaseError>;
+    async fn remove(db: &'a DB, key: Key<DB>);
+    async fn keys(db: &'a DB) -> BTreeSet<Key<DB>>;
+    /// Lazily yields the keys present in every column of the row, as planned by a [`Join`]
+    fn common_keys(db: &'a DB) -> LocalBoxStream<'a, Key<DB>>;
+
+    /// Inserts the row, overwriting any cells that already exist
+    async fn upsert(db: &'a DB, key: Key<DB>, row: Self::Insert) -> Result<(), DatabaseError>
+    where
+        'a: 'async_trait,
+    {
+        Self::patch(db, key, row.into(), PatchPolicy::CreateMissing).await
+    }
+
+    /// Writes only the fields present in `patch`, treating missing cells according to `policy`
+    async fn patch(
+        db: &'a DB,
+        key: Key<DB>,
+        patch: Self::Patch,
+        policy: PatchPolicy,
+    ) -> Result<(), DatabaseError>
+    where
+        'a: 'async_trait,
+    {
+        patch.apply(db, key, policy).await
+    }
+
+    /// Like [`Row::wait_for`], but gives up once `timeout` has elapsed
+    async fn wait_for_timeout(
+        db: &'a DB,
+        key: Key<DB>,
+        timeout: Duration,
+    ) -> Result<Self, DatabaseError>
+    where
+        Self: Sized,
+    {
+        async_std::future::timeout(timeout, Self::wait_for(db, key))
+            .await
+            .map_err(|_| DatabaseError::WaitTimeout {
+                waiting_for: type_name::<Self>(),
+                key: key.untagged(),
+            })
+    }
 }
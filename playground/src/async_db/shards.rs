@@ -0,0 +1,211 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{CellLock, ColumnLock, Key, ReadColumn, ShardCollection, WriteColumn};
+
+/// Number of shards used by [`Column::default`]
+pub const DEFAULT_SHARDS: usize = 16;
+
+/// The cells of a [`Column`], split by key across independently-locked maps.
+///
+/// Keys are assigned to shards by modulo, so runs of sequential keys are spread evenly.
+#[derive(Debug)]
+pub struct Shards<T>(Vec<ColumnLock<T>>);
+
+impl<T> Shards<T> {
+    pub fn new(count: usize) -> Self {
+        assert!(count > 0, "A column needs at least one shard");
+        Shards((0..count).map(|_| Default::default()).collect())
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn index(&self, key: &Key) -> usize {
+        key.index() % self.0.len()
+    }
+
+    /// The shard responsible for `key`
+    pub fn shard(&self, key: &Key) -> &ColumnLock<T> {
+        &self.0[self.index(key)]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ColumnLock<T>> {
+        self.0.iter()
+    }
+
+    /// Read-locks every shard, in index order
+    pub async fn read_all(&self) -> ColumnCollection<ReadColumn<'_, T>> {
+        let mut guards = Vec::with_capacity(self.0.len());
+        for shard in &self.0 {
+            guards.push(shard.read().await);
+        }
+        ColumnCollection { guards }
+    }
+
+    /// Write-locks every shard, in index order
+    pub async fn write_all(&self) -> ColumnCollection<WriteColumn<'_, T>> {
+        let mut guards = Vec::with_capacity(self.0.len());
+        for shard in &self.0 {
+            guards.push(shard.write().await);
+        }
+        ColumnCollection { guards }
+    }
+
+    /// Write-locks every shard, or returns `None` without waiting if any of them is in use
+    pub fn try_write_all(&self) -> Option<ColumnCollection<WriteColumn<'_, T>>> {
+        let guards = self
+            .0
+            .iter()
+            .map(|shard| shard.try_write())
+            .collect::<Option<_>>()?;
+        Some(ColumnCollection { guards })
+    }
+}
+
+/// The cells of a whole [`Column`], held through a guard `G` on each of its shards
+#[derive(Debug)]
+pub struct ColumnCollection<G> {
+    guards: Vec<G>,
+}
+
+impl<G, T> ColumnCollection<G>
+where
+    G: Deref<Target = ShardCollection<T>>,
+{
+    pub fn get(&self, key: &Key) -> Option<&CellLock<T>> {
+        self.shard(key).get(key)
+    }
+
+    pub fn contains_key(&self, key: &Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.guards.iter().map(|guard| guard.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guards.iter().all(|guard| guard.is_empty())
+    }
+
+    /// Keys of every shard, merged into ascending order
+    pub fn keys<'b>(&'b self) -> impl Iterator<Item = Key> + 'b
+    where
+        T: 'b,
+    {
+        itertools::Itertools::kmerge(self.guards.iter().map(|guard| guard.keys())).copied()
+    }
+
+    /// Cells of every shard, merged into ascending key order
+    pub fn iter<'b>(&'b self) -> impl Iterator<Item = (Key, &'b CellLock<T>)> + 'b
+    where
+        T: 'b,
+    {
+        itertools::Itertools::kmerge_by(
+            self.guards.iter().map(|guard| guard.iter()),
+            |(lhs, _): &(&Key, _), (rhs, _): &(&Key, _)| lhs < rhs,
+        )
+        .map(|(key, cell)| (*key, cell))
+    }
+
+    /// The shard responsible for `key`
+    pub fn shard(&self, key: &Key) -> &ShardCollection<T> {
+        &self.guards[key.index() % self.guards.len()]
+    }
+}
+
+impl<G, T> ColumnCollection<G>
+where
+    G: DerefMut<Target = ShardCollection<T>>,
+{
+    pub fn get_mut(&mut self, key: &Key) -> Option<&mut CellLock<T>> {
+        self.shard_mut(key).get_mut(key)
+    }
+
+    pub(crate) fn shard_mut(&mut self, key: &Key) -> &mut ShardCollection<T> {
+        let shard = key.index() % self.guards.len();
+        &mut self.guards[shard]
+    }
+}
+
+/// Implements the read-only map methods of a view over a `column` field, keyed by the view's table
+macro_rules! impl_shard_reads {
+    ($view:ident) => {
+        impl<'a, T, DB> $view<'a, T, DB> {
+            pub fn get(&self, key: &Key<DB>) -> Option<&CellLock<T>> {
+                self.column.get(&key.untagged())
+            }
+
+            pub fn contains_key(&self, key: &Key<DB>) -> bool {
+                self.get(key).is_some()
+            }
+
+            pub fn len(&self) -> usize {
+                self.column.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.column.is_empty()
+            }
+
+            /// Keys of every shard, merged into ascending order
+            pub fn keys(&self) -> impl Iterator<Item = Key<DB>> + '_ {
+                self.column.keys().map(|key| key.cast())
+            }
+
+            /// Cells of every shard, merged into ascending key order
+            pub fn iter(&self) -> impl Iterator<Item = (Key<DB>, &CellLock<T>)> + '_ {
+                self.column.iter().map(|(key, cell)| (key.cast(), cell))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_db::{CellView, Column, ColumnView, ColumnViewMut, Key};
+
+    #[async_std::test]
+    async fn views_merge_shards_in_key_order() {
+        let column = Column::<usize>::with_shards(3);
+        for key in (0..10).rev() {
+            column.insert(Key::new(key), key).await.unwrap();
+        }
+
+        let view = ColumnView::new(&column).await;
+        assert_eq!(view.len(), 10);
+        assert!(view.keys().map(|key| key.index()).eq(0..10));
+        assert!(view.iter().map(|(key, _)| key.index()).eq(0..10));
+    }
+
+    #[async_std::test]
+    async fn writes_only_lock_their_shard() {
+        let column = Column::<i32>::with_shards(2);
+        column.insert(Key::new(0), 0).await.unwrap();
+
+        let _cell = CellView::new(&column, Key::new(0)).await;
+        assert!(column.shards().shard(&Key::new(1)).try_write().is_some());
+        assert!(column.shards().shard(&Key::new(2)).try_write().is_none());
+        column.insert(Key::new(1), 1).await.unwrap();
+    }
+
+    #[async_std::test]
+    async fn views_deref_to_their_cells() {
+        let column = Column::<usize>::with_shards(3);
+        for key in 0..5 {
+            column.insert(Key::new(key), key).await.unwrap();
+        }
+
+        let mut view = ColumnViewMut::new(&column).await;
+        view.insert(Key::new(4), 40).unwrap();
+        assert_eq!(view.column().len(), 5);
+        drop(view);
+
+        // Keys 1 and 4 share a shard
+        let cell = CellView::new(&column, Key::new(4)).await;
+        assert_eq!(*cell, 40);
+        assert!(cell.shard().contains_key(&Key::new(1)));
+        assert!(!cell.shard().contains_key(&Key::new(2)));
+    }
+}
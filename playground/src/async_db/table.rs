@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use super::{Key, TableHooks};
+
+/// A type that owns a set of [`Column`]s sharing the same key space.
+#[async_trait(?Send)]
+pub trait Table: Send + Sync + 'static {
+    /// Returns true if any column of the table holds a cell for `key`
+    async fn contains_key(&self, key: Key) -> bool;
+
+    /// Removes the cells for `key` from every column of the table
+    async fn remove_key(&self, key: Key);
+
+    /// Hooks for [`Database`] to run around writes to this table
+    fn hooks(&self) -> Option<&TableHooks> {
+        None
+    }
+}
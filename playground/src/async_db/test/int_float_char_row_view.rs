@@ -1,10 +1,12 @@
 use std::collections::BTreeSet;
 
 use crate::async_db::{
-    BorrowColumn, CellView, CellViewMut, ColumnView, ColumnViewMut, Key, Row,
+    ApplyPatch, BorrowColumn, CellView, CellViewMut, Column, ColumnView, DatabaseError, Join, Key,
+    PatchPolicy, Row,
 };
 
 use async_trait::async_trait;
+use futures::stream::LocalBoxStream;
 
 /// A user-created row query result holding references to table cells.
 /// Used as the output type for table queries.
@@ -15,14 +17,24 @@ pub struct IntFloatCharRow<'a> {
     pub char: CellViewMut<'a, char>,
 }
 
+row_patch! {
+    /// Partial update of an [`IntFloatCharRow`]
+    pub struct IntFloatCharPatch {
+        int: i32,
+        float: f32,
+        char: char,
+    }
+}
+
 #[async_trait(?Send)]
 impl<'a, DB> Row<'a, DB> for IntFloatCharRow<'a>
 where
     DB: BorrowColumn<i32> + BorrowColumn<f32> + BorrowColumn<char> + Send + Sync,
 {
     type Insert = (i32, f32, char);
+    type Patch = IntFloatCharPatch;
 
-    async fn new(db: &'a DB, key: Key) -> Self {
+    async fn new(db: &'a DB, key: Key<DB>) -> Self {
         let int = CellView::<i32>::new(db, key).await;
         let float = CellView::<f32>::new(db, key).await;
         let char = CellViewMut::<char>::new(db, key).await;
@@ -30,50 +42,65 @@ where
         IntFloatCharRow { int, float, char }
     }
 
-    async fn insert(db: &'a DB, key: Key, (int, float, char): (i32, f32, char)) {
-        let mut ints = ColumnViewMut::<i32>::new(db).await;
-        let mut floats = ColumnViewMut::<f32>::new(db).await;
-        let mut chars = ColumnViewMut::<char>::new(db).await;
+    async fn try_new(db: &'a DB, key: Key<DB>) -> Option<Self> {
+        let int = CellView::<i32>::try_new(db, key).await?;
+        let float = CellView::<f32>::try_new(db, key).await?;
+        let char = CellViewMut::<char>::try_new(db, key).await?;
+
+        Some(IntFloatCharRow { int, float, char })
+    }
+
+    async fn wait_for(db: &'a DB, key: Key<DB>) -> Self {
+        let ints: &Column<i32> = db.borrow();
+        let floats: &Column<f32> = db.borrow();
+        let chars: &Column<char> = db.borrow();
 
-        ints.insert(key, int.into());
-        floats.insert(key, float.into());
-        chars.insert(key, char.into());
+        // Each cell view is dropped straight away, so a producer can't be blocked by a partial row
+        loop {
+            ints.wait_for(key.untagged()).await;
+            floats.wait_for(key.untagged()).await;
+            chars.wait_for(key.untagged()).await;
+
+            if let Some(row) = Self::try_new(db, key).await {
+                return row;
+            }
+        }
+    }
+
+    async fn insert(db: &'a DB, key: Key<DB>, row: (i32, f32, char)) -> Result<(), DatabaseError> {
+        // The patch writes through the columns, so only the shard holding `key` is locked
+        IntFloatCharPatch::from(row)
+            .apply(db, key, PatchPolicy::CreateMissing)
+            .await
     }
 
-    async fn remove(db: &'a DB, key: Key) {
-        let mut ints = ColumnViewMut::<i32>::new(db).await;
-        let mut floats = ColumnViewMut::<f32>::new(db).await;
-        let mut chars = ColumnViewMut::<char>::new(db).await;
+    async fn remove(db: &'a DB, key: Key<DB>) {
+        let ints: &Column<i32> = db.borrow();
+        let floats: &Column<f32> = db.borrow();
+        let chars: &Column<char> = db.borrow();
 
-        ints.remove(&key);
-        floats.remove(&key);
-        chars.remove(&key);
+        ints.remove(&key.untagged()).await;
+        floats.remove(&key.untagged()).await;
+        chars.remove(&key.untagged()).await;
     }
 
-    async fn keys(db: &'a DB) -> BTreeSet<Key> {
-        let ints = ColumnView::<i32>::new(db).await;
-        let floats = ColumnView::<f32>::new(db).await;
-        let chars = ColumnView::<char>::new(db).await;
+    async fn keys(db: &'a DB) -> BTreeSet<Key<DB>> {
+        let ints = ColumnView::<i32, _>::new(db).await;
+        let floats = ColumnView::<f32, _>::new(db).await;
+        let chars = ColumnView::<char, _>::new(db).await;
 
         std::iter::empty()
             .chain(ints.keys())
             .chain(floats.keys())
             .chain(chars.keys())
-            .copied()
             .collect::<BTreeSet<_>>()
     }
 
-    async fn common_keys(db: &'a DB) -> BTreeSet<Key> {
-        let ints = ColumnView::<i32>::new(db).await;
-        let floats = ColumnView::<f32>::new(db).await;
-        let chars = ColumnView::<char>::new(db).await;
+    fn common_keys(db: &'a DB) -> LocalBoxStream<'a, Key<DB>> {
+        let ints: &Column<i32> = db.borrow();
+        let floats: &Column<f32> = db.borrow();
+        let chars: &Column<char> = db.borrow();
 
-        Self::keys(db)
-            .await
-            .into_iter()
-            .filter(move |key| {
-                ints.contains_key(key) && floats.contains_key(key) && chars.contains_key(key)
-            })
-            .collect::<BTreeSet<_>>()
+        Join::new().with(ints).with(floats).with(chars).keys()
     }
 }
@@ -1,7 +1,13 @@
 mod int_float_char_row_view;
+mod my_ref_table;
 mod my_table;
+mod named_ref_row;
+mod print_strings_system;
 mod print_system;
 
 pub use int_float_char_row_view::*;
+pub use my_ref_table::*;
 pub use my_table::*;
+pub use named_ref_row::*;
+pub use print_strings_system::*;
 pub use print_system::*;
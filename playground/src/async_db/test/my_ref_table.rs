@@ -0,0 +1,39 @@
+use std::borrow::Borrow;
+
+use async_trait::async_trait;
+
+use crate::async_db::{Column, ForeignKey, Key, Table};
+
+use super::MyTable;
+
+/// A user-created table holding named references into [`MyTable`]
+#[derive(Debug, Default)]
+pub struct MyRefTable {
+    names: Column<&'static str>,
+    targets: Column<ForeignKey<MyTable>>,
+}
+
+// This should eventually be derived alongside the Borrow impls
+#[async_trait(?Send)]
+impl Table for MyRefTable {
+    async fn contains_key(&self, key: Key) -> bool {
+        self.names.contains_key(&key).await || self.targets.contains_key(&key).await
+    }
+
+    async fn remove_key(&self, key: Key) {
+        self.names.remove(&key).await;
+        self.targets.remove(&key).await;
+    }
+}
+
+impl Borrow<Column<&'static str>> for MyRefTable {
+    fn borrow(&self) -> &Column<&'static str> {
+        &self.names
+    }
+}
+
+impl Borrow<Column<ForeignKey<MyTable>>> for MyRefTable {
+    fn borrow(&self) -> &Column<ForeignKey<MyTable>> {
+        &self.targets
+    }
+}
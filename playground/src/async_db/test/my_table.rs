@@ -1,6 +1,8 @@
 use std::borrow::Borrow;
 
-use crate::async_db::{Column, Row};
+use async_trait::async_trait;
+
+use crate::async_db::{Column, Key, Row, Table, TableHooks};
 
 use super::IntFloatCharRow;
 
@@ -12,29 +14,74 @@ pub struct MyTable {
     chars: Column<char>,
     strs: Column<&'static str>,
     strings: Column<String>,
+    hooks: TableHooks,
 }
 
 impl MyTable {
     pub async fn new() -> Self {
+        let ints = Column::default();
+        let chars = Column::default();
+        let strings = Column::derived((&ints, &chars), |int: &i32, char: &char| {
+            format!("{}{}", int, char)
+        });
+
         let table = MyTable {
-            ints: Default::default(),
+            ints,
             floats: Default::default(),
-            chars: Default::default(),
+            chars,
             strs: Default::default(),
-            strings: Default::default(),
+            strings,
+            hooks: Default::default(),
         };
 
         // Insert
-        IntFloatCharRow::insert(&table, 0.into(), (1, 4.0, '7')).await;
-        IntFloatCharRow::insert(&table, 1.into(), (2, 5.0, '8')).await;
-        IntFloatCharRow::insert(&table, 2.into(), (2, 5.0, '8')).await;
-        IntFloatCharRow::insert(&table, 3.into(), (3, 6.0, '9')).await;
+        IntFloatCharRow::insert(&table, Key::new(0), (1, 4.0, '7'))
+            .await
+            .unwrap();
+        IntFloatCharRow::insert(&table, Key::new(1), (2, 5.0, '8'))
+            .await
+            .unwrap();
+        IntFloatCharRow::insert(&table, Key::new(2), (2, 5.0, '8'))
+            .await
+            .unwrap();
+        IntFloatCharRow::insert(&table, Key::new(3), (3, 6.0, '9'))
+            .await
+            .unwrap();
 
         // Remove
-        IntFloatCharRow::remove(&table, 1.into()).await;
+        IntFloatCharRow::remove(&table, Key::new(1)).await;
 
         table
     }
+
+    pub fn hooks_mut(&mut self) -> &mut TableHooks {
+        &mut self.hooks
+    }
+}
+
+// Tables need to know their full set of columns to check for and remove whole rows
+// This should eventually be derived alongside the Borrow impls
+#[async_trait(?Send)]
+impl Table for MyTable {
+    async fn contains_key(&self, key: Key) -> bool {
+        self.ints.contains_key(&key).await
+            || self.floats.contains_key(&key).await
+            || self.chars.contains_key(&key).await
+            || self.strs.contains_key(&key).await
+            || self.strings.contains_key(&key).await
+    }
+
+    async fn remove_key(&self, key: Key) {
+        self.ints.remove(&key).await;
+        self.floats.remove(&key).await;
+        self.chars.remove(&key).await;
+        self.strs.remove(&key).await;
+        self.strings.remove(&key).await;
+    }
+
+    fn hooks(&self) -> Option<&TableHooks> {
+        Some(&self.hooks)
+    }
 }
 
 // Practically speaking, a table is any struct you can borrow tables from
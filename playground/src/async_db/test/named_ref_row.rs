@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+
+use crate::async_db::{
+    ApplyPatch, BorrowColumn, CellView, Column, ColumnView, DatabaseError, ForeignKey, Join, Key,
+    PatchPolicy, Row,
+};
+
+use async_trait::async_trait;
+use futures::stream::LocalBoxStream;
+
+use super::MyTable;
+
+/// A user-created row holding a name and a reference into [`MyTable`]
+#[derive(Debug)]
+pub struct NamedRefRow<'a> {
+    pub name: CellView<'a, &'static str>,
+    pub target: CellView<'a, ForeignKey<MyTable>>,
+}
+
+row_patch! {
+    /// Partial update of a [`NamedRefRow`]
+    pub struct NamedRefPatch {
+        name: &'static str,
+        target: ForeignKey<MyTable>,
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, DB> Row<'a, DB> for NamedRefRow<'a>
+where
+    DB: BorrowColumn<&'static str> + BorrowColumn<ForeignKey<MyTable>> + Send + Sync,
+{
+    type Insert = (&'static str, ForeignKey<MyTable>);
+    type Patch = NamedRefPatch;
+
+    async fn new(db: &'a DB, key: Key<DB>) -> Self {
+        let name = CellView::<&'static str>::new(db, key).await;
+        let target = CellView::<ForeignKey<MyTable>>::new(db, key).await;
+
+        NamedRefRow { name, target }
+    }
+
+    async fn try_new(db: &'a DB, key: Key<DB>) -> Option<Self> {
+        let name = CellView::<&'static str>::try_new(db, key).await?;
+        let target = CellView::<ForeignKey<MyTable>>::try_new(db, key).await?;
+
+        Some(NamedRefRow { name, target })
+    }
+
+    async fn wait_for(db: &'a DB, key: Key<DB>) -> Self {
+        let names: &Column<&'static str> = db.borrow();
+        let targets: &Column<ForeignKey<MyTable>> = db.borrow();
+
+        loop {
+            names.wait_for(key.untagged()).await;
+            targets.wait_for(key.untagged()).await;
+
+            if let Some(row) = Self::try_new(db, key).await {
+                return row;
+            }
+        }
+    }
+
+    async fn insert(db: &'a DB, key: Key<DB>, row: Self::Insert) -> Result<(), DatabaseError> {
+        NamedRefPatch::from(row)
+            .apply(db, key, PatchPolicy::CreateMissing)
+            .await
+    }
+
+    async fn remove(db: &'a DB, key: Key<DB>) {
+        let names: &Column<&'static str> = db.borrow();
+        let targets: &Column<ForeignKey<MyTable>> = db.borrow();
+
+        names.remove(&key.untagged()).await;
+        targets.remove(&key.untagged()).await;
+    }
+
+    async fn keys(db: &'a DB) -> BTreeSet<Key<DB>> {
+        let names = ColumnView::<&'static str, _>::new(db).await;
+        let targets = ColumnView::<ForeignKey<MyTable>, _>::new(db).await;
+
+        std::iter::empty()
+            .chain(names.keys())
+            .chain(targets.keys())
+            .collect::<BTreeSet<_>>()
+    }
+
+    fn common_keys(db: &'a DB) -> LocalBoxStream<'a, Key<DB>> {
+        let names: &Column<&'static str> = db.borrow();
+        let targets: &Column<ForeignKey<MyTable>> = db.borrow();
+
+        Join::new().with(names).with(targets).keys()
+    }
+}
@@ -0,0 +1,17 @@
+use crate::async_db::{BorrowColumn, CellView, ColumnView};
+
+/// Prints the derived strings column, which is kept up to date from the int and char columns
+pub async fn print_strings_system<T>(table: &T)
+where
+    T: BorrowColumn<String>,
+{
+    let keys = ColumnView::<String, _>::new(table)
+        .await
+        .keys()
+        .collect::<Vec<_>>();
+
+    for key in keys {
+        let string = CellView::<String>::new(table, key).await;
+        println!("{:?}, String: {}", key, *string);
+    }
+}
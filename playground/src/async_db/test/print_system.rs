@@ -6,9 +6,7 @@ pub async fn print_system<T>(table: &T)
 where
     T: BorrowColumn<i32> + BorrowColumn<f32> + BorrowColumn<char> + Send + Sync,
 {
-    let keys = IntFloatCharRow::common_keys(table).await;
-    
-    let stream = async_std::stream::from_iter(keys)
+    let stream = IntFloatCharRow::common_keys(table)
         .then(move |i| async move { (i, IntFloatCharRow::new(table, i).await) });
 
     futures::pin_mut!(stream);
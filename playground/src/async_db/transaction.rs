@@ -0,0 +1,763 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    future::Future,
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use futures::future::LocalBoxFuture;
+
+use super::{
+    BorrowColumn, ColumnView, ColumnViewMut, Database, DatabaseError, Key, Relation, Table,
+    TableOperation,
+};
+
+/// Buffered writes to the columns of a [`Database`], applied together on commit.
+///
+/// Handles are cheap to clone, so one can be moved into each async block that needs it.
+/// Foreign keys, table hooks and column constraints are all checked on commit.
+#[derive(Clone)]
+pub struct Transaction<'a> {
+    db: &'a Database,
+    writes: Rc<RefCell<WriteSet>>,
+}
+
+/// Pending writes keyed by table and column type
+type WriteSet = BTreeMap<(TypeId, TypeId), Box<dyn PendingColumn>>;
+
+impl Database {
+    /// Runs `f` against a new [`Transaction`], committing its writes if it returns `Ok`.
+    ///
+    /// Nothing is written if `f` returns `Err`, the commit fails, or the returned future is dropped.
+    pub async fn transaction<'a, F, Fut, R, E>(&'a self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(Transaction<'a>) -> Fut,
+        Fut: Future<Output = Result<R, E>>,
+        E: From<DatabaseError>,
+    {
+        let tx = Transaction {
+            db: self,
+            writes: Default::default(),
+        };
+
+        let result = f(tx.clone()).await?;
+        tx.commit().await?;
+        Ok(result)
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Buffers a write of `value` to the cell `key` of table `T`
+    pub fn insert<T, V>(&self, key: Key<T>, value: V) -> Result<(), DatabaseError>
+    where
+        T: Table + BorrowColumn<V>,
+        V: 'static,
+    {
+        self.write::<T, V>(key, Some(value))
+    }
+
+    /// Buffers the removal of the cell `key` of table `T`.
+    ///
+    /// Fails if a foreign key references `T`, as its `OnDelete` policy can't be applied here;
+    /// use [`Database::remove`] instead.
+    pub fn remove<T, V>(&self, key: Key<T>) -> Result<(), DatabaseError>
+    where
+        T: Table + BorrowColumn<V>,
+        V: 'static,
+    {
+        if self.db.is_referenced(TypeId::of::<T>()) {
+            return Err(DatabaseError::ReferencedRemove {
+                table: type_name::<T>(),
+                key: key.untagged(),
+            });
+        }
+        self.write::<T, V>(key, None)
+    }
+
+    /// Reads the cell `key` of table `T`, as seen by this transaction
+    pub async fn get<T, V>(&self, key: Key<T>) -> Result<Option<V>, DatabaseError>
+    where
+        T: Table + BorrowColumn<V>,
+        V: Clone + 'static,
+    {
+        let buffered = self.with_column::<T, V, _, _>(|column| column.writes.get(&key).cloned());
+
+        match buffered {
+            Some(write) => Ok(write),
+            None => {
+                let table = self.table::<T>()?;
+                let column = ColumnView::<V, T>::new(table).await;
+                Ok(match column.get(&key) {
+                    Some(cell) => Some(cell.read().await.clone()),
+                    None => None,
+                })
+            }
+        }
+    }
+
+    /// Buffers an edit to the existing cell `key` of table `T`
+    pub async fn update<T, V, F>(&self, key: Key<T>, f: F) -> Result<(), DatabaseError>
+    where
+        T: Table + BorrowColumn<V>,
+        V: Clone + 'static,
+        F: FnOnce(&mut V),
+    {
+        let mut value = self
+            .get::<T, V>(key)
+            .await?
+            .ok_or(DatabaseError::MissingRow {
+                table: type_name::<T>(),
+                key: key.untagged(),
+            })?;
+
+        f(&mut value);
+        self.insert::<T, V>(key, value)
+    }
+
+    fn table<T>(&self) -> Result<&'a T, DatabaseError>
+    where
+        T: Table,
+    {
+        self.db
+            .table::<T>()
+            .ok_or_else(|| DatabaseError::MissingTable(type_name::<T>()))
+    }
+
+    fn write<T, V>(&self, key: Key<T>, value: Option<V>) -> Result<(), DatabaseError>
+    where
+        T: Table + BorrowColumn<V>,
+        V: 'static,
+    {
+        self.table::<T>()?;
+        self.with_column::<T, V, _, _>(move |column| {
+            column.writes.insert(key, value);
+        });
+        Ok(())
+    }
+
+    fn with_column<T, V, F, O>(&self, f: F) -> O
+    where
+        T: Table + BorrowColumn<V>,
+        V: 'static,
+        F: FnOnce(&mut ColumnWrites<T, V>) -> O,
+    {
+        let mut writes = self.writes.borrow_mut();
+        let column = writes
+            .entry((TypeId::of::<T>(), TypeId::of::<V>()))
+            .or_insert_with(|| Box::new(ColumnWrites::<T, V>::default()));
+
+        f(column
+            .as_any_mut()
+            .downcast_mut::<ColumnWrites<T, V>>()
+            .unwrap())
+    }
+
+    /// Locks every written column, checks foreign keys and runs the before hooks,
+    /// then applies the writes, undoing them all if any fails.
+    ///
+    /// Foreign keys into tables the commit also writes are checked before locking,
+    /// since reading those tables afterwards would wait on the commit's own locks.
+    async fn commit(self) -> Result<(), DatabaseError> {
+        let writes = std::mem::take(&mut *self.writes.borrow_mut());
+
+        let tables: Vec<_> = writes.keys().map(|(table, _)| *table).collect();
+        let (early, late): (Vec<_>, Vec<_>) = self
+            .foreign_key_targets(&writes)
+            .into_iter()
+            .partition(|(relation, _, _)| tables.contains(&relation.referenced()));
+
+        check_targets(self.db, early).await?;
+        let mut locked = lock_all(self.db, writes.into_values().collect()).await?;
+        check_targets(self.db, late).await?;
+
+        let mut seen = HashSet::new();
+        let mut operations = vec![];
+        for (table, column) in tables.into_iter().zip(&locked) {
+            for (operation, key) in column.operations() {
+                if seen.insert((table, operation, key)) {
+                    operations.push((table, operation, key));
+                }
+            }
+        }
+        for &(table, operation, key) in &operations {
+            self.db.run_before(table, operation, key)?;
+        }
+
+        for i in 0..locked.len() {
+            if let Err(e) = locked[i].apply() {
+                for column in locked[..=i].iter_mut().rev() {
+                    column.rollback();
+                }
+                return Err(e);
+            }
+        }
+        drop(locked);
+
+        for (table, operation, key) in operations {
+            self.db.run_after(table, operation, key);
+        }
+        Ok(())
+    }
+
+    /// Returns the relation, referencing key and target of each foreign key written,
+    /// leaving out those that point at rows written too
+    fn foreign_key_targets(&self, writes: &WriteSet) -> Vec<(&'a dyn Relation, Key, Key)> {
+        let written: HashSet<(TypeId, Key)> = writes
+            .iter()
+            .flat_map(|((table, _), column)| {
+                column
+                    .inserted_keys()
+                    .into_iter()
+                    .map(move |key| (*table, key))
+            })
+            .collect();
+
+        let mut targets = vec![];
+        for ((table, _), column) in writes {
+            for relation in self.db.relations_from(*table) {
+                for (key, target) in relation.written_targets(column.writes()) {
+                    if !written.contains(&(relation.referenced(), target)) {
+                        targets.push((relation, key, target));
+                    }
+                }
+            }
+        }
+        targets
+    }
+}
+
+/// Checks that each foreign key target is a row of the referenced table
+async fn check_targets(
+    db: &Database,
+    targets: Vec<(&dyn Relation, Key, Key)>,
+) -> Result<(), DatabaseError> {
+    for (relation, key, target) in targets {
+        relation.check_target(db, key, target).await?;
+    }
+    Ok(())
+}
+
+/// Write-locks every column in `columns`.
+///
+/// Only the first column is waited for, while holding no other locks. The rest are taken only if
+/// they're free; otherwise everything is released and the contended column is waited for first,
+/// so commits can't deadlock with writers that lock columns in a different order.
+async fn lock_all<'a>(
+    db: &'a Database,
+    mut columns: Vec<Box<dyn PendingColumn>>,
+) -> Result<Vec<Box<dyn LockedColumn + 'a>>, DatabaseError> {
+    if columns.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut first = 0;
+    loop {
+        let mut pending: Vec<_> = columns.into_iter().map(Some).collect();
+        let mut locked: Vec<Option<Box<dyn LockedColumn + 'a>>> =
+            pending.iter().map(|_| None).collect();
+
+        locked[first] = Some(pending[first].take().unwrap().lock(db).await?);
+
+        let mut contended = None;
+        for i in (0..pending.len()).filter(|&i| i != first) {
+            match pending[i].take().unwrap().try_lock(db).await? {
+                Ok(column) => locked[i] = Some(column),
+                Err(column) => {
+                    pending[i] = Some(column);
+                    contended = Some(i);
+                    break;
+                }
+            }
+        }
+
+        match contended {
+            None => return Ok(locked.into_iter().map(Option::unwrap).collect()),
+            Some(i) => {
+                for (pending, locked) in pending.iter_mut().zip(locked) {
+                    if let Some(locked) = locked {
+                        *pending = Some(locked.unlock());
+                    }
+                }
+                columns = pending.into_iter().map(Option::unwrap).collect();
+                first = i;
+            }
+        }
+    }
+}
+
+/// Type-erased set of buffered writes to a single column
+trait PendingColumn {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// The buffered writes, as a `BTreeMap<Key<T>, Option<V>>`
+    fn writes(&self) -> &dyn Any;
+
+    /// Keys of the cells given a value by the buffered writes
+    fn inserted_keys(&self) -> Vec<Key>;
+
+    fn lock<'a>(
+        self: Box<Self>,
+        db: &'a Database,
+    ) -> LocalBoxFuture<'a, Result<Box<dyn LockedColumn + 'a>, DatabaseError>>;
+
+    /// Like `lock`, but hands the writes back instead of waiting if the column is in use
+    #[allow(clippy::type_complexity)]
+    fn try_lock<'a>(
+        self: Box<Self>,
+        db: &'a Database,
+    ) -> LocalBoxFuture<
+        'a,
+        Result<Result<Box<dyn LockedColumn + 'a>, Box<dyn PendingColumn>>, DatabaseError>,
+    >;
+}
+
+/// A column whose write lock is held for the duration of a commit
+trait LockedColumn {
+    /// The kind of write each buffered write makes, as seen by table hooks
+    fn operations(&self) -> Vec<(TableOperation, Key)>;
+    fn apply(&mut self) -> Result<(), DatabaseError>;
+    fn rollback(&mut self);
+    /// Releases the lock, handing back the writes if they haven't been applied
+    fn unlock(self: Box<Self>) -> Box<dyn PendingColumn>;
+}
+
+struct ColumnWrites<T, V> {
+    writes: BTreeMap<Key<T>, Option<V>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, V> Default for ColumnWrites<T, V> {
+    fn default() -> Self {
+        ColumnWrites {
+            writes: Default::default(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, V> PendingColumn for ColumnWrites<T, V>
+where
+    T: Table + BorrowColumn<V>,
+    V: 'static,
+{
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn writes(&self) -> &dyn Any {
+        &self.writes
+    }
+
+    fn inserted_keys(&self) -> Vec<Key> {
+        self.writes
+            .iter()
+            .filter(|(_, write)| write.is_some())
+            .map(|(key, _)| key.untagged())
+            .collect()
+    }
+
+    fn lock<'a>(
+        self: Box<Self>,
+        db: &'a Database,
+    ) -> LocalBoxFuture<'a, Result<Box<dyn LockedColumn + 'a>, DatabaseError>> {
+        Box::pin(async move {
+            let table = db
+                .table::<T>()
+                .ok_or_else(|| DatabaseError::MissingTable(type_name::<T>()))?;
+
+            let locked: Box<dyn LockedColumn + 'a> = Box::new(LockedWrites {
+                view: ColumnViewMut::<V, T>::new(table).await,
+                writes: self.writes,
+                undo: vec![],
+            });
+            Ok(locked)
+        })
+    }
+
+    fn try_lock<'a>(
+        self: Box<Self>,
+        db: &'a Database,
+    ) -> LocalBoxFuture<
+        'a,
+        Result<Result<Box<dyn LockedColumn + 'a>, Box<dyn PendingColumn>>, DatabaseError>,
+    > {
+        Box::pin(async move {
+            let table = db
+                .table::<T>()
+                .ok_or_else(|| DatabaseError::MissingTable(type_name::<T>()))?;
+
+            Ok(match ColumnViewMut::<V, T>::try_new(table).await {
+                Some(view) => {
+                    let locked: Box<dyn LockedColumn + 'a> = Box::new(LockedWrites {
+                        view,
+                        writes: self.writes,
+                        undo: vec![],
+                    });
+                    Ok(locked)
+                }
+                None => Err(self as Box<dyn PendingColumn>),
+            })
+        })
+    }
+}
+
+struct LockedWrites<'a, V, T> {
+    view: ColumnViewMut<'a, V, T>,
+    writes: BTreeMap<Key<T>, Option<V>>,
+    undo: Vec<(Key<T>, Option<V>)>,
+}
+
+impl<'a, V, T> LockedColumn for LockedWrites<'a, V, T>
+where
+    T: Table + BorrowColumn<V>,
+    V: 'static,
+{
+    fn operations(&self) -> Vec<(TableOperation, Key)> {
+        self.writes
+            .iter()
+            .filter_map(|(key, write)| {
+                let exists = self.view.contains_key(key);
+                let operation = match write {
+                    Some(_) if exists => TableOperation::Update,
+                    Some(_) => TableOperation::Insert,
+                    None if exists => TableOperation::Remove,
+                    None => return None,
+                };
+                Some((operation, key.untagged()))
+            })
+            .collect()
+    }
+
+    fn unlock(self: Box<Self>) -> Box<dyn PendingColumn> {
+        Box::new(ColumnWrites {
+            writes: self.writes,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn apply(&mut self) -> Result<(), DatabaseError> {
+        for (key, write) in std::mem::take(&mut self.writes) {
+            let prev = match write {
+                Some(value) => self.view.insert(key, value)?,
+                None => self.view.remove(&key),
+            };
+            self.undo.push((key, prev));
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) {
+        while let Some((key, prev)) = self.undo.pop() {
+            self.view.restore(key, prev);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_db::{
+        Check, Column, ForeignKey, IntFloatCharRow, MyRefTable, MyTable, OnDelete, Row, Unique,
+    };
+    use async_std::task;
+    use futures::future;
+    use std::{
+        borrow::Borrow,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    async fn database() -> Database {
+        let mut db = Database::new();
+        db.add_table("my_table", MyTable::new().await);
+        db
+    }
+
+    #[async_std::test]
+    async fn commit() {
+        let db = database().await;
+
+        let int = db
+            .transaction(|tx| async move {
+                tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+                tx.insert::<MyTable, f32>(Key::new(4), 7.0)?;
+                tx.insert::<MyTable, char>(Key::new(4), 'a')?;
+                tx.remove::<MyTable, i32>(Key::new(0))?;
+                tx.update::<MyTable, i32, _>(Key::new(2), |int| *int += 10)
+                    .await?;
+                tx.get::<MyTable, i32>(Key::new(2)).await
+            })
+            .await
+            .unwrap();
+        assert_eq!(int, Some(12));
+
+        let table = db.table::<MyTable>().unwrap();
+        let row = IntFloatCharRow::new(table, Key::new(4)).await;
+        assert_eq!((*row.int, *row.float, *row.char), (4, 7.0, 'a'));
+        drop(row);
+
+        assert!(!ColumnView::<i32, _>::new(table)
+            .await
+            .contains_key(&Key::new(0)));
+        assert_eq!(*IntFloatCharRow::new(table, Key::new(2)).await.int, 12);
+    }
+
+    #[async_std::test]
+    async fn rollback_on_error() {
+        let db = database().await;
+
+        let result: Result<(), DatabaseError> = db
+            .transaction(|tx| async move {
+                tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+                tx.update::<MyTable, i32, _>(Key::new(1), |int| *int += 1)
+                    .await?;
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(DatabaseError::MissingRow { .. })));
+
+        let table = db.table::<MyTable>().unwrap();
+        assert!(!ColumnView::<i32, _>::new(table)
+            .await
+            .contains_key(&Key::new(4)));
+    }
+
+    #[async_std::test]
+    async fn rollback_on_drop() {
+        let db = database().await;
+
+        {
+            let tx = db.transaction(|tx| async move {
+                tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+                futures::future::pending::<()>().await;
+                Ok::<_, DatabaseError>(())
+            });
+            futures::pin_mut!(tx);
+            assert!(futures::poll!(tx).is_pending());
+        }
+
+        let table = db.table::<MyTable>().unwrap();
+        assert!(!ColumnView::<i32, _>::new(table)
+            .await
+            .contains_key(&Key::new(4)));
+    }
+
+    #[async_std::test]
+    async fn isolation() {
+        let db = database().await;
+
+        db.transaction(|outer| async move {
+            outer.insert::<MyTable, i32>(Key::new(4), 4)?;
+
+            let inner = outer
+                .db
+                .transaction(|inner| async move { inner.get::<MyTable, i32>(Key::new(4)).await })
+                .await?;
+            assert_eq!(inner, None);
+
+            assert_eq!(outer.get::<MyTable, i32>(Key::new(4)).await?, Some(4));
+            Ok::<_, DatabaseError>(())
+        })
+        .await
+        .unwrap();
+    }
+
+    struct UniqueTable {
+        ints: Column<i32>,
+        floats: Column<f32>,
+    }
+
+    impl Borrow<Column<i32>> for UniqueTable {
+        fn borrow(&self) -> &Column<i32> {
+            &self.ints
+        }
+    }
+
+    impl Borrow<Column<f32>> for UniqueTable {
+        fn borrow(&self) -> &Column<f32> {
+            &self.floats
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl Table for UniqueTable {
+        async fn contains_key(&self, key: Key) -> bool {
+            self.ints.contains_key(&key).await || self.floats.contains_key(&key).await
+        }
+
+        async fn remove_key(&self, key: Key) {
+            self.ints.remove(&key).await;
+            self.floats.remove(&key).await;
+        }
+    }
+
+    #[async_std::test]
+    async fn commit_failure_rolls_back() {
+        let mut db = Database::new();
+        db.add_table(
+            "unique_table",
+            UniqueTable {
+                ints: Column::default().with_constraint(Unique::default()),
+                floats: Column::default()
+                    .with_constraint(Check::new("positive", |float: &f32| *float > 0.0)),
+            },
+        );
+
+        let result = db
+            .transaction(|tx| async move {
+                tx.insert::<UniqueTable, i32>(Key::new(0), 1)?;
+                tx.insert::<UniqueTable, i32>(Key::new(1), 2)?;
+                tx.insert::<UniqueTable, f32>(Key::new(0), -1.0)?;
+                Ok::<_, DatabaseError>(())
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ConstraintViolation { .. })
+        ));
+
+        let table = db.table::<UniqueTable>().unwrap();
+        assert!(ColumnView::<i32, _>::new(table).await.is_empty());
+        assert!(ColumnView::<f32, _>::new(table).await.is_empty());
+
+        // The unique index was rolled back along with the cells
+        ColumnViewMut::<i32, _>::new(table)
+            .await
+            .insert(Key::new(0), 1)
+            .unwrap();
+    }
+
+    #[async_std::test]
+    async fn commit_checks_foreign_keys() {
+        let mut db = database().await;
+        db.add_table("my_ref_table", MyRefTable::default());
+        db.add_foreign_key::<MyRefTable, MyTable>(OnDelete::Restrict);
+
+        // Row 1 of MyTable was removed in MyTable::new
+        let result = db
+            .transaction(|tx| async move {
+                tx.insert::<MyRefTable, &str>(Key::new(0), "One")?;
+                tx.insert::<MyRefTable, ForeignKey<MyTable>>(Key::new(0), Key::new(1).into())?;
+                Ok::<_, DatabaseError>(())
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ForeignKeyViolation { .. })
+        ));
+        let table = db.table::<MyRefTable>().unwrap();
+        assert!(!table.contains_key(Key::new(0)).await);
+
+        // Checking rows of a table the transaction also writes doesn't wait on its own locks
+        let result = db
+            .transaction(|tx| async move {
+                tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+                tx.insert::<MyRefTable, ForeignKey<MyTable>>(Key::new(0), Key::new(1).into())?;
+                Ok::<_, DatabaseError>(())
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ForeignKeyViolation { .. })
+        ));
+
+        // Rows written by the same transaction can be referenced
+        db.transaction(|tx| async move {
+            tx.insert::<MyTable, i32>(Key::new(1), 1)?;
+            tx.insert::<MyRefTable, ForeignKey<MyTable>>(Key::new(0), Key::new(1).into())?;
+            Ok::<_, DatabaseError>(())
+        })
+        .await
+        .unwrap();
+
+        // Removing from the referenced table would skip its OnDelete policy
+        let result = db
+            .transaction(|tx| async move { tx.remove::<MyTable, i32>(Key::new(1)) })
+            .await;
+        assert_eq!(
+            result,
+            Err(DatabaseError::ReferencedRemove {
+                table: type_name::<MyTable>(),
+                key: Key::new(1),
+            })
+        );
+    }
+
+    #[async_std::test]
+    async fn commit_runs_hooks() {
+        let inserted = Arc::new(AtomicUsize::new(0));
+
+        let mut table = MyTable::new().await;
+        table
+            .hooks_mut()
+            .before(TableOperation::Update, |key| match key.index() {
+                0 => Err("Row 0 is permanent".into()),
+                _ => Ok(()),
+            })
+            .after(TableOperation::Insert, {
+                let inserted = inserted.clone();
+                move |_| {
+                    inserted.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        let mut db = Database::new();
+        db.add_table("my_table", table);
+
+        let result = db
+            .transaction(|tx| async move {
+                tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+                tx.insert::<MyTable, i32>(Key::new(0), 10)?;
+                Ok::<_, DatabaseError>(())
+            })
+            .await;
+        assert!(matches!(result, Err(DatabaseError::HookRejected { .. })));
+        assert_eq!(inserted.load(Ordering::SeqCst), 0);
+
+        // Writing several columns of a row runs its hooks once
+        db.transaction(|tx| async move {
+            tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+            tx.insert::<MyTable, f32>(Key::new(4), 7.0)?;
+            Ok::<_, DatabaseError>(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(inserted.load(Ordering::SeqCst), 1);
+    }
+
+    #[async_std::test]
+    async fn commit_lock_order() {
+        let db = database().await;
+        let table = db.table::<MyTable>().unwrap();
+
+        // A writer holding one column while waiting for the other, in either order
+        for &ints_first in &[true, false] {
+            let writer = async {
+                if ints_first {
+                    let _ints = ColumnViewMut::<i32, _>::new(table).await;
+                    task::yield_now().await;
+                    let _chars = ColumnViewMut::<char, _>::new(table).await;
+                } else {
+                    let _chars = ColumnViewMut::<char, _>::new(table).await;
+                    task::yield_now().await;
+                    let _ints = ColumnViewMut::<i32, _>::new(table).await;
+                }
+            };
+            let commit = db.transaction(|tx| async move {
+                tx.insert::<MyTable, i32>(Key::new(4), 4)?;
+                tx.insert::<MyTable, char>(Key::new(4), 'a')?;
+                Ok::<_, DatabaseError>(())
+            });
+
+            let (_, result) =
+                async_std::future::timeout(Duration::from_secs(5), future::join(writer, commit))
+                    .await
+                    .expect("commit deadlocked");
+            result.unwrap();
+        }
+    }
+}
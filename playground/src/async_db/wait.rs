@@ -0,0 +1,150 @@
+use std::{any::type_name, time::Duration};
+
+use futures::StreamExt;
+
+use super::{CellView, Column, ColumnEvent, DatabaseError, Key};
+
+impl<T> Column<T> {
+    /// Waits until the column contains `key`, then returns a view of its cell.
+    ///
+    /// The wait is driven by insert events, so dropping the future before it resolves is safe.
+    /// A derived column is woken by inserts into its sources.
+    pub async fn wait_for(&self, key: Key) -> CellView<'_, T> {
+        loop {
+            // Subscribe before checking so an insert between the two isn't missed
+            let mut events = futures::stream::select_all(self.subscribe_all());
+
+            if let Some(view) = CellView::try_new(self, key.cast()).await {
+                return view;
+            }
+
+            while let Some(event) = events.next().await {
+                if event == ColumnEvent::Insert(key) {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Like [`Column::wait_for`], but gives up once `timeout` has elapsed
+    pub async fn wait_for_timeout(
+        &self,
+        key: Key,
+        timeout: Duration,
+    ) -> Result<CellView<'_, T>, DatabaseError> {
+        async_std::future::timeout(timeout, self.wait_for(key))
+            .await
+            .map_err(|_| DatabaseError::WaitTimeout {
+                waiting_for: type_name::<T>(),
+                key,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Borrow, time::Duration};
+
+    use async_std::task;
+    use futures::{future, FutureExt};
+
+    use crate::async_db::{Column, DatabaseError, IntFloatCharRow, Key, MyTable, Row};
+
+    #[async_std::test]
+    async fn wait_for_insert() {
+        let column = Column::<i32>::default();
+
+        let waiter = column.wait_for(Key::new(0));
+        let producer = async {
+            task::yield_now().await;
+            column.insert(Key::new(1), 1).await.unwrap();
+            column.insert(Key::new(0), 2).await.unwrap();
+        };
+
+        let (view, _) = future::join(waiter, producer).await;
+        assert_eq!(*view, 2);
+    }
+
+    #[async_std::test]
+    async fn wait_for_existing() {
+        let column = Column::<i32>::default();
+        column.insert(Key::new(0), 1).await.unwrap();
+
+        assert_eq!(*column.wait_for(Key::new(0)).await, 1);
+    }
+
+    #[async_std::test]
+    async fn wait_for_timeout() {
+        let column = Column::<i32>::default();
+
+        assert_eq!(
+            column
+                .wait_for_timeout(Key::new(0), Duration::from_millis(10))
+                .await
+                .unwrap_err(),
+            DatabaseError::WaitTimeout {
+                waiting_for: "i32",
+                key: Key::new(0)
+            }
+        );
+    }
+
+    #[async_std::test]
+    async fn cancelled_wait() {
+        let column = Column::<i32>::default();
+
+        assert!(column.wait_for(Key::new(0)).now_or_never().is_none());
+        column.insert(Key::new(0), 1).await.unwrap();
+        assert_eq!(*column.wait_for(Key::new(0)).await, 1);
+    }
+
+    #[async_std::test]
+    async fn wait_for_derived() {
+        let table = MyTable::new().await;
+
+        let strings: &Column<String> = table.borrow();
+        let waiter = strings.wait_for(Key::new(10));
+        let producer = async {
+            task::yield_now().await;
+            IntFloatCharRow::insert(&table, Key::new(10), (1, 2.0, 'a'))
+                .await
+                .unwrap();
+        };
+
+        let (view, _) = future::join(waiter, producer).await;
+        assert_eq!(*view, "1a");
+    }
+
+    #[async_std::test]
+    async fn wait_for_row() {
+        let table = MyTable::new().await;
+
+        let waiter = IntFloatCharRow::wait_for(&table, Key::new(10));
+        let producer = async {
+            task::yield_now().await;
+            Borrow::<Column<i32>>::borrow(&table)
+                .insert(Key::new(10), 1)
+                .await
+                .unwrap();
+            task::yield_now().await;
+            Borrow::<Column<f32>>::borrow(&table)
+                .insert(Key::new(10), 2.0)
+                .await
+                .unwrap();
+            task::yield_now().await;
+            Borrow::<Column<char>>::borrow(&table)
+                .insert(Key::new(10), 'a')
+                .await
+                .unwrap();
+        };
+
+        let (row, _) = future::join(waiter, producer).await;
+        assert_eq!((*row.int, *row.float, *row.char), (1, 2.0, 'a'));
+
+        assert!(
+            IntFloatCharRow::wait_for_timeout(&table, Key::new(11), Duration::from_millis(10))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -17,9 +17,9 @@ mod from;
 mod into;
 
 use super::{cons, Cons};
-use std::{fmt::Debug};
+use std::fmt::Debug;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct HNil;
 
 #[macro_export]
@@ -1,6 +1,7 @@
 mod hlist;
 pub use hlist::*;
 
+#[derive(Clone, Copy)]
 pub struct Cons<A, B>(A, B);
 
 pub fn cons<A, B>(a: A, b: B) -> Cons<A, B> {
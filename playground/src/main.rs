@@ -1,3 +1,13 @@
+#[allow(dead_code)]
+mod async_db;
+// Tuples are foreign, so conversions into them can't be written as From impls
+#[allow(dead_code, clippy::from_over_into)]
+mod cons;
+#[allow(dead_code)]
+mod mini_store;
+#[allow(dead_code)]
+mod skeleton_ecs;
+
 /// Allows using .then(f) to apply function f to self and return the result
 /// Useful for running a series of functions on a value without using intermediate variable bindings
 pub trait Map<R>: Sized {
@@ -9,6 +19,7 @@ pub trait Map<R>: Sized {
 /// Implement Then for any Sized type
 impl<T: Sized, R> Map<R> for T {}
 
+#[allow(clippy::disallowed_names)]
 fn main() {
     let foo = Some(1234);
 
@@ -0,0 +1,56 @@
+use std::{
+    cell::{Ref, RefMut},
+    marker::PhantomData,
+};
+
+use super::{SyncRef, SyncRefMut};
+
+/// How a query accesses the value of one type stored under its key
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum FieldType<T> {
+    Some,
+    None,
+    Immutable,
+    Mutable,
+    MaybeImmutable,
+    MaybeMutable,
+    /// Present and added after the given tick. Borrows nothing, like `Some`
+    Added(u64),
+    /// Present and changed after the given tick. Borrows nothing, like `Some`
+    Changed(u64),
+    /// Absent, and removed after the given tick. Borrows nothing, like `None`
+    Removed(u64),
+    #[non_exhaustive]
+    Phantom(PhantomData<T>),
+}
+
+// Copyable whatever T is, so signatures can be reused for every key of an iteration
+impl<T> Clone for FieldType<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FieldType<T> {}
+
+/// The result of querying a value with a [`FieldType`]
+#[derive(Debug)]
+pub enum Field<'a, T> {
+    Some,
+    None,
+    Immutable(Ref<'a, T>),
+    Mutable(RefMut<'a, T>),
+    MaybeImmutable(Option<Ref<'a, T>>),
+    MaybeMutable(Option<RefMut<'a, T>>),
+}
+
+/// The result of querying a value of a [`SyncMiniStore`](super::SyncMiniStore) with a [`FieldType`]
+#[derive(Debug)]
+pub enum SyncField<'a, T> {
+    Some,
+    None,
+    Immutable(SyncRef<'a, T>),
+    Mutable(SyncRefMut<'a, T>),
+    MaybeImmutable(Option<SyncRef<'a, T>>),
+    MaybeMutable(Option<SyncRefMut<'a, T>>),
+}
@@ -0,0 +1,216 @@
+use std::hash::Hash;
+
+use super::{MiniStore, MiniStoreQuery, QueryFilter};
+
+impl<K> MiniStore<K>
+where
+    K: Ord + Hash + Clone + 'static,
+{
+    /// Queries every key matching `signature`, yielding it along with its fields.
+    ///
+    /// Keys are visited in the order of the storage of the first field that must be present,
+    /// so in order if that's a `BTreeMap`, or in order of all the fields' keys if none must be.
+    /// Mutable fields borrow their whole storage, so each item must be dropped before the next.
+    pub fn iter<'a, S>(
+        &'a self,
+        signature: S,
+    ) -> impl Iterator<Item = (K, <Self as MiniStoreQuery<'a, K, S>>::GetOutput)> + 'a
+    where
+        S: QueryFilter<K> + Copy + 'a,
+        Self: MiniStoreQuery<'a, K, S>,
+    {
+        // Keys are copied out first, so no storage is borrowed between items
+        let keys = signature.required_keys(self).unwrap_or_else(|| {
+            let mut keys = vec![];
+            signature.all_keys(self, &mut keys);
+            keys.sort();
+            keys.dedup();
+            keys
+        });
+
+        keys.into_iter()
+            .filter(move |key| signature.matches(self, key))
+            .map(move |key| {
+                let fields = MiniStoreQuery::get(self, signature, &key);
+                (key, fields)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        cons::{cons, HNil},
+        hlist,
+        mini_store::{Field, FieldType, Ticks},
+    };
+
+    fn store() -> MiniStore<i32> {
+        let mut store = MiniStore::default();
+        for key in (0..6).rev() {
+            store.set(key, key);
+        }
+        for key in &[4, 1, 3] {
+            store.set(*key, key.to_string());
+        }
+        store.set(3, true);
+        store.set(5, true);
+        store
+    }
+
+    #[test]
+    fn required_fields_drive_iteration_in_key_order() {
+        let store = store();
+        let keys = store
+            .iter((FieldType::<String>::Immutable, FieldType::<i32>::Mutable))
+            .map(|(key, (string, int))| match (string, int) {
+                (Field::Immutable(string), Field::Mutable(mut int)) => {
+                    *int += 10;
+                    assert_eq!(*string, key.to_string());
+                    key
+                }
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(keys, vec![1, 3, 4]);
+        assert_eq!(store.get::<i32>(&4).as_deref(), Some(&14));
+        assert_eq!(store.get::<i32>(&0).as_deref(), Some(&0));
+    }
+
+    #[test]
+    fn with_and_without_filters() {
+        let store = store();
+        let keys = store
+            .iter((
+                FieldType::<i32>::Immutable,
+                FieldType::<String>::Some,
+                FieldType::<bool>::None,
+            ))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![1, 4]);
+    }
+
+    #[test]
+    fn optional_fields_visit_every_key_of_their_types() {
+        let store = store();
+        let fields = store
+            .iter(hlist![
+                FieldType::<String>::MaybeImmutable,
+                FieldType::<bool>::MaybeImmutable
+            ])
+            .map(|(key, fields)| match fields.into() {
+                (Field::MaybeImmutable(string), Field::MaybeImmutable(bool)) => {
+                    (key, string.is_some(), bool.is_some())
+                }
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fields,
+            vec![
+                (1, true, false),
+                (3, true, true),
+                (4, true, false),
+                (5, false, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn unordered_storage_visits_every_key() {
+        let mut store = store();
+        store.register::<i32, _>(HashMap::new());
+
+        let mut keys = store
+            .iter((FieldType::<i32>::Immutable,))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!(keys, (0..6).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn changes_are_filtered_by_tick() {
+        let mut store = store();
+        let since = store.tick();
+        store.advance_tick();
+
+        store.set(6, 6);
+        store.set(2, 20);
+        *store.get_mut::<i32>(&4).unwrap() += 1;
+        let _ = store.get::<i32>(&5);
+
+        let changed = |store: &MiniStore<i32>, since| {
+            store
+                .iter((FieldType::<i32>::Changed(since),))
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(changed(&store, since), vec![2, 4, 6]);
+        let added = store
+            .iter((FieldType::<i32>::Added(since), FieldType::<i32>::Immutable))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(added, vec![6]);
+
+        let since = store.tick();
+        store.advance_tick();
+        assert!(changed(&store, since).is_empty());
+        assert_eq!(store.iter((FieldType::<i32>::Added(since),)).count(), 0);
+
+        // Mutable queries mark what they borrow as changed
+        for _ in store.iter((FieldType::<i32>::Mutable, FieldType::<bool>::Some)) {}
+        assert_eq!(changed(&store, since), vec![3, 5]);
+        assert_eq!(
+            store.ticks::<i32>(&6),
+            Some(Ticks {
+                added: 1,
+                changed: 1
+            })
+        );
+    }
+
+    #[test]
+    fn removals_are_filtered_by_tick() {
+        let mut store = store();
+        store.remove::<i32>(&0);
+        let since = store.tick();
+        store.advance_tick();
+
+        store.remove::<i32>(&1);
+        store.remove_all(&3);
+        store.remove::<i32>(&2);
+        store.set(2, 2);
+
+        let removed = store
+            .iter((FieldType::<i32>::Removed(since),))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(removed, vec![1, 3]);
+        assert_eq!(store.removed_keys::<i32>(), vec![0, 1, 3]);
+        assert_eq!(store.removed_tick::<bool>(&3), Some(1));
+
+        store.clear::<String>();
+        let cleared = store
+            .iter((FieldType::<String>::Removed(since), FieldType::<i32>::Some))
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(cleared, vec![4]);
+
+        store.forget_removed(since);
+        assert_eq!(store.removed_keys::<i32>(), vec![1, 3]);
+    }
+
+    #[test]
+    fn unused_types_match_nothing() {
+        let store = store();
+        assert_eq!(store.iter((FieldType::<f32>::Immutable,)).count(), 0);
+        assert_eq!(store.iter((FieldType::<f32>::None,)).count(), 0);
+    }
+}
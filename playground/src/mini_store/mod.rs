@@ -0,0 +1,144 @@
+mod field;
+mod iter;
+mod query;
+mod query_error;
+mod query_param;
+mod registry;
+mod serialization_error;
+mod storage;
+mod store;
+mod sync_ref;
+mod sync_store;
+mod tracked;
+mod try_query;
+mod vec_map;
+mod would_block;
+
+pub use field::*;
+pub use query::*;
+pub use query_error::*;
+pub use query_param::*;
+pub use registry::*;
+pub use serialization_error::*;
+pub use storage::*;
+pub use store::*;
+pub use sync_ref::*;
+pub use sync_store::*;
+pub use tracked::*;
+pub use vec_map::*;
+pub use would_block::*;
+
+use fnv::FnvHashMap;
+
+use crate::{
+    cons::{cons, HNil},
+    hlist, query,
+};
+
+pub fn main() {
+    let mut mini_store = MiniStore::<i32>::default();
+    mini_store.register::<bool, _>(FnvHashMap::default());
+    mini_store.register::<&str, _>(VecMap::new());
+
+    mini_store.set(0, true);
+    mini_store.set(1, false);
+    mini_store.set(2, false);
+    mini_store.set(3, false);
+
+    mini_store.set(0, "Foo");
+    mini_store.set(1, "Bar");
+    mini_store.set(2, "Baz");
+
+    mini_store.set(0, 1234);
+    mini_store.set(1, 5678);
+
+    mini_store.set(0, "Hmm".to_string());
+
+    mini_store.get::<bool>(&0);
+    mini_store.get::<bool>(&1);
+    mini_store.get::<bool>(&2);
+    mini_store.get::<bool>(&3);
+
+    if let (Field::MaybeImmutable(maybe_string),) =
+        MiniStoreQuery::get(&mini_store, (FieldType::<String>::MaybeImmutable,), &4)
+    {
+        println!("Arity 1 result: {:?}", maybe_string);
+    };
+
+    if let (Field::Immutable(string), Field::Mutable(int)) = MiniStoreQuery::get(
+        &mini_store,
+        (FieldType::<String>::Immutable, FieldType::<i32>::Mutable),
+        &0,
+    ) {
+        println!("Arity 2 result: {:?}, {:?}", string, int);
+    };
+
+    if let Ok((string, int, flag)) = query!(mini_store, 0 => &String, &mut i32, Option<&bool>) {
+        println!("Macro result: {:?}, {:?}, {:?}", string, int, flag);
+    }
+
+    let fields = MiniStoreQuery::get(
+        &mini_store,
+        hlist![
+            FieldType::<bool>::Immutable,
+            FieldType::<&str>::Immutable,
+            FieldType::<i32>::MaybeImmutable
+        ],
+        &2,
+    );
+    println!("HList result: {:?}", fields);
+    drop(fields);
+
+    if let Err(error) =
+        mini_store.try_query((FieldType::<i32>::Mutable, FieldType::<i32>::Immutable), &0)
+    {
+        println!("Aliasing query refused: {}", error);
+    }
+
+    for (key, fields) in mini_store.iter((
+        FieldType::<&str>::Immutable,
+        FieldType::<i32>::MaybeImmutable,
+        FieldType::<String>::None,
+    )) {
+        println!("Iterated {}: {:?}", key, fields);
+    }
+
+    let since = mini_store.tick();
+    mini_store.advance_tick();
+    *mini_store.get_mut::<i32>(&1).unwrap() += 1;
+    mini_store.set(2, 9012);
+    for (key, _) in mini_store.iter((FieldType::<i32>::Changed(since),)) {
+        println!("Changed since tick {}: {}", since, key);
+    }
+    mini_store.remove::<i32>(&0);
+    for (key, _) in mini_store.iter((FieldType::<i32>::Removed(since),)) {
+        println!("Removed since tick {}: {}", since, key);
+    }
+
+    // Borrowed strs can't be loaded back, so they're left unregistered and skipped
+    let mut registry = TypeRegistry::new();
+    registry
+        .register::<bool>("bool")
+        .register::<i32>("i32")
+        .register::<String>("string");
+    let saved = registry.save_json(&mini_store, Unregistered::Skip).unwrap();
+    println!("Saved: {}, skipping {:?}", saved.data, saved.skipped);
+    let mut loaded = MiniStore::default();
+    registry
+        .load_json(&mut loaded, &saved.data, Unregistered::Fail)
+        .unwrap();
+    println!("Loaded string: {:?}", loaded.get::<String>(&0));
+
+    let mut sync_store = SyncMiniStore::<i32>::default();
+    sync_store.register::<u32, _>(FnvHashMap::default());
+    sync_store.set(0, 0u32);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| *sync_store.get_mut::<u32>(&0).unwrap() += 1);
+        }
+    });
+    println!(
+        "Incremented from 4 threads: {:?}",
+        sync_store.get::<u32>(&0)
+    );
+}
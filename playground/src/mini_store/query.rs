@@ -0,0 +1,393 @@
+use std::{any::TypeId, hash::Hash};
+
+use super::{Field, FieldType, MiniStore, QueryError};
+use crate::cons::{cons, Cons, HNil};
+
+/// A store that can look up the value of `T` under a key with a [`FieldType`].
+///
+/// Implementing this for each stored type makes the store queryable through [`MiniStoreQuery`].
+pub trait QueryField<'a, K, T> {
+    /// The borrow of one value, such as a [`Field`]
+    type Field;
+
+    fn query_field(&'a self, field_type: FieldType<T>, key: &K) -> Self::Field;
+}
+
+impl<'a, K, T> QueryField<'a, K, T> for MiniStore<K>
+where
+    K: Ord + Hash + 'static,
+    T: 'static,
+{
+    type Field = Field<'a, T>;
+
+    fn query_field(&'a self, field_type: FieldType<T>, key: &K) -> Self::Field {
+        MiniStore::query_field(self, field_type, key)
+    }
+}
+
+/// Looks up the fields of one key described by a signature of [`FieldType`]s.
+///
+/// Signatures are either `HList![FieldType<A>, ...]`s of any length, returning the matching
+/// HList of fields, or tuples of up to 16 `FieldType`s, returning a tuple.
+pub trait MiniStoreQuery<'a, Key, Signature> {
+    type GetOutput;
+
+    fn get(&'a self, signature: Signature, key: &Key) -> Self::GetOutput;
+}
+
+impl<'a, S, K> MiniStoreQuery<'a, K, HNil> for S {
+    type GetOutput = HNil;
+
+    fn get(&'a self, _: HNil, _: &K) -> Self::GetOutput {
+        HNil
+    }
+}
+
+impl<'a, S, K, T, Rest> MiniStoreQuery<'a, K, Cons<FieldType<T>, Rest>> for S
+where
+    S: QueryField<'a, K, T> + MiniStoreQuery<'a, K, Rest>,
+{
+    type GetOutput =
+        Cons<<S as QueryField<'a, K, T>>::Field, <S as MiniStoreQuery<'a, K, Rest>>::GetOutput>;
+
+    fn get(&'a self, sig: Cons<FieldType<T>, Rest>, key: &K) -> Self::GetOutput {
+        let (field_type, rest) = sig.into();
+        let field = QueryField::query_field(self, field_type, key);
+        cons(field, MiniStoreQuery::get(self, rest, key))
+    }
+}
+
+macro_rules! impl_tuple_query {
+    ($($t:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<'a, S, K, $($t),+> MiniStoreQuery<'a, K, ($(FieldType<$t>,)+)> for S
+        where
+            $(S: QueryField<'a, K, $t>,)+
+        {
+            type GetOutput = ($(<S as QueryField<'a, K, $t>>::Field,)+);
+
+            fn get(&'a self, ($($t,)+): ($(FieldType<$t>,)+), key: &K) -> Self::GetOutput {
+                ($(QueryField::query_field(self, $t, key),)+)
+            }
+        }
+    };
+}
+
+/// Implements [`MiniStoreQuery`] for tuples of every length up to the number of idents given
+macro_rules! impl_tuple_queries {
+    ($a:ident $(, $bs:ident)*) => {
+        impl_tuple_query!($a $(, $bs)*);
+        impl_tuple_queries!($($bs),*);
+    };
+    () => {};
+}
+
+impl_tuple_queries!(A, B, C, D, E, F, G, H, I, J, K0, L, M, N, O, P);
+
+/// The keys a query signature matches when iterating, and whether its fields can be borrowed.
+///
+/// `Immutable`, `Mutable` and `Some` fields must be present, `None` fields absent,
+/// and `MaybeImmutable` and `MaybeMutable` fields may be either.
+/// `Added` and `Changed` fields must be present and added or changed after their tick,
+/// and `Removed` fields absent and removed after theirs.
+pub trait QueryFilter<K> {
+    /// Keys of the first field that must be present, or `None` if no field must be
+    fn required_keys(&self, store: &MiniStore<K>) -> Option<Vec<K>>;
+
+    /// Appends the keys of every field to `keys`
+    fn all_keys(&self, store: &MiniStore<K>, keys: &mut Vec<K>);
+
+    fn matches(&self, store: &MiniStore<K>, key: &K) -> bool;
+
+    /// Checks that every field under `key` can be borrowed, adding the types borrowed and
+    /// whether mutably to `borrows` so later fields can't alias them
+    fn validate(
+        &self,
+        store: &MiniStore<K>,
+        key: &K,
+        borrows: &mut Vec<(TypeId, bool)>,
+    ) -> Result<(), QueryError>;
+}
+
+impl<K, T> QueryFilter<K> for FieldType<T>
+where
+    K: Ord + Hash + Clone + 'static,
+    T: 'static,
+{
+    fn required_keys(&self, store: &MiniStore<K>) -> Option<Vec<K>> {
+        match self {
+            FieldType::Immutable
+            | FieldType::Mutable
+            | FieldType::Some
+            | FieldType::Added(_)
+            | FieldType::Changed(_) => Some(store.keys::<T>()),
+            FieldType::Removed(_) => Some(store.removed_keys::<T>()),
+            _ => None,
+        }
+    }
+
+    fn all_keys(&self, store: &MiniStore<K>, keys: &mut Vec<K>) {
+        match self {
+            FieldType::Removed(_) => keys.extend(store.removed_keys::<T>()),
+            _ => keys.extend(store.keys::<T>()),
+        }
+    }
+
+    fn matches(&self, store: &MiniStore<K>, key: &K) -> bool {
+        match self {
+            FieldType::Immutable | FieldType::Mutable | FieldType::Some => store.contains::<T>(key),
+            FieldType::None => !store.contains::<T>(key),
+            FieldType::MaybeImmutable | FieldType::MaybeMutable => true,
+            FieldType::Added(since) => store
+                .ticks::<T>(key)
+                .is_some_and(|ticks| ticks.added > *since),
+            FieldType::Changed(since) => store
+                .ticks::<T>(key)
+                .is_some_and(|ticks| ticks.changed > *since),
+            FieldType::Removed(since) => {
+                !store.contains::<T>(key)
+                    && store
+                        .removed_tick::<T>(key)
+                        .is_some_and(|removed| removed > *since)
+            }
+            FieldType::Phantom(_) => panic!(),
+        }
+    }
+
+    fn validate(
+        &self,
+        store: &MiniStore<K>,
+        key: &K,
+        borrows: &mut Vec<(TypeId, bool)>,
+    ) -> Result<(), QueryError> {
+        let (mutable, required) = match self {
+            FieldType::Some => return store.check_presence::<T>(key, true),
+            FieldType::None => return store.check_presence::<T>(key, false),
+            FieldType::Added(since) => {
+                return store.check_ticks::<T>(key, |ticks| ticks.added > *since)
+            }
+            FieldType::Changed(since) => {
+                return store.check_ticks::<T>(key, |ticks| ticks.changed > *since)
+            }
+            FieldType::Removed(since) => return store.check_removed::<T>(key, *since),
+            FieldType::Immutable => (false, true),
+            FieldType::Mutable => (true, true),
+            FieldType::MaybeImmutable => (false, false),
+            FieldType::MaybeMutable => (true, false),
+            _ => return Ok(()),
+        };
+
+        let type_id = TypeId::of::<T>();
+        if borrows.iter().any(|&(borrowed, borrowed_mutably)| {
+            borrowed == type_id && (mutable || borrowed_mutably)
+        }) {
+            return Err(QueryError::Aliased(std::any::type_name::<T>()));
+        }
+        borrows.push((type_id, mutable));
+        store.check_field::<T>(key, mutable, required)
+    }
+}
+
+impl<K> QueryFilter<K> for HNil {
+    fn required_keys(&self, _: &MiniStore<K>) -> Option<Vec<K>> {
+        None
+    }
+
+    fn all_keys(&self, _: &MiniStore<K>, _: &mut Vec<K>) {}
+
+    fn matches(&self, _: &MiniStore<K>, _: &K) -> bool {
+        true
+    }
+
+    fn validate(
+        &self,
+        _: &MiniStore<K>,
+        _: &K,
+        _: &mut Vec<(TypeId, bool)>,
+    ) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl<K, A, B> QueryFilter<K> for Cons<A, B>
+where
+    A: QueryFilter<K>,
+    B: QueryFilter<K>,
+{
+    fn required_keys(&self, store: &MiniStore<K>) -> Option<Vec<K>> {
+        self.car()
+            .required_keys(store)
+            .or_else(|| self.cdr().required_keys(store))
+    }
+
+    fn all_keys(&self, store: &MiniStore<K>, keys: &mut Vec<K>) {
+        self.car().all_keys(store, keys);
+        self.cdr().all_keys(store, keys);
+    }
+
+    fn matches(&self, store: &MiniStore<K>, key: &K) -> bool {
+        self.car().matches(store, key) && self.cdr().matches(store, key)
+    }
+
+    fn validate(
+        &self,
+        store: &MiniStore<K>,
+        key: &K,
+        borrows: &mut Vec<(TypeId, bool)>,
+    ) -> Result<(), QueryError> {
+        self.car().validate(store, key, borrows)?;
+        self.cdr().validate(store, key, borrows)
+    }
+}
+
+macro_rules! impl_tuple_filter {
+    ($($t:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<K, $($t),+> QueryFilter<K> for ($($t,)+)
+        where
+            $($t: QueryFilter<K>,)+
+        {
+            fn required_keys(&self, store: &MiniStore<K>) -> Option<Vec<K>> {
+                let ($($t,)+) = self;
+                None$(.or_else(|| $t.required_keys(store)))+
+            }
+
+            fn all_keys(&self, store: &MiniStore<K>, keys: &mut Vec<K>) {
+                let ($($t,)+) = self;
+                $($t.all_keys(store, keys);)+
+            }
+
+            fn matches(&self, store: &MiniStore<K>, key: &K) -> bool {
+                let ($($t,)+) = self;
+                true $(&& $t.matches(store, key))+
+            }
+
+            fn validate(
+                &self,
+                store: &MiniStore<K>,
+                key: &K,
+                borrows: &mut Vec<(TypeId, bool)>,
+            ) -> Result<(), QueryError> {
+                let ($($t,)+) = self;
+                $($t.validate(store, key, borrows)?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+/// Implements [`QueryFilter`] for tuples of every length up to the number of idents given
+macro_rules! impl_tuple_filters {
+    ($a:ident $(, $bs:ident)*) => {
+        impl_tuple_filter!($a $(, $bs)*);
+        impl_tuple_filters!($($bs),*);
+    };
+    () => {};
+}
+
+impl_tuple_filters!(A, B, C, D, E, F, G, H, I, J, K0, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hlist, HList};
+
+    fn store() -> MiniStore<i32> {
+        let mut store = MiniStore::default();
+        store.set(0, 1u8);
+        store.set(0, 2u16);
+        store.set(0, 3u32);
+        store.set(0, 4u64);
+        store.set(0, 5i8);
+        store.set(0, 6i16);
+        store.set(0, 7i32);
+        store.set(0, 8i64);
+        store.set(0, 9f32);
+        store.set(0, 10f64);
+        store.set(0, 'k');
+        store.set(0, "l");
+        store.set(0, "m".to_string());
+        store.set(0, true);
+        store.set(0, ());
+        store.set(0, 16usize);
+        store.set(0, 17isize);
+        store
+    }
+
+    #[test]
+    fn hlist_queries_have_any_width() {
+        let store = store();
+        let signature: HList![
+            FieldType<u8>,
+            FieldType<u16>,
+            FieldType<u32>,
+            FieldType<u64>,
+            FieldType<i8>,
+            FieldType<i16>,
+            FieldType<i32>,
+            FieldType<i64>,
+            FieldType<f32>,
+            FieldType<f64>,
+            FieldType<char>,
+            FieldType<&str>,
+            FieldType<String>,
+            FieldType<bool>,
+            FieldType<()>,
+            FieldType<usize>,
+            FieldType<isize>
+        ] = hlist![
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Immutable,
+            FieldType::Mutable
+        ];
+
+        let fields = MiniStoreQuery::get(&store, signature, &0);
+        assert_eq!(fields.len(), 17);
+        assert!(matches!(fields.car(), Field::Immutable(a) if **a == 1));
+        assert!(format!("{:?}", fields).ends_with("Mutable(17)]"));
+    }
+
+    #[test]
+    fn tuple_queries_have_up_to_sixteen_fields() {
+        let store = store();
+        let (a, .., p) = MiniStoreQuery::get(
+            &store,
+            (
+                FieldType::<u8>::Immutable,
+                FieldType::<u16>::Immutable,
+                FieldType::<u32>::Immutable,
+                FieldType::<u64>::Immutable,
+                FieldType::<i8>::Immutable,
+                FieldType::<i16>::Immutable,
+                FieldType::<i32>::Immutable,
+                FieldType::<i64>::Immutable,
+                FieldType::<f32>::Immutable,
+                FieldType::<f64>::Immutable,
+                FieldType::<char>::Immutable,
+                FieldType::<&str>::Immutable,
+                FieldType::<String>::Immutable,
+                FieldType::<bool>::Immutable,
+                FieldType::<()>::Immutable,
+                FieldType::<usize>::Mutable,
+            ),
+            &0,
+        );
+
+        assert!(matches!(a, Field::Immutable(a) if *a == 1));
+        assert!(matches!(p, Field::Mutable(p) if *p == 16));
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt::{self, Display};
+
+/// Why a [`MiniStore::try_query`](super::MiniStore::try_query) couldn't borrow its fields.
+/// Each variant names the type of the offending field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryError {
+    /// The field would be borrowed mutably alongside another borrow of the same type,
+    /// either in the signature or still held from outside the query
+    Aliased(&'static str),
+    /// A required field's type has never been stored
+    MissingStorage(&'static str),
+    /// A required field has no value under the key
+    MissingKey(&'static str),
+    /// A `None` or `Removed` field has a value under the key
+    Excluded(&'static str),
+    /// An `Added`, `Changed` or `Removed` field wasn't touched that way since its tick
+    Unchanged(&'static str),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Aliased(type_name) => write!(f, "{} is borrowed more than once", type_name),
+            QueryError::MissingStorage(type_name) => write!(f, "No storage for {}", type_name),
+            QueryError::MissingKey(type_name) => write!(f, "No field of type {}", type_name),
+            QueryError::Excluded(type_name) => write!(f, "Unexpected field of type {}", type_name),
+            QueryError::Unchanged(type_name) => {
+                write!(f, "Field of type {} is older than the query", type_name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
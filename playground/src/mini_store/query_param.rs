@@ -0,0 +1,162 @@
+use std::{
+    cell::{Ref, RefMut},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use super::{Field, FieldType, MiniStore, QueryError};
+
+/// Requires a value of `T` under the key, without borrowing it
+pub struct With<T>(PhantomData<T>);
+
+/// Requires that there's no value of `T` under the key
+pub struct Without<T>(PhantomData<T>);
+
+/// A type written in a [`query!`](crate::query) to ask for one field, and what it returns:
+///
+/// - `&T` borrows a value of `T`, returning `Ref<T>`
+/// - `&mut T` borrows it mutably, returning `RefMut<T>`
+/// - `Option<&T>` and `Option<&mut T>` borrow it if there is one
+/// - `With<T>` and `Without<T>` filter on its presence, returning `()`
+pub trait QueryParam<'a> {
+    type Value: 'static;
+    type Output;
+
+    const FIELD_TYPE: FieldType<Self::Value>;
+
+    /// Unwraps the field queried with [`QueryParam::FIELD_TYPE`]
+    fn from_field(field: Field<'a, Self::Value>) -> Self::Output;
+}
+
+macro_rules! impl_query_param {
+    ($param:ty, $output:ty, $field_type:ident, $field:pat => $value:expr) => {
+        impl<'a, T> QueryParam<'a> for $param
+        where
+            T: 'static,
+        {
+            type Value = T;
+            type Output = $output;
+
+            const FIELD_TYPE: FieldType<T> = FieldType::$field_type;
+
+            fn from_field(field: Field<'a, T>) -> Self::Output {
+                match field {
+                    $field => $value,
+                    _ => unreachable!("Field doesn't match its FieldType"),
+                }
+            }
+        }
+    };
+}
+
+impl_query_param!(&T, Ref<'a, T>, Immutable, Field::Immutable(value) => value);
+impl_query_param!(&mut T, RefMut<'a, T>, Mutable, Field::Mutable(value) => value);
+impl_query_param!(Option<&T>, Option<Ref<'a, T>>, MaybeImmutable, Field::MaybeImmutable(value) => value);
+impl_query_param!(Option<&mut T>, Option<RefMut<'a, T>>, MaybeMutable, Field::MaybeMutable(value) => value);
+impl_query_param!(With<T>, (), Some, Field::Some => ());
+impl_query_param!(Without<T>, (), None, Field::None => ());
+
+/// A tuple of [`QueryParam`]s, queried together by [`query!`](crate::query)
+pub trait QueryParams<'a, K> {
+    type Output;
+
+    fn query(store: &'a MiniStore<K>, key: &K) -> Result<Self::Output, QueryError>;
+}
+
+macro_rules! impl_query_params {
+    ($($t:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<'a, K, $($t),+> QueryParams<'a, K> for ($($t,)+)
+        where
+            K: Ord + Hash + Clone + 'static,
+            $($t: QueryParam<'a>,)+
+        {
+            type Output = ($($t::Output,)+);
+
+            fn query(store: &'a MiniStore<K>, key: &K) -> Result<Self::Output, QueryError> {
+                let ($($t,)+) = store.try_query(($($t::FIELD_TYPE,)+), key)?;
+                Ok(($($t::from_field($t),)+))
+            }
+        }
+    };
+}
+
+/// Implements [`QueryParams`] for tuples of every length up to the number of idents given
+macro_rules! impl_query_params_tuples {
+    ($a:ident $(, $bs:ident)*) => {
+        impl_query_params!($a $(, $bs)*);
+        impl_query_params_tuples!($($bs),*);
+    };
+    () => {};
+}
+
+impl_query_params_tuples!(A, B, C, D, E, F, G, H, I, J, K0, L, M, N, O, P);
+
+/// Queries the fields of one key of a [`MiniStore`], returning them directly typed.
+///
+/// `query!(store, key => &String, &mut i32, Option<&bool>, With<Tag>)` returns a
+/// `Result<(Ref<String>, RefMut<i32>, Option<Ref<bool>>, ()), QueryError>`,
+/// checked like [`MiniStore::try_query`]. See [`QueryParam`] for what each type asks for.
+#[macro_export]
+macro_rules! query {
+    ($store:expr, $key:expr => $($param:ty),+ $(,)?) => {
+        <($($param,)+) as $crate::mini_store::QueryParams<_>>::query(&$store, &$key)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::type_name;
+
+    use super::*;
+
+    struct Tag;
+
+    fn store() -> MiniStore<i32> {
+        let mut store = MiniStore::default();
+        for key in 0..3 {
+            store.set(key, key);
+            store.set(key, key.to_string());
+        }
+        store.set(1, true);
+        store.set(0, Tag);
+        store.set(1, Tag);
+        store
+    }
+
+    #[test]
+    fn fields_are_returned_typed() {
+        let store = store();
+        let (string, mut int, flag, ()) =
+            query!(store, 1 => &String, &mut i32, Option<&bool>, With<Tag>).unwrap();
+        *int += string.len() as i32;
+        assert_eq!(flag.as_deref(), Some(&true));
+        drop((string, int, flag));
+        assert_eq!(store.get::<i32>(&1).as_deref(), Some(&2));
+
+        let (flag, (), float) =
+            query!(store, 2 => Option<&mut bool>, Without<Tag>, Option<&f32>,).unwrap();
+        assert!(flag.is_none() && float.is_none());
+    }
+
+    #[test]
+    fn mismatches_are_errors() {
+        let store = store();
+        assert_eq!(
+            query!(store, 2 => &i32, With<Tag>).unwrap_err(),
+            QueryError::MissingKey(type_name::<Tag>())
+        );
+        assert_eq!(
+            query!(store, 0 => Without<Tag>).unwrap_err(),
+            QueryError::Excluded(type_name::<Tag>())
+        );
+        assert_eq!(
+            query!(store, 0 => &mut i32, &i32).unwrap_err(),
+            QueryError::Aliased(type_name::<i32>())
+        );
+        assert_eq!(
+            query!(store, 0 => &bool).unwrap_err(),
+            QueryError::MissingKey(type_name::<bool>())
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+    hash::Hash,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use super::{MiniStore, SerializationError};
+
+/// What to do with values whose type isn't in a [`TypeRegistry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unregistered {
+    /// Leave them out, returning the names of their types
+    Skip,
+    /// Fail with [`SerializationError::Unregistered`]
+    Fail,
+}
+
+impl Unregistered {
+    fn handle(self, name: &str, skipped: &mut Vec<String>) -> Result<(), SerializationError> {
+        match self {
+            Unregistered::Skip => {
+                skipped.push(name.to_string());
+                Ok(())
+            }
+            Unregistered::Fail => Err(SerializationError::Unregistered(name.to_string())),
+        }
+    }
+}
+
+/// A store saved by a [`TypeRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saved<T> {
+    pub data: T,
+    /// Names of the unregistered types left out by [`Unregistered::Skip`], in order
+    pub skipped: Vec<String>,
+}
+
+/// Functions to save and load the values of one type, monomorphised at registration
+struct Registration<K> {
+    name: String,
+    to_json: fn(&MiniStore<K>) -> Result<Value, SerializationError>,
+    from_json: fn(&mut MiniStore<K>, Value) -> Result<(), SerializationError>,
+    to_binary: fn(&MiniStore<K>) -> Result<Vec<u8>, SerializationError>,
+    from_binary: fn(&mut MiniStore<K>, &[u8]) -> Result<(), SerializationError>,
+}
+
+/// Maps each registered type to a stable name and the functions to serialize its values,
+/// so a whole [`MiniStore`] can be saved and loaded back.
+///
+/// Saved stores hold, for each type, its name and a list of its `(key, value)` entries.
+pub struct TypeRegistry<K> {
+    by_type: HashMap<TypeId, Registration<K>>,
+    by_name: HashMap<String, TypeId>,
+}
+
+impl<K> Default for TypeRegistry<K> {
+    fn default() -> Self {
+        TypeRegistry {
+            by_type: HashMap::new(),
+            by_name: HashMap::new(),
+        }
+    }
+}
+
+impl<K> TypeRegistry<K>
+where
+    K: Ord + Hash + Serialize + DeserializeOwned + 'static,
+{
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Saves and loads values of `T` under `name`, which must identify `T` across versions.
+    /// Panics if `name` is taken by another type.
+    pub fn register<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        let name = name.into();
+        let type_id = TypeId::of::<T>();
+        if let Some(registered) = self.by_name.get(&name) {
+            assert!(
+                *registered == type_id,
+                "{} is registered to another type",
+                name
+            );
+        }
+        if let Some(previous) = self.by_type.get(&type_id) {
+            self.by_name.remove(&previous.name);
+        }
+
+        self.by_name.insert(name.clone(), type_id);
+        self.by_type.insert(
+            type_id,
+            Registration {
+                name,
+                to_json: to_json::<K, T>,
+                from_json: from_json::<K, T>,
+                to_binary: to_binary::<K, T>,
+                from_binary: from_binary::<K, T>,
+            },
+        );
+        self
+    }
+
+    /// The registrations of every type in `store` in order of name,
+    /// and the names of the unregistered types skipped
+    fn registrations(
+        &self,
+        store: &MiniStore<K>,
+        unregistered: Unregistered,
+    ) -> Result<(Vec<&Registration<K>>, Vec<String>), SerializationError> {
+        let mut registrations = vec![];
+        let mut skipped = vec![];
+        for (type_id, type_name) in store.types() {
+            match self.by_type.get(&type_id) {
+                Some(registration) => registrations.push(registration),
+                None => {
+                    let type_name = type_name.map_err(|_| SerializationError::Borrowed(None))?;
+                    unregistered.handle(type_name, &mut skipped)?
+                }
+            }
+        }
+        registrations.sort_by(|a, b| a.name.cmp(&b.name));
+        skipped.sort();
+        Ok((registrations, skipped))
+    }
+
+    fn registration(&self, name: &str) -> Option<&Registration<K>> {
+        self.by_name.get(name).map(|type_id| &self.by_type[type_id])
+    }
+
+    /// Writes `store` as a JSON object mapping type names to their entries.
+    /// Fails with [`SerializationError::Borrowed`] if any values are borrowed mutably.
+    pub fn save_json(
+        &self,
+        store: &MiniStore<K>,
+        unregistered: Unregistered,
+    ) -> Result<Saved<String>, SerializationError> {
+        let (registrations, skipped) = self.registrations(store, unregistered)?;
+        let mut types = Map::new();
+        for registration in registrations {
+            types.insert(registration.name.clone(), (registration.to_json)(store)?);
+        }
+        Ok(Saved {
+            data: serde_json::to_string(&types)?,
+            skipped,
+        })
+    }
+
+    /// Sets the values saved by [`TypeRegistry::save_json`] in `store`,
+    /// returning the names of the unregistered types skipped.
+    /// Values of types loaded before an error stay set.
+    pub fn load_json(
+        &self,
+        store: &mut MiniStore<K>,
+        json: &str,
+        unregistered: Unregistered,
+    ) -> Result<Vec<String>, SerializationError> {
+        let types: Map<String, Value> = serde_json::from_str(json)?;
+        let mut skipped = vec![];
+        for (name, entries) in types {
+            match self.registration(&name) {
+                Some(registration) => (registration.from_json)(store, entries)?,
+                None => unregistered.handle(&name, &mut skipped)?,
+            }
+        }
+        Ok(skipped)
+    }
+
+    /// Writes `store` in a compact binary form.
+    /// Fails with [`SerializationError::Borrowed`] if any values are borrowed mutably.
+    pub fn save_binary(
+        &self,
+        store: &MiniStore<K>,
+        unregistered: Unregistered,
+    ) -> Result<Saved<Vec<u8>>, SerializationError> {
+        let (registrations, skipped) = self.registrations(store, unregistered)?;
+        let types = registrations
+            .into_iter()
+            .map(|registration| Ok((registration.name.as_str(), (registration.to_binary)(store)?)))
+            .collect::<Result<Vec<_>, SerializationError>>()?;
+        Ok(Saved {
+            data: bincode::serialize(&types)?,
+            skipped,
+        })
+    }
+
+    /// Sets the values saved by [`TypeRegistry::save_binary`] in `store`,
+    /// returning the names of the unregistered types skipped.
+    /// Values of types loaded before an error stay set.
+    pub fn load_binary(
+        &self,
+        store: &mut MiniStore<K>,
+        bytes: &[u8],
+        unregistered: Unregistered,
+    ) -> Result<Vec<String>, SerializationError> {
+        let types: Vec<(String, Vec<u8>)> = bincode::deserialize(bytes)?;
+        let mut skipped = vec![];
+        for (name, entries) in types {
+            match self.registration(&name) {
+                Some(registration) => (registration.from_binary)(store, &entries)?,
+                None => unregistered.handle(&name, &mut skipped)?,
+            }
+        }
+        Ok(skipped)
+    }
+}
+
+/// Calls `f` with every entry of `T` in `store`, failing if they're borrowed mutably
+fn with_entries<K, T, R>(
+    store: &MiniStore<K>,
+    f: impl FnOnce(&[(&K, &T)]) -> R,
+) -> Result<R, SerializationError>
+where
+    K: Ord + Hash + 'static,
+    T: 'static,
+{
+    let storage = store
+        .try_storage::<T>()
+        .map_err(|_| SerializationError::Borrowed(Some(type_name::<T>())))?;
+    Ok(match storage {
+        Some(storage) => {
+            let entries = storage
+                .keys()
+                .map(|key| (key, storage.get(key).unwrap().value()))
+                .collect::<Vec<_>>();
+            f(&entries)
+        }
+        None => f(&[]),
+    })
+}
+
+fn to_json<K, T>(store: &MiniStore<K>) -> Result<Value, SerializationError>
+where
+    K: Ord + Hash + Serialize + 'static,
+    T: Serialize + 'static,
+{
+    let value = with_entries::<K, T, _>(store, |entries| serde_json::to_value(entries))??;
+    Ok(value)
+}
+
+fn from_json<K, T>(store: &mut MiniStore<K>, entries: Value) -> Result<(), SerializationError>
+where
+    K: Ord + Hash + DeserializeOwned + 'static,
+    T: DeserializeOwned + 'static,
+{
+    for (key, value) in serde_json::from_value::<Vec<(K, T)>>(entries)? {
+        store.set(key, value);
+    }
+    Ok(())
+}
+
+fn to_binary<K, T>(store: &MiniStore<K>) -> Result<Vec<u8>, SerializationError>
+where
+    K: Ord + Hash + Serialize + 'static,
+    T: Serialize + 'static,
+{
+    let bytes = with_entries::<K, T, _>(store, |entries| bincode::serialize(entries))??;
+    Ok(bytes)
+}
+
+fn from_binary<K, T>(store: &mut MiniStore<K>, entries: &[u8]) -> Result<(), SerializationError>
+where
+    K: Ord + Hash + DeserializeOwned + 'static,
+    T: DeserializeOwned + 'static,
+{
+    for (key, value) in bincode::deserialize::<Vec<(K, T)>>(entries)? {
+        store.set(key, value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use fnv::FnvHashMap;
+
+    use super::*;
+    use crate::mini_store::VecMap;
+
+    fn store() -> MiniStore<u32> {
+        let mut store = MiniStore::default();
+        store.register::<String, _>(VecMap::new());
+        for key in 0..3 {
+            store.set(key, key.to_string());
+            store.set(key * 2, key as f32 / 2.0);
+        }
+        store.set(7, true);
+        store
+    }
+
+    fn registry() -> TypeRegistry<u32> {
+        let mut registry = TypeRegistry::new();
+        registry
+            .register::<String>("string")
+            .register::<f32>("float");
+        registry
+    }
+
+    fn assert_loaded(store: &MiniStore<u32>) {
+        assert_eq!(store.keys::<String>(), vec![0, 1, 2]);
+        assert_eq!(store.get::<String>(&2).as_deref(), Some(&"2".to_string()));
+        let mut floats = store.keys::<f32>();
+        floats.sort();
+        assert_eq!(floats, vec![0, 2, 4]);
+        assert_eq!(store.get::<f32>(&4).as_deref(), Some(&1.0));
+        assert!(!store.contains::<bool>(&7));
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let saved = registry().save_json(&store(), Unregistered::Skip).unwrap();
+        assert_eq!(
+            saved.data,
+            r#"{"float":[[0,0.0],[2,0.5],[4,1.0]],"string":[[0,"0"],[1,"1"],[2,"2"]]}"#
+        );
+        assert_eq!(saved.skipped, vec![std::any::type_name::<bool>()]);
+
+        let mut loaded = MiniStore::default();
+        loaded.register::<f32, _>(FnvHashMap::default());
+        registry()
+            .load_json(&mut loaded, &saved.data, Unregistered::Fail)
+            .unwrap();
+        assert_loaded(&loaded);
+    }
+
+    #[test]
+    fn binary_round_trips() {
+        let bytes = registry()
+            .save_binary(&store(), Unregistered::Skip)
+            .unwrap()
+            .data;
+        let mut loaded = MiniStore::default();
+        registry()
+            .load_binary(&mut loaded, &bytes, Unregistered::Fail)
+            .unwrap();
+        assert_loaded(&loaded);
+
+        assert!(matches!(
+            registry().load_binary(&mut loaded, &bytes[..bytes.len() - 1], Unregistered::Fail),
+            Err(SerializationError::Binary(_))
+        ));
+    }
+
+    #[test]
+    fn unregistered_types_fail_if_asked() {
+        assert!(matches!(
+            registry().save_json(&store(), Unregistered::Fail),
+            Err(SerializationError::Unregistered(name)) if name == "bool"
+        ));
+
+        let json = registry()
+            .save_json(&store(), Unregistered::Skip)
+            .unwrap()
+            .data;
+        let mut partial = TypeRegistry::<u32>::new();
+        partial.register::<String>("string");
+        assert!(matches!(
+            partial.load_json(&mut MiniStore::default(), &json, Unregistered::Fail),
+            Err(SerializationError::Unregistered(name)) if name == "float"
+        ));
+
+        let mut loaded = MiniStore::default();
+        let skipped = partial
+            .load_json(&mut loaded, &json, Unregistered::Skip)
+            .unwrap();
+        assert_eq!(skipped, vec!["float"]);
+        assert_eq!(loaded.keys::<String>(), vec![0, 1, 2]);
+        assert!(loaded.keys::<f32>().is_empty());
+    }
+
+    #[test]
+    fn borrowed_values_fail_to_save() {
+        let store = store();
+        let held = store.get_mut::<f32>(&0);
+        assert!(matches!(
+            registry().save_json(&store, Unregistered::Skip),
+            Err(SerializationError::Borrowed(Some("f32")))
+        ));
+        drop(held);
+
+        let held = store.get_mut::<bool>(&7);
+        assert!(matches!(
+            registry().save_binary(&store, Unregistered::Skip),
+            Err(SerializationError::Borrowed(None))
+        ));
+        drop(held);
+    }
+
+    #[test]
+    #[should_panic]
+    fn names_are_unique() {
+        registry().register::<i32>("float");
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::{self, Display};
+
+/// Why a [`TypeRegistry`](super::TypeRegistry) couldn't save or load a store
+#[derive(Debug)]
+pub enum SerializationError {
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+    /// Values of a type, or under a name, that isn't registered
+    Unregistered(String),
+    /// Values of a type are borrowed mutably, so can't be read.
+    /// Unregistered types can't be named while borrowed.
+    Borrowed(Option<&'static str>),
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializationError::Json(error) => write!(f, "JSON error: {}", error),
+            SerializationError::Binary(error) => write!(f, "Binary error: {}", error),
+            SerializationError::Unregistered(name) => write!(f, "{} isn't registered", name),
+            SerializationError::Borrowed(Some(name)) => {
+                write!(f, "Values of {} are borrowed mutably", name)
+            }
+            SerializationError::Borrowed(None) => {
+                write!(f, "Values of an unregistered type are borrowed mutably")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SerializationError {}
+
+impl From<serde_json::Error> for SerializationError {
+    fn from(error: serde_json::Error) -> Self {
+        SerializationError::Json(error)
+    }
+}
+
+impl From<bincode::Error> for SerializationError {
+    fn from(error: bincode::Error) -> Self {
+        SerializationError::Binary(error)
+    }
+}
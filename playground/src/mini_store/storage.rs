@@ -0,0 +1,200 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+};
+
+use downcast_rs::{impl_downcast, Downcast};
+
+use super::Tracked;
+
+/// A map from keys to the values of one type, which a [`MiniStore`] keeps for each type.
+///
+/// Implement this to add a storage backend selectable through [`MiniStore::register`].
+/// A `MiniStore` stores its values wrapped in [`Tracked`], so backends should be generic over `V`.
+pub trait Storage<K, V> {
+    /// Returns the previous value stored under `key`, if any
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn get(&self, key: &K) -> Option<&V>;
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterates the keys present, in order if the storage is ordered
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_>;
+
+    /// Removes and returns every entry, in no particular order
+    fn drain(&mut self) -> Vec<(K, V)>;
+
+    fn clear(&mut self) {
+        self.drain();
+    }
+}
+
+/// Object-safe view of the storage of any one type, so a [`MiniStore`](super::MiniStore) can act
+/// on a key in every storage without knowing their types
+pub trait AnyStorage<K>: Downcast {
+    /// Removes the value under `key`, returning whether there was one
+    fn remove_key(&mut self, key: &K) -> bool;
+
+    /// Name of the type of the values stored, for diagnostics
+    fn value_type_name(&self) -> &'static str;
+}
+impl_downcast!(AnyStorage<K>);
+
+/// Type-erased storage for the values of one type
+pub struct AssocMap<K, V>(Box<dyn Storage<K, V>>);
+
+impl<K, V> AssocMap<K, V> {
+    pub fn new<S>(storage: S) -> Self
+    where
+        S: Storage<K, V> + 'static,
+    {
+        AssocMap(Box::new(storage))
+    }
+}
+
+impl<K, V> AnyStorage<K> for AssocMap<K, Tracked<V>>
+where
+    K: 'static,
+    V: 'static,
+{
+    fn remove_key(&mut self, key: &K) -> bool {
+        self.remove(key).is_some()
+    }
+
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<V>()
+    }
+}
+
+impl<K, V> Default for AssocMap<K, V>
+where
+    K: Ord + 'static,
+    V: 'static,
+{
+    fn default() -> Self {
+        AssocMap::new(BTreeMap::new())
+    }
+}
+
+impl<K, V> std::ops::Deref for AssocMap<K, V> {
+    type Target = dyn Storage<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl<K, V> std::ops::DerefMut for AssocMap<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *self.0
+    }
+}
+
+/// Type-erased storage for the values of one type that can be shared between threads,
+/// which a [`SyncMiniStore`](super::SyncMiniStore) keeps for each type
+pub struct SyncAssocMap<K, V>(Box<dyn Storage<K, V> + Send + Sync>);
+
+impl<K, V> SyncAssocMap<K, V> {
+    pub fn new<S>(storage: S) -> Self
+    where
+        S: Storage<K, V> + Send + Sync + 'static,
+    {
+        SyncAssocMap(Box::new(storage))
+    }
+}
+
+impl<K, V> Default for SyncAssocMap<K, V>
+where
+    K: Ord + Send + Sync + 'static,
+    V: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        SyncAssocMap::new(BTreeMap::new())
+    }
+}
+
+impl<K, V> std::ops::Deref for SyncAssocMap<K, V> {
+    type Target = dyn Storage<K, V> + Send + Sync;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl<K, V> std::ops::DerefMut for SyncAssocMap<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *self.0
+    }
+}
+
+impl<K, V> Storage<K, V> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(BTreeMap::keys(self))
+    }
+
+    fn drain(&mut self) -> Vec<(K, V)> {
+        std::mem::take(self).into_iter().collect()
+    }
+
+    fn clear(&mut self) {
+        BTreeMap::clear(self)
+    }
+}
+
+// Covers both std's HashMap and FnvHashMap
+impl<K, V, S> Storage<K, V> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(HashMap::keys(self))
+    }
+
+    fn drain(&mut self) -> Vec<(K, V)> {
+        HashMap::drain(self).collect()
+    }
+
+    fn clear(&mut self) {
+        HashMap::clear(self)
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    any::{type_name, TypeId},
+    cell::{BorrowError, Ref, RefCell, RefMut},
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use super::{AnyStorage, AssocMap, Field, FieldType, QueryError, Storage, Ticks, Tracked};
+
+/// The storage of values of `T`, borrowed from a [`MiniStore`]
+type StorageRef<'a, K, T> = Ref<'a, AssocMap<K, Tracked<T>>>;
+
+/// Values of any number of types, each stored under keys of type `K`.
+///
+/// Types that were never stored are treated as present with no keys.
+///
+/// Each value records the store's tick when it was added, and when it was last set or
+/// borrowed mutably, for queries to pick out what changed since a given tick.
+/// The tick each key's value of a type was removed at is kept until the key is set again,
+/// or forgotten with [`MiniStore::forget_removed`].
+pub struct MiniStore<K> {
+    storage: HashMap<TypeId, RefCell<Box<dyn AnyStorage<K>>>>,
+    removed: HashMap<TypeId, BTreeMap<K, u64>>,
+    tick: u64,
+    _phantom: PhantomData<K>,
+}
+
+impl<K> Default for MiniStore<K> {
+    fn default() -> Self {
+        MiniStore {
+            storage: HashMap::new(),
+            removed: HashMap::new(),
+            tick: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K> MiniStore<K>
+where
+    K: Ord + Hash + 'static,
+{
+    fn create_storage_for<T>() -> Box<dyn AnyStorage<K>>
+    where
+        T: 'static,
+    {
+        Box::new(AssocMap::<K, Tracked<T>>::default())
+    }
+
+    fn downcast<T>(storage: &dyn AnyStorage<K>) -> &AssocMap<K, Tracked<T>>
+    where
+        T: 'static,
+    {
+        storage.downcast_ref::<AssocMap<K, Tracked<T>>>().unwrap()
+    }
+
+    fn downcast_mut<T>(storage: &mut dyn AnyStorage<K>) -> &mut AssocMap<K, Tracked<T>>
+    where
+        T: 'static,
+    {
+        storage.downcast_mut::<AssocMap<K, Tracked<T>>>().unwrap()
+    }
+
+    /// The tick that values set or borrowed mutably are stamped with
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Starts a new tick, so changes from now on are newer than any before, and returns it
+    pub fn advance_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Stores values of `T` in `storage` from now on, moving any already stored into it.
+    ///
+    /// Types that aren't registered are stored in a `BTreeMap`.
+    pub fn register<T, S>(&mut self, storage: S)
+    where
+        T: 'static,
+        S: Storage<K, Tracked<T>> + 'static,
+    {
+        let mut storage = AssocMap::new(storage);
+        if let Some(previous) = self.storage.remove(&TypeId::of::<T>()) {
+            for (key, value) in Self::downcast_mut::<T>(&mut **previous.borrow_mut()).drain() {
+                storage.insert(key, value);
+            }
+        }
+        self.storage
+            .insert(TypeId::of::<T>(), RefCell::new(Box::new(storage)));
+    }
+
+    /// Stores `value` under `key`, marking it added if there was none, and changed
+    pub fn set<T>(&mut self, key: K, value: T)
+    where
+        T: 'static,
+    {
+        let tick = self.tick;
+        let storage = self
+            .storage
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| RefCell::new(Self::create_storage_for::<T>()))
+            .get_mut();
+
+        let storage = Self::downcast_mut::<T>(&mut **storage);
+        match storage.get_mut(&key) {
+            Some(tracked) => {
+                tracked.value = value;
+                tracked.ticks.changed = tick;
+            }
+            None => {
+                if let Some(removed) = self.removed.get_mut(&TypeId::of::<T>()) {
+                    removed.remove(&key);
+                }
+                storage.insert(key, Tracked::new(value, tick));
+            }
+        }
+    }
+
+    /// Removes and returns the value of `T` under `key`, marking it removed at the current tick
+    pub fn remove<T>(&mut self, key: &K) -> Option<T>
+    where
+        T: 'static,
+        K: Clone,
+    {
+        let storage = self.storage.get_mut(&TypeId::of::<T>())?.get_mut();
+        let value = Self::downcast_mut::<T>(&mut **storage).remove(key)?;
+        self.removed
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .insert(key.clone(), self.tick);
+        Some(value.into_value())
+    }
+
+    /// Removes the values of every type under `key`
+    pub fn remove_all(&mut self, key: &K)
+    where
+        K: Clone,
+    {
+        for (type_id, storage) in &mut self.storage {
+            if storage.get_mut().remove_key(key) {
+                self.removed
+                    .entry(*type_id)
+                    .or_default()
+                    .insert(key.clone(), self.tick);
+            }
+        }
+    }
+
+    /// Removes every value of `T`, keeping the storage it was registered with
+    pub fn clear<T>(&mut self)
+    where
+        T: 'static,
+    {
+        if let Some(storage) = self.storage.get_mut(&TypeId::of::<T>()) {
+            let removed = self.removed.entry(TypeId::of::<T>()).or_default();
+            for (key, _) in Self::downcast_mut::<T>(&mut **storage.get_mut()).drain() {
+                removed.insert(key, self.tick);
+            }
+        }
+    }
+
+    /// The tick at which the value of `T` under `key` was removed, if it hasn't been set since
+    pub fn removed_tick<T>(&self, key: &K) -> Option<u64>
+    where
+        T: 'static,
+    {
+        self.removed.get(&TypeId::of::<T>())?.get(key).copied()
+    }
+
+    /// The keys whose value of `T` was removed and not set since, in order
+    pub fn removed_keys<T>(&self) -> Vec<K>
+    where
+        T: 'static,
+        K: Clone,
+    {
+        self.removed
+            .get(&TypeId::of::<T>())
+            .map_or_else(Vec::new, |removed| removed.keys().cloned().collect())
+    }
+
+    /// Forgets the removals made at or before `tick`, so they no longer match `Removed` fields
+    pub fn forget_removed(&mut self, tick: u64) {
+        for removed in self.removed.values_mut() {
+            removed.retain(|_, removed| *removed > tick);
+        }
+    }
+
+    /// Whether a value of `T` is stored under `key`
+    pub fn contains<T>(&self, key: &K) -> bool
+    where
+        T: 'static,
+    {
+        self.storage
+            .get(&TypeId::of::<T>())
+            .is_some_and(|storage| Self::downcast::<T>(&**storage.borrow()).contains_key(key))
+    }
+
+    /// The keys with a value of `T`, in the order the storage iterates them
+    pub fn keys<T>(&self) -> Vec<K>
+    where
+        T: 'static,
+        K: Clone,
+    {
+        self.storage
+            .get(&TypeId::of::<T>())
+            .map_or_else(Vec::new, |storage| {
+                Self::downcast::<T>(&**storage.borrow())
+                    .keys()
+                    .cloned()
+                    .collect()
+            })
+    }
+
+    /// The ticks at which the value of `T` under `key` was added and last changed
+    pub fn ticks<T>(&self, key: &K) -> Option<Ticks>
+    where
+        T: 'static,
+    {
+        let storage = self.storage.get(&TypeId::of::<T>())?.borrow();
+        Self::downcast::<T>(&**storage).get(key).map(Tracked::ticks)
+    }
+
+    /// The storage of `T`, or an error if it's borrowed mutably
+    pub(super) fn try_storage<T>(&self) -> Result<Option<StorageRef<'_, K, T>>, BorrowError>
+    where
+        T: 'static,
+    {
+        match self.storage.get(&TypeId::of::<T>()) {
+            Some(storage) => Ok(Some(Ref::map(storage.try_borrow()?, |storage| {
+                Self::downcast::<T>(&**storage)
+            }))),
+            None => Ok(None),
+        }
+    }
+
+    /// The types stored, with their names if their storage isn't borrowed mutably
+    pub(super) fn types(
+        &self,
+    ) -> impl Iterator<Item = (TypeId, Result<&'static str, BorrowError>)> + '_ {
+        self.storage.iter().map(|(type_id, storage)| {
+            let name = storage
+                .try_borrow()
+                .map(|storage| storage.value_type_name());
+            (*type_id, name)
+        })
+    }
+
+    /// Checks that the field of `T` under `key` could be borrowed, without keeping the borrow
+    pub(super) fn check_field<T>(
+        &self,
+        key: &K,
+        mutable: bool,
+        required: bool,
+    ) -> Result<(), QueryError>
+    where
+        T: 'static,
+    {
+        let storage = match self.storage.get(&TypeId::of::<T>()) {
+            Some(storage) => storage,
+            None if required => return Err(QueryError::MissingStorage(type_name::<T>())),
+            None => return Ok(()),
+        };
+
+        let aliased = match mutable {
+            true => storage.try_borrow_mut().is_err(),
+            false => storage.try_borrow().is_err(),
+        };
+        if aliased {
+            return Err(QueryError::Aliased(type_name::<T>()));
+        }
+        if required && !self.contains::<T>(key) {
+            return Err(QueryError::MissingKey(type_name::<T>()));
+        }
+        Ok(())
+    }
+
+    /// Checks that a value of `T` is under `key` and its ticks pass `recent`
+    pub(super) fn check_ticks<T>(
+        &self,
+        key: &K,
+        recent: impl FnOnce(Ticks) -> bool,
+    ) -> Result<(), QueryError>
+    where
+        T: 'static,
+    {
+        let storage = self
+            .try_storage::<T>()
+            .map_err(|_| QueryError::Aliased(type_name::<T>()))?;
+        match storage.and_then(|storage| storage.get(key).map(Tracked::ticks)) {
+            Some(ticks) if recent(ticks) => Ok(()),
+            Some(_) => Err(QueryError::Unchanged(type_name::<T>())),
+            None => Err(QueryError::MissingKey(type_name::<T>())),
+        }
+    }
+
+    /// Checks that no value of `T` is under `key`, and that one was removed after `since`
+    pub(super) fn check_removed<T>(&self, key: &K, since: u64) -> Result<(), QueryError>
+    where
+        T: 'static,
+    {
+        self.check_presence::<T>(key, false)?;
+        match self.removed_tick::<T>(key) {
+            Some(removed) if removed > since => Ok(()),
+            _ => Err(QueryError::Unchanged(type_name::<T>())),
+        }
+    }
+
+    /// Checks that a value of `T` is under `key` if `present`, or isn't if not
+    pub(super) fn check_presence<T>(&self, key: &K, present: bool) -> Result<(), QueryError>
+    where
+        T: 'static,
+    {
+        let contains = match self.storage.get(&TypeId::of::<T>()) {
+            Some(storage) => match storage.try_borrow() {
+                Ok(storage) => Self::downcast::<T>(&**storage).contains_key(key),
+                Err(_) => return Err(QueryError::Aliased(type_name::<T>())),
+            },
+            None => false,
+        };
+        match (present, contains) {
+            (true, false) => Err(QueryError::MissingKey(type_name::<T>())),
+            (false, true) => Err(QueryError::Excluded(type_name::<T>())),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn get<'a, T>(&'a self, key: &K) -> Option<Ref<'a, T>>
+    where
+        T: 'static,
+    {
+        let storage = self.storage.get(&TypeId::of::<T>())?;
+        Ref::filter_map(storage.borrow(), |storage| {
+            Self::downcast::<T>(&**storage).get(key).map(Tracked::value)
+        })
+        .ok()
+    }
+
+    /// Mutably borrows the value of `T` under `key`, marking it changed at the current tick
+    pub fn get_mut<'a, T>(&'a self, key: &K) -> Option<RefMut<'a, T>>
+    where
+        T: 'static,
+    {
+        let storage = self.storage.get(&TypeId::of::<T>())?;
+        RefMut::filter_map(storage.borrow_mut(), |storage| {
+            let tracked = Self::downcast_mut::<T>(&mut **storage).get_mut(key)?;
+            tracked.ticks.changed = self.tick;
+            Some(&mut tracked.value)
+        })
+        .ok()
+    }
+
+    /// Borrows the field of `T` under `key` as `field_type` asks.
+    ///
+    /// Panics if a required field is missing or already borrowed;
+    /// [`MiniStore::try_query`] reports that instead.
+    pub fn query_field<'a, T>(&'a self, field_type: FieldType<T>, key: &K) -> Field<'a, T>
+    where
+        T: 'static,
+    {
+        match field_type {
+            FieldType::Some | FieldType::Added(_) | FieldType::Changed(_) => Field::Some,
+            FieldType::None | FieldType::Removed(_) => Field::None,
+            FieldType::Immutable => Field::Immutable(
+                self.get::<T>(key)
+                    .unwrap_or_else(|| panic!("No field of type {:?}", type_name::<T>())),
+            ),
+            FieldType::Mutable => Field::Mutable(
+                self.get_mut::<T>(key)
+                    .unwrap_or_else(|| panic!("No field of type {:?}", type_name::<T>())),
+            ),
+            FieldType::MaybeImmutable => Field::MaybeImmutable(self.get::<T>(key)),
+            FieldType::MaybeMutable => Field::MaybeMutable(self.get_mut::<T>(key)),
+            FieldType::Phantom(_) => panic!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use fnv::FnvHashMap;
+
+    use super::*;
+    use crate::mini_store::VecMap;
+
+    fn store_with<S>(storage: S) -> MiniStore<i32>
+    where
+        S: Storage<i32, Tracked<String>> + 'static,
+    {
+        let mut store = MiniStore::default();
+        store.register::<String, _>(storage);
+        for key in 0..4 {
+            store.set(key, key.to_string());
+        }
+        store
+    }
+
+    fn assert_backend_works(store: MiniStore<i32>) {
+        assert_eq!(store.get::<String>(&2).as_deref(), Some(&"2".to_string()));
+        assert!(store.get::<String>(&4).is_none());
+
+        store.get_mut::<String>(&3).unwrap().push('!');
+        assert_eq!(store.get::<String>(&3).as_deref(), Some(&"3!".to_string()));
+    }
+
+    #[test]
+    fn every_backend_stores_values() {
+        assert_backend_works(store_with(BTreeMap::new()));
+        assert_backend_works(store_with(HashMap::new()));
+        assert_backend_works(store_with(FnvHashMap::default()));
+        assert_backend_works(store_with(VecMap::new()));
+    }
+
+    #[test]
+    fn registering_moves_existing_values() {
+        let mut store = MiniStore::default();
+        store.set(0, 1.5f32);
+        store.set(1, 2.5f32);
+
+        store.register::<f32, _>(VecMap::new());
+        assert_eq!(store.get::<f32>(&0).as_deref(), Some(&1.5));
+        assert_eq!(store.get::<f32>(&1).as_deref(), Some(&2.5));
+    }
+
+    #[test]
+    fn values_can_be_removed() {
+        let mut store = store_with(VecMap::new());
+        store.set(1, 1.5f32);
+        store.set(2, 2.5f32);
+
+        assert_eq!(store.remove::<String>(&1), Some("1".to_string()));
+        assert_eq!(store.remove::<String>(&1), None);
+        assert!(!store.contains::<String>(&1));
+        assert!(store.contains::<f32>(&1));
+
+        store.remove_all(&2);
+        assert!(!store.contains::<String>(&2));
+        assert!(!store.contains::<f32>(&2));
+        assert_eq!(store.keys::<String>(), vec![0, 3]);
+        assert_eq!(store.keys::<f32>(), vec![1]);
+
+        store.clear::<String>();
+        assert!(store.keys::<String>().is_empty());
+        store.set(5, "5".to_string());
+        assert_eq!(store.keys::<String>(), vec![5]);
+    }
+
+    #[test]
+    fn unregistered_types_are_absent() {
+        let mut store = MiniStore::<i32>::default();
+        assert!(store.get::<u8>(&0).is_none());
+        assert!(store.get_mut::<u8>(&0).is_none());
+        assert!(!store.contains::<u8>(&0));
+        assert!(store.keys::<u8>().is_empty());
+        assert_eq!(store.remove::<u8>(&0), None);
+        store.clear::<u8>();
+        store.remove_all(&0);
+    }
+}
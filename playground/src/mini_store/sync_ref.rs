@@ -0,0 +1,109 @@
+use std::{
+    any::Any,
+    fmt::{self, Debug},
+    ops::{Deref, DerefMut},
+};
+
+use async_std::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+use super::SyncAssocMap;
+
+pub(super) type ReadStorage<'a> = RwLockReadGuard<'a, Box<dyn Any + Send + Sync>>;
+pub(super) type WriteStorage<'a> = RwLockWriteGuard<'a, Box<dyn Any + Send + Sync>>;
+
+/// A value of a [`SyncMiniStore`](super::SyncMiniStore), holding a read lock on the storage of
+/// its type
+pub struct SyncRef<'a, T> {
+    _guard: ReadStorage<'a>,
+    value: *const T,
+}
+
+impl<'a, T> SyncRef<'a, T>
+where
+    T: 'static,
+{
+    /// Borrows the value under `key` from the storage of `T` locked by `guard`
+    pub(super) fn new<K>(guard: ReadStorage<'a>, key: &K) -> Option<Self>
+    where
+        K: 'static,
+    {
+        let value: *const T = guard
+            .downcast_ref::<SyncAssocMap<K, T>>()
+            .unwrap()
+            .get(key)?;
+        Some(SyncRef {
+            _guard: guard,
+            value,
+        })
+    }
+}
+
+impl<'a, T> Deref for SyncRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // The storage can't be modified while its read guard is held,
+        // so the value stays where it was found
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T> Debug for SyncRef<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+/// A value of a [`SyncMiniStore`](super::SyncMiniStore), holding a write lock on the storage of
+/// its type
+pub struct SyncRefMut<'a, T> {
+    _guard: WriteStorage<'a>,
+    value: *mut T,
+}
+
+impl<'a, T> SyncRefMut<'a, T>
+where
+    T: 'static,
+{
+    /// Mutably borrows the value under `key` from the storage of `T` locked by `guard`
+    pub(super) fn new<K>(mut guard: WriteStorage<'a>, key: &K) -> Option<Self>
+    where
+        K: 'static,
+    {
+        let value: *mut T = guard
+            .downcast_mut::<SyncAssocMap<K, T>>()
+            .unwrap()
+            .get_mut(key)?;
+        Some(SyncRefMut {
+            _guard: guard,
+            value,
+        })
+    }
+}
+
+impl<'a, T> Deref for SyncRefMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // The guard is exclusive, so this is the only borrow of the storage
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T> DerefMut for SyncRefMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.value }
+    }
+}
+
+impl<'a, T> Debug for SyncRefMut<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use async_std::{sync::RwLock, task};
+
+use super::{
+    FieldType, QueryField, Storage, SyncAssocMap, SyncField, SyncRef, SyncRefMut, WouldBlock,
+};
+
+type TypeLock = RwLock<Box<dyn Any + Send + Sync>>;
+
+/// A [`MiniStore`](super::MiniStore) that can be shared between threads.
+///
+/// The storage of each type sits behind its own read-write lock, so any number of threads can
+/// read a type while no one writes it, and borrows of different types never contend.
+/// Each lock can be taken blocking the thread (`get`), failing if it's held (`try_get`),
+/// or awaiting it (`get_async`).
+///
+/// As with `RefCell` borrows in a `MiniStore`, a thread that borrows a type mutably while holding
+/// another borrow of it blocks forever; use the `try_` accessors where that can happen.
+pub struct SyncMiniStore<K> {
+    storage: HashMap<TypeId, TypeLock>,
+    _phantom: PhantomData<K>,
+}
+
+impl<K> Default for SyncMiniStore<K> {
+    fn default() -> Self {
+        SyncMiniStore {
+            storage: HashMap::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K> SyncMiniStore<K>
+where
+    K: Ord + Hash + Send + Sync + 'static,
+{
+    fn lock<T>(&self) -> Option<&TypeLock>
+    where
+        T: 'static,
+    {
+        self.storage.get(&TypeId::of::<T>())
+    }
+
+    fn downcast_mut<T>(storage: &mut dyn Any) -> &mut SyncAssocMap<K, T>
+    where
+        T: 'static,
+    {
+        storage.downcast_mut::<SyncAssocMap<K, T>>().unwrap()
+    }
+
+    /// Stores values of `T` in `storage` from now on, moving any already stored into it.
+    ///
+    /// Types that aren't registered are stored in a `BTreeMap`.
+    pub fn register<T, S>(&mut self, storage: S)
+    where
+        T: Send + Sync + 'static,
+        S: Storage<K, T> + Send + Sync + 'static,
+    {
+        let mut storage = SyncAssocMap::new(storage);
+        if let Some(previous) = self.storage.remove(&TypeId::of::<T>()) {
+            for (key, value) in Self::downcast_mut::<T>(&mut *previous.into_inner()).drain() {
+                storage.insert(key, value);
+            }
+        }
+        self.storage
+            .insert(TypeId::of::<T>(), RwLock::new(Box::new(storage)));
+    }
+
+    /// Stores `value` under `key`.
+    ///
+    /// This takes the store mutably, so it never waits on a lock.
+    pub fn set<T>(&mut self, key: K, value: T)
+    where
+        T: Send + Sync + 'static,
+    {
+        let storage = self
+            .storage
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| RwLock::new(Box::new(SyncAssocMap::<K, T>::default())))
+            .get_mut();
+
+        Self::downcast_mut::<T>(&mut **storage).insert(key, value);
+    }
+
+    /// Borrows the value of `T` under `key`, blocking while the storage of `T` is written
+    pub fn get<T>(&self, key: &K) -> Option<SyncRef<'_, T>>
+    where
+        T: 'static,
+    {
+        SyncRef::new(task::block_on(self.lock::<T>()?.read()), key)
+    }
+
+    /// Mutably borrows the value of `T` under `key`, blocking while the storage of `T` is borrowed
+    pub fn get_mut<T>(&self, key: &K) -> Option<SyncRefMut<'_, T>>
+    where
+        T: 'static,
+    {
+        SyncRefMut::new(task::block_on(self.lock::<T>()?.write()), key)
+    }
+
+    /// Borrows the value of `T` under `key`, failing if the storage of `T` is being written
+    pub fn try_get<T>(&self, key: &K) -> Result<Option<SyncRef<'_, T>>, WouldBlock>
+    where
+        T: 'static,
+    {
+        match self.lock::<T>() {
+            Some(lock) => {
+                let guard = lock.try_read().ok_or(WouldBlock(type_name::<T>()))?;
+                Ok(SyncRef::new(guard, key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Mutably borrows the value of `T` under `key`, failing if the storage of `T` is borrowed
+    pub fn try_get_mut<T>(&self, key: &K) -> Result<Option<SyncRefMut<'_, T>>, WouldBlock>
+    where
+        T: 'static,
+    {
+        match self.lock::<T>() {
+            Some(lock) => {
+                let guard = lock.try_write().ok_or(WouldBlock(type_name::<T>()))?;
+                Ok(SyncRefMut::new(guard, key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Borrows the value of `T` under `key`, waiting for any write of the storage of `T` to end
+    pub async fn get_async<T>(&self, key: &K) -> Option<SyncRef<'_, T>>
+    where
+        T: 'static,
+    {
+        SyncRef::new(self.lock::<T>()?.read().await, key)
+    }
+
+    /// Mutably borrows the value of `T` under `key`, waiting for every borrow of the storage of
+    /// `T` to end
+    pub async fn get_mut_async<T>(&self, key: &K) -> Option<SyncRefMut<'_, T>>
+    where
+        T: 'static,
+    {
+        SyncRefMut::new(self.lock::<T>()?.write().await, key)
+    }
+
+    /// Looks up the value of `T` under `key` with `field_type`, blocking like [`Self::get`].
+    ///
+    /// `Some` and `None` filters borrow nothing and aren't checked.
+    ///
+    /// # Panics
+    ///
+    /// Panics on `Added`, `Changed` and `Removed` filters, as values have no ticks to check
+    /// them against.
+    pub fn query_field<T>(&self, field_type: FieldType<T>, key: &K) -> SyncField<'_, T>
+    where
+        T: 'static,
+    {
+        match field_type {
+            FieldType::Some => SyncField::Some,
+            FieldType::None => SyncField::None,
+            FieldType::Added(_) | FieldType::Changed(_) | FieldType::Removed(_) => {
+                panic!("SyncMiniStore doesn't track ticks")
+            }
+            FieldType::Immutable => SyncField::Immutable(
+                self.get::<T>(key)
+                    .unwrap_or_else(|| panic!("No field of type {:?}", type_name::<T>())),
+            ),
+            FieldType::Mutable => SyncField::Mutable(
+                self.get_mut::<T>(key)
+                    .unwrap_or_else(|| panic!("No field of type {:?}", type_name::<T>())),
+            ),
+            FieldType::MaybeImmutable => SyncField::MaybeImmutable(self.get::<T>(key)),
+            FieldType::MaybeMutable => SyncField::MaybeMutable(self.get_mut::<T>(key)),
+            FieldType::Phantom(_) => panic!(),
+        }
+    }
+}
+
+impl<'a, K, T> QueryField<'a, K, T> for SyncMiniStore<K>
+where
+    K: Ord + Hash + Send + Sync + 'static,
+    T: 'static,
+{
+    type Field = SyncField<'a, T>;
+
+    fn query_field(&'a self, field_type: FieldType<T>, key: &K) -> Self::Field {
+        SyncMiniStore::query_field(self, field_type, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, thread};
+
+    use super::*;
+    use crate::mini_store::{MiniStoreQuery, VecMap};
+
+    #[test]
+    fn threads_share_the_store() {
+        let mut store = SyncMiniStore::<u32>::default();
+        store.register::<u64, _>(HashMap::new());
+        store.register::<String, _>(VecMap::new());
+        for key in 0..4 {
+            store.set(key, 0u64);
+            store.set(key, format!("counter {}", key));
+        }
+
+        thread::scope(|scope| {
+            for thread in 0..8u32 {
+                let store = &store;
+                scope.spawn(move || {
+                    for round in 0..1000u32 {
+                        let key = (thread + round) % 4;
+                        *store.get_mut::<u64>(&key).unwrap() += 1;
+                        assert_eq!(
+                            *store.get::<String>(&key).unwrap(),
+                            format!("counter {}", key)
+                        );
+                    }
+                });
+            }
+        });
+
+        let total: u64 = (0..4).map(|key| *store.get::<u64>(&key).unwrap()).sum();
+        assert_eq!(total, 8000);
+    }
+
+    #[test]
+    fn try_accessors_fail_instead_of_blocking() {
+        let mut store = SyncMiniStore::<u32>::default();
+        store.set(0, 1i32);
+        store.set(0, true);
+
+        let int = store.get_mut::<i32>(&0).unwrap();
+        assert_eq!(
+            store.try_get::<i32>(&0).unwrap_err(),
+            WouldBlock(type_name::<i32>())
+        );
+        assert!(store.try_get_mut::<bool>(&0).unwrap().is_some());
+        drop(int);
+
+        let int = store.try_get::<i32>(&0).unwrap();
+        assert!(store.try_get::<i32>(&0).unwrap().is_some());
+        assert!(store.try_get_mut::<i32>(&0).is_err());
+        drop(int);
+
+        assert!(store.try_get::<u8>(&0).unwrap().is_none());
+        assert!(store.get::<u8>(&0).is_none());
+    }
+
+    #[async_std::test]
+    async fn async_borrows_wait_for_the_lock() {
+        let mut store = SyncMiniStore::<u32>::default();
+        store.set(0, 1i32);
+        let store = std::sync::Arc::new(store);
+
+        let int = store.get_mut_async::<i32>(&0).await.unwrap();
+        let reader = task::spawn({
+            let store = store.clone();
+            async move { *store.get_async::<i32>(&0).await.unwrap() }
+        });
+        task::sleep(std::time::Duration::from_millis(10)).await;
+
+        let mut int = int;
+        *int = 2;
+        drop(int);
+        assert_eq!(reader.await, 2);
+    }
+
+    #[test]
+    fn queries_borrow_several_types() {
+        let mut store = SyncMiniStore::<u32>::default();
+        store.set(0, "zero".to_string());
+        store.set(0, 0i32);
+
+        if let (
+            SyncField::Immutable(string),
+            SyncField::Mutable(mut int),
+            SyncField::MaybeImmutable(None),
+        ) = MiniStoreQuery::get(
+            &store,
+            (
+                FieldType::<String>::Immutable,
+                FieldType::<i32>::Mutable,
+                FieldType::<bool>::MaybeImmutable,
+            ),
+            &0,
+        ) {
+            *int = string.len() as i32;
+        } else {
+            panic!("Query didn't match");
+        }
+        assert_eq!(*store.get::<i32>(&0).unwrap(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "SyncMiniStore doesn't track ticks")]
+    fn tick_filters_panic() {
+        let mut store = SyncMiniStore::<u32>::default();
+        store.set(0, 0i32);
+
+        MiniStoreQuery::get(&store, (FieldType::<i32>::Added(0),), &0);
+    }
+}
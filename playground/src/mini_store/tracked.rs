@@ -0,0 +1,40 @@
+/// The ticks of a [`MiniStore`](super::MiniStore) at which a value was added and last changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ticks {
+    pub added: u64,
+    pub changed: u64,
+}
+
+/// A stored value along with its [`Ticks`].
+///
+/// [`Storage`](super::Storage) backends hold these rather than bare values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tracked<T> {
+    pub(super) value: T,
+    pub(super) ticks: Ticks,
+}
+
+impl<T> Tracked<T> {
+    /// A value added at `tick`
+    pub(super) fn new(value: T, tick: u64) -> Self {
+        Tracked {
+            value,
+            ticks: Ticks {
+                added: tick,
+                changed: tick,
+            },
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn ticks(&self) -> Ticks {
+        self.ticks
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
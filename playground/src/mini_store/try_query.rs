@@ -0,0 +1,207 @@
+use std::hash::Hash;
+
+use super::{MiniStore, MiniStoreQuery, QueryError, QueryFilter};
+
+impl<K> MiniStore<K>
+where
+    K: Ord + Hash + Clone + 'static,
+{
+    /// Queries the fields of `key` like [`MiniStoreQuery::get`], but checks the whole signature
+    /// before borrowing anything, returning an error instead of panicking.
+    ///
+    /// Fails if a type would be borrowed mutably alongside another borrow of it,
+    /// if an `Immutable`, `Mutable` or `Some` field is missing, or if a `None` field is present.
+    pub fn try_query<'a, S>(
+        &'a self,
+        signature: S,
+        key: &K,
+    ) -> Result<<Self as MiniStoreQuery<'a, K, S>>::GetOutput, QueryError>
+    where
+        S: QueryFilter<K>,
+        Self: MiniStoreQuery<'a, K, S>,
+    {
+        signature.validate(self, key, &mut vec![])?;
+        Ok(MiniStoreQuery::get(self, signature, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::type_name;
+
+    use super::*;
+    use crate::{
+        cons::{cons, HNil},
+        hlist,
+        mini_store::{Field, FieldType},
+    };
+
+    fn store() -> MiniStore<i32> {
+        let mut store = MiniStore::default();
+        store.set(0, 0);
+        store.set(0, "zero".to_string());
+        store.set(1, 1);
+        store
+    }
+
+    #[test]
+    fn valid_queries_borrow_their_fields() {
+        let store = store();
+        let (int, string, flag) = store
+            .try_query(
+                (
+                    FieldType::<i32>::Mutable,
+                    FieldType::<String>::Immutable,
+                    FieldType::<bool>::MaybeImmutable,
+                ),
+                &0,
+            )
+            .unwrap();
+        assert!(matches!(int, Field::Mutable(int) if *int == 0));
+        assert!(matches!(string, Field::Immutable(string) if *string == "zero"));
+        assert!(matches!(flag, Field::MaybeImmutable(None)));
+
+        let shared = (FieldType::<i32>::Immutable, FieldType::<i32>::Immutable);
+        assert!(store.try_query(shared, &1).is_ok());
+    }
+
+    #[test]
+    fn aliasing_is_reported() {
+        let store = store();
+        assert_eq!(
+            store
+                .try_query((FieldType::<i32>::Mutable, FieldType::<i32>::Immutable), &0)
+                .unwrap_err(),
+            QueryError::Aliased(type_name::<i32>())
+        );
+        assert_eq!(
+            store
+                .try_query(
+                    hlist![
+                        FieldType::<String>::MaybeImmutable,
+                        FieldType::<String>::MaybeMutable
+                    ],
+                    &1
+                )
+                .unwrap_err(),
+            QueryError::Aliased(type_name::<String>())
+        );
+
+        let held = store.get::<i32>(&1);
+        assert_eq!(
+            store
+                .try_query((FieldType::<i32>::MaybeMutable,), &0)
+                .unwrap_err(),
+            QueryError::Aliased(type_name::<i32>())
+        );
+        drop(held);
+    }
+
+    #[test]
+    fn missing_fields_are_reported() {
+        let store = store();
+        assert_eq!(
+            store
+                .try_query((FieldType::<u8>::Immutable,), &0)
+                .unwrap_err(),
+            QueryError::MissingStorage(type_name::<u8>())
+        );
+        assert_eq!(
+            store
+                .try_query(
+                    (FieldType::<i32>::Immutable, FieldType::<String>::Mutable),
+                    &1
+                )
+                .unwrap_err(),
+            QueryError::MissingKey(type_name::<String>())
+        );
+        assert!(store
+            .try_query(
+                (
+                    FieldType::<u8>::MaybeMutable,
+                    FieldType::<String>::MaybeImmutable
+                ),
+                &1
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn tick_filters_are_checked() {
+        let mut store = store();
+        let since = store.tick();
+        store.advance_tick();
+        store.set(1, 10);
+        store.remove::<String>(&0);
+
+        assert!(store
+            .try_query((FieldType::<i32>::Changed(since),), &1)
+            .is_ok());
+        assert_eq!(
+            store
+                .try_query((FieldType::<i32>::Added(since),), &1)
+                .unwrap_err(),
+            QueryError::Unchanged(type_name::<i32>())
+        );
+        assert_eq!(
+            store
+                .try_query((FieldType::<i32>::Changed(since),), &0)
+                .unwrap_err(),
+            QueryError::Unchanged(type_name::<i32>())
+        );
+        assert_eq!(
+            store
+                .try_query((FieldType::<String>::Changed(since),), &0)
+                .unwrap_err(),
+            QueryError::MissingKey(type_name::<String>())
+        );
+
+        assert!(store
+            .try_query((FieldType::<String>::Removed(since),), &0)
+            .is_ok());
+        assert_eq!(
+            store
+                .try_query((FieldType::<String>::Removed(since),), &1)
+                .unwrap_err(),
+            QueryError::Unchanged(type_name::<String>())
+        );
+        assert_eq!(
+            store
+                .try_query((FieldType::<i32>::Removed(since),), &0)
+                .unwrap_err(),
+            QueryError::Excluded(type_name::<i32>())
+        );
+
+        let held = store.get_mut::<i32>(&0);
+        assert_eq!(
+            store
+                .try_query((FieldType::<i32>::Added(since),), &1)
+                .unwrap_err(),
+            QueryError::Aliased(type_name::<i32>())
+        );
+        drop(held);
+    }
+
+    #[test]
+    fn presence_filters_are_checked() {
+        let store = store();
+        assert!(store
+            .try_query((FieldType::<String>::Some, FieldType::<i32>::Immutable), &0)
+            .is_ok());
+        assert!(store
+            .try_query((FieldType::<String>::None, FieldType::<u8>::None), &1)
+            .is_ok());
+        assert_eq!(
+            store
+                .try_query((FieldType::<String>::Some,), &1)
+                .unwrap_err(),
+            QueryError::MissingKey(type_name::<String>())
+        );
+        assert_eq!(
+            store
+                .try_query((FieldType::<String>::None,), &0)
+                .unwrap_err(),
+            QueryError::Excluded(type_name::<String>())
+        );
+    }
+}
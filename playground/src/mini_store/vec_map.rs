@@ -0,0 +1,83 @@
+use super::Storage;
+
+/// A map kept as a vector of entries sorted by key, for dense storage and fast in-order iteration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VecMap<K, V>(Vec<(K, V)>);
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        VecMap(vec![])
+    }
+}
+
+impl<K, V> VecMap<K, V>
+where
+    K: Ord,
+{
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn index(&self, key: &K) -> Result<usize, usize> {
+        self.0.binary_search_by(|(k, _)| k.cmp(key))
+    }
+}
+
+impl<K, V> Storage<K, V> for VecMap<K, V>
+where
+    K: Ord,
+{
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.index(&key) {
+            Ok(index) => Some(std::mem::replace(&mut self.0[index].1, value)),
+            Err(index) => {
+                self.0.insert(index, (key, value));
+                None
+            }
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let index = self.index(key).ok()?;
+        Some(&self.0[index].1)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.index(key).ok()?;
+        Some(&mut self.0[index].1)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.index(key).ok()?;
+        Some(self.0.remove(index).1)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = &K> + '_> {
+        Box::new(self.0.iter().map(|(key, _)| key))
+    }
+
+    fn drain(&mut self) -> Vec<(K, V)> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_stay_sorted() {
+        let mut map = VecMap::new();
+        for key in &[3, 1, 2, 1] {
+            map.insert(*key, key * 10);
+        }
+
+        assert_eq!(map.get(&1), Some(&10));
+        assert_eq!(map.get(&4), None);
+        assert_eq!(map.insert(2, 0), Some(20));
+        assert_eq!(map.remove(&4), None);
+        map.insert(4, 40);
+        assert_eq!(map.remove(&4), Some(40));
+        assert_eq!(map.drain(), vec![(1, 10), (2, 0), (3, 30)]);
+    }
+}
@@ -0,0 +1,14 @@
+use std::fmt::{self, Display};
+
+/// Returned by the `try_` accessors of a [`SyncMiniStore`](super::SyncMiniStore) when the
+/// storage of the named type is locked by another borrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WouldBlock(pub &'static str);
+
+impl Display for WouldBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The storage of {} is locked", self.0)
+    }
+}
+
+impl std::error::Error for WouldBlock {}
@@ -0,0 +1,164 @@
+use std::{
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use super::System;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Which system finished, and how
+type Completion = (usize, thread::Result<()>);
+
+/// Worker threads kept alive between ticks, each running the jobs sent to the pool in turn
+pub(super) struct ThreadPool {
+    jobs: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    pub(super) fn new(threads: usize) -> Self {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                thread::spawn(move || loop {
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+        ThreadPool {
+            jobs: Some(jobs),
+            workers,
+        }
+    }
+
+    pub(super) fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    fn execute(&self, job: Job) {
+        self.jobs
+            .as_ref()
+            .unwrap()
+            .send(job)
+            .expect("Worker threads exited");
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Closing the channel lets each worker return once it's idle
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Systems handed to the pool that haven't reported back yet.
+/// Waits for them when dropped, so none outlives its borrows even if the caller unwinds.
+struct InFlight {
+    completions: Receiver<Completion>,
+    count: usize,
+}
+
+impl InFlight {
+    fn next(&mut self) -> Completion {
+        let completion = self.completions.recv().unwrap();
+        self.count -= 1;
+        completion
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        while self.count > 0 && self.completions.recv().is_ok() {
+            self.count -= 1;
+        }
+    }
+}
+
+/// Runs `systems` on `pool`, returning once all have finished.
+///
+/// The system at each position starts once those at the positions in its `waits_for` have
+/// finished. `None` stands for a system skipped this tick, which finishes as soon as it may start
+/// so the systems waiting on it still wait on those it waits for.
+/// If any system panics, the rest still run and the first panic is resumed afterwards.
+pub(super) fn run_parallel<S>(
+    pool: &ThreadPool,
+    mut systems: Vec<Option<&mut dyn System<S>>>,
+    waits_for: &[Vec<usize>],
+    store: &S,
+) where
+    S: Sync,
+{
+    let count = systems.len();
+    let mut started = vec![false; count];
+    let mut finished = vec![false; count];
+    let mut panicked = None;
+
+    let (done, completions) = mpsc::channel();
+    let mut in_flight = InFlight {
+        completions,
+        count: 0,
+    };
+
+    loop {
+        // Skipped systems finish on the spot, which may let later ones start
+        let mut skipped_any = true;
+        while skipped_any {
+            skipped_any = false;
+            for position in 0..count {
+                if started[position]
+                    || !waits_for[position].iter().all(|&earlier| finished[earlier])
+                {
+                    continue;
+                }
+                started[position] = true;
+
+                let system = match systems[position].take() {
+                    Some(system) => system,
+                    None => {
+                        finished[position] = true;
+                        skipped_any = true;
+                        continue;
+                    }
+                };
+                let done = done.clone();
+                let job: Box<dyn FnOnce() + Send + '_> = Box::new(move || {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| system.run(store)));
+                    let _ = done.send((position, result));
+                });
+                // SAFETY: the job only borrows `system` and `store`, which outlive this call,
+                // and `in_flight` waits for it to report back before this call returns or unwinds
+                let job: Job = unsafe { mem::transmute(job) };
+                pool.execute(job);
+                in_flight.count += 1;
+            }
+        }
+
+        if in_flight.count == 0 {
+            break;
+        }
+        let (position, result) = in_flight.next();
+        finished[position] = true;
+        if let Err(payload) = result {
+            panicked.get_or_insert(payload);
+        }
+    }
+
+    if let Some(payload) = panicked {
+        panic::resume_unwind(payload);
+    }
+}
@@ -0,0 +1,259 @@
+use std::{
+    cell::Ref, cell::RefMut, collections::HashMap, hash::Hash, ops::Deref, ops::DerefMut,
+    sync::atomic::AtomicUsize, sync::atomic::Ordering,
+};
+
+mod executor;
+mod run_criteria;
+mod schedule;
+mod schedule_error;
+mod stage;
+pub mod store;
+mod system;
+
+pub use run_criteria::*;
+pub use schedule::*;
+pub use schedule_error::*;
+pub use stage::*;
+pub use system::*;
+
+use store::{
+    Access, ArchetypeStorage, IterArchetypes, IterStoreFields, SparseIndex, SparseSet, Storable,
+    Store,
+};
+
+// Entity
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+struct EntityID(usize);
+
+impl EntityID {
+    pub fn next() -> Self {
+        static ENTITY_ID: AtomicUsize = AtomicUsize::new(0);
+        EntityID(ENTITY_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl SparseIndex for EntityID {
+    fn sparse_index(&self) -> usize {
+        self.0
+    }
+}
+
+// Components
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+struct Position {
+    x: i64,
+    y: i64,
+}
+
+impl Storable for Position {
+    type Storage = ArchetypeStorage<EntityID>;
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+struct Velocity {
+    x: i64,
+    y: i64,
+}
+
+impl Storable for Velocity {
+    type Storage = ArchetypeStorage<EntityID>;
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+struct EntityRef(pub EntityID);
+
+impl Storable for EntityRef {
+    type Storage = HashMap<EntityID, Self>;
+}
+
+impl Deref for EntityRef {
+    type Target = EntityID;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for EntityRef {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+struct EntityTag;
+
+impl Storable for EntityTag {
+    type Storage = SparseSet<EntityID, Self>;
+}
+
+// Systems
+struct SpawnSystem {}
+
+impl System for SpawnSystem {
+    fn run(&mut self, store: &Store) {
+        let entity_a = EntityID::next();
+        let entity_b = EntityID::next();
+        let entity_c = EntityID::next();
+        let entity_d = EntityID::next();
+
+        store.insert(entity_a, Position { x: 0, y: 0 });
+        store.insert(entity_b, Position { x: 5, y: 5 });
+        store.insert(entity_c, Position { x: 10, y: 10 });
+        store.insert(entity_d, Position { x: 15, y: 15 });
+
+        store.insert(entity_a, Velocity { x: 1, y: 1 });
+        store.insert(entity_b, Velocity { x: -1, y: 2 });
+        store.insert(entity_c, Velocity { x: -3, y: -3 });
+        store.insert(entity_d, Velocity { x: -5, y: -5 });
+
+        {
+            let mut entity_ref_storage = store.get_storage::<EntityRef>().borrow_mut();
+            entity_ref_storage.insert(entity_a, EntityRef(entity_d));
+            entity_ref_storage.insert(entity_b, EntityRef(entity_a));
+            entity_ref_storage.insert(entity_c, EntityRef(entity_b));
+            entity_ref_storage.insert(entity_d, EntityRef(entity_c));
+        }
+
+        store.insert(entity_a, EntityTag);
+        store.insert(entity_c, EntityTag);
+    }
+}
+
+struct PositionIntegratorSystem {}
+
+impl System for PositionIntegratorSystem {
+    fn run(&mut self, store: &Store) {
+        IterArchetypes::<EntityID, (Ref<Velocity>, RefMut<Position>)>::iter_archetypes(store)
+            .for_each(|(_, (velocity, mut position))| {
+                position.x += velocity.x;
+                position.y += velocity.y;
+            });
+    }
+
+    fn access(&self) -> Access {
+        Access::of::<(Ref<Velocity>, RefMut<Position>)>()
+    }
+}
+
+struct PrinterSystem {}
+
+impl System for PrinterSystem {
+    fn run(&mut self, store: &Store) {
+        IterStoreFields::<EntityID, (Ref<Position>, Ref<Velocity>)>::iter(store).for_each(
+            |(key, (position, velocity))| {
+                println!(
+                    "ID: {:?}\nPosition: {:#?}\nVelocity: {:#?}",
+                    key, position, velocity
+                );
+            },
+        );
+    }
+
+    fn access(&self) -> Access {
+        Access::of::<(Ref<Position>, Ref<Velocity>)>()
+    }
+}
+
+struct PositionRefSystem {}
+
+impl System for PositionRefSystem {
+    fn run(&mut self, store: &Store) {
+        let (referencing_entities, referenced_entities): (Vec<EntityID>, Vec<EntityID>) =
+            IterStoreFields::<EntityID, (Ref<EntityRef>,)>::iter(store)
+                .map(|(referencer_key, (entity_ref,))| (referencer_key, **entity_ref))
+                .unzip();
+
+        referencing_entities
+            .into_iter()
+            .zip(IterStoreFields::<EntityID, (Ref<Position>,)>::iter_keys(
+                store,
+                referenced_entities,
+            ))
+            .for_each(|(referencing_entity, (referenced_key, (position,)))| {
+                println!(
+                    "Entity {:?} referenced entity {:?} with position {:?} ",
+                    referencing_entity, referenced_key, position
+                );
+            });
+    }
+
+    fn access(&self) -> Access {
+        Access::of::<(Ref<EntityRef>, Ref<Position>)>()
+    }
+}
+
+// Main Loop
+pub fn main() {
+    let mut store = Store::default();
+    store.add_storage_for::<Position>();
+    store.add_storage_for::<Velocity>();
+    store.add_storage_for::<EntityRef>();
+    store.add_storage_for::<EntityTag>();
+
+    let mut schedule = Schedule::default();
+    schedule.add_system(Stage::Startup, SpawnSystem {});
+    schedule
+        .add_system(Stage::Update, PositionIntegratorSystem {})
+        .label("integrate");
+    schedule
+        .add_system(Stage::Update, PrinterSystem {})
+        .after("integrate")
+        .run_criteria(RunCriteria::EveryNTicks(2));
+    schedule.add_system(Stage::PostUpdate, PositionRefSystem {});
+
+    for _i in 0..4 {
+        schedule.tick(&store).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn systems_run_against_the_store() {
+        let mut store = Store::default();
+        store.add_storage_for::<Position>();
+        store.add_storage_for::<Velocity>();
+        store.add_storage_for::<EntityRef>();
+
+        let (moving, still) = (EntityID::next(), EntityID::next());
+        store.insert(moving, Position { x: 0, y: 0 });
+        store.insert(still, Position { x: 5, y: 5 });
+        store.insert(moving, Velocity { x: 1, y: -2 });
+        store.insert(still, EntityRef(moving));
+
+        for _ in 0..3 {
+            PositionIntegratorSystem {}.run(&store);
+            PositionRefSystem {}.run(&store);
+        }
+
+        let archetypes = store.archetypes::<EntityID>().unwrap();
+        assert_eq!(
+            archetypes.get::<Position>(&moving).as_deref(),
+            Some(&Position { x: 3, y: -6 })
+        );
+        assert_eq!(
+            archetypes.get::<Position>(&still).as_deref(),
+            Some(&Position { x: 5, y: 5 })
+        );
+
+        // Losing its velocity moves the entity out of the integrator's archetype
+        assert_eq!(
+            store.remove::<Velocity>(&moving),
+            Some(Velocity { x: 1, y: -2 })
+        );
+        PositionIntegratorSystem {}.run(&store);
+        assert_eq!(
+            archetypes.get::<Position>(&moving).as_deref(),
+            Some(&Position { x: 3, y: -6 })
+        );
+    }
+
+    #[test]
+    fn demo_runs() {
+        main();
+    }
+}
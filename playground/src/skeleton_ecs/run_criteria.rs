@@ -0,0 +1,32 @@
+use std::fmt::{self, Debug};
+
+/// Decides on which ticks of a [`Schedule`](super::Schedule) a system runs
+#[derive(Default)]
+pub enum RunCriteria {
+    #[default]
+    Always,
+    /// Runs on the first tick and every `n`th tick after it
+    EveryNTicks(u64),
+    /// Runs when the function returns true for the current tick
+    Custom(Box<dyn FnMut(u64) -> bool + Send>),
+}
+
+impl RunCriteria {
+    pub fn should_run(&mut self, tick: u64) -> bool {
+        match self {
+            RunCriteria::Always => true,
+            RunCriteria::EveryNTicks(n) => tick.is_multiple_of(*n),
+            RunCriteria::Custom(criteria) => criteria(tick),
+        }
+    }
+}
+
+impl Debug for RunCriteria {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunCriteria::Always => write!(f, "Always"),
+            RunCriteria::EveryNTicks(n) => write!(f, "EveryNTicks({})", n),
+            RunCriteria::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
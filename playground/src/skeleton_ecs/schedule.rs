@@ -0,0 +1,505 @@
+use std::collections::BTreeMap;
+
+use super::{
+    executor::{self, ThreadPool},
+    store::{Access, Store},
+    RunCriteria, ScheduleError, Stage, System,
+};
+
+struct ScheduledSystem<S> {
+    system: Box<dyn System<S>>,
+    access: Access,
+    label: Option<&'static str>,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+    run_criteria: RunCriteria,
+}
+
+impl<S> ScheduledSystem<S> {
+    /// Whether this system must run before `other`
+    fn precedes(&self, other: &ScheduledSystem<S>) -> bool {
+        let labelled = |system: &ScheduledSystem<S>, labels: &[&'static str]| {
+            system.label.is_some_and(|label| labels.contains(&label))
+        };
+        labelled(other, &self.before) || labelled(self, &other.after)
+    }
+}
+
+/// Configures a system added to a [`Schedule`]
+pub struct SystemConfig<'a, S>(&'a mut ScheduledSystem<S>);
+
+impl<'a, S> SystemConfig<'a, S> {
+    /// Names the system so others in its stage can be ordered against it.
+    /// Several systems may share a label.
+    pub fn label(self, label: &'static str) -> Self {
+        self.0.label = Some(label);
+        self
+    }
+
+    /// Runs the system before every system labelled `label`
+    pub fn before(self, label: &'static str) -> Self {
+        self.0.before.push(label);
+        self
+    }
+
+    /// Runs the system after every system labelled `label`
+    pub fn after(self, label: &'static str) -> Self {
+        self.0.after.push(label);
+        self
+    }
+
+    pub fn run_criteria(self, run_criteria: RunCriteria) -> Self {
+        self.0.run_criteria = run_criteria;
+        self
+    }
+}
+
+/// Systems grouped in [`Stage`]s, run in order each time the schedule ticks.
+///
+/// Within a stage, systems run in the order they were added unless `before` and `after`
+/// constraints say otherwise. [`tick_parallel`](Schedule::tick_parallel) also runs them
+/// alongside each other when neither must follow the other and their [`Access`] doesn't conflict.
+pub struct Schedule<S = Store> {
+    stages: BTreeMap<Stage, Vec<ScheduledSystem<S>>>,
+    /// Indices of each stage's systems in run order, or `None` if they changed since sorting
+    order: Option<BTreeMap<Stage, Vec<usize>>>,
+    /// Workers for `tick_parallel`, started on its first call
+    pool: Option<ThreadPool>,
+    tick: u64,
+}
+
+impl<S> Default for Schedule<S> {
+    fn default() -> Self {
+        Schedule {
+            stages: Default::default(),
+            order: None,
+            pool: None,
+            tick: 0,
+        }
+    }
+}
+
+impl<S> Schedule<S> {
+    pub fn add_system<Sys>(&mut self, stage: Stage, system: Sys) -> SystemConfig<'_, S>
+    where
+        Sys: System<S> + 'static,
+    {
+        self.order = None;
+        let systems = self.stages.entry(stage).or_default();
+        systems.push(ScheduledSystem {
+            access: system.access(),
+            system: Box::new(system),
+            label: None,
+            before: vec![],
+            after: vec![],
+            run_criteria: RunCriteria::Always,
+        });
+        SystemConfig(systems.last_mut().unwrap())
+    }
+
+    /// The number of times the schedule has ticked
+    pub fn tick_count(&self) -> u64 {
+        self.tick
+    }
+
+    /// Sorts the systems of `stage` so each runs after those it must follow,
+    /// keeping the order they were added in otherwise
+    fn sort(stage: Stage, systems: &[ScheduledSystem<S>]) -> Result<Vec<usize>, ScheduleError> {
+        for system in systems {
+            let labels = system.before.iter().chain(&system.after);
+            for &label in labels {
+                if !systems.iter().any(|other| other.label == Some(label)) {
+                    return Err(ScheduleError::UnknownLabel {
+                        stage,
+                        system: system.system.name(),
+                        label,
+                    });
+                }
+            }
+        }
+
+        let mut remaining = (0..systems.len()).collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(systems.len());
+        while !remaining.is_empty() {
+            let ready = remaining.iter().position(|&index| {
+                !remaining
+                    .iter()
+                    .any(|&other| other != index && systems[other].precedes(&systems[index]))
+            });
+            match ready {
+                Some(position) => order.push(remaining.remove(position)),
+                None => {
+                    return Err(ScheduleError::Cycle {
+                        stage,
+                        systems: remaining
+                            .iter()
+                            .map(|&index| systems[index].system.name())
+                            .collect(),
+                    })
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// Checks that the systems of every stage can be ordered
+    pub fn validate(&mut self) -> Result<(), ScheduleError> {
+        if self.order.is_none() {
+            let order = self
+                .stages
+                .iter()
+                .map(|(&stage, systems)| Ok((stage, Self::sort(stage, systems)?)))
+                .collect::<Result<_, _>>()?;
+            self.order = Some(order);
+        }
+        Ok(())
+    }
+
+    /// Runs every stage once, skipping `Startup` after the first tick,
+    /// and every system whose run criteria allow it
+    pub fn tick(&mut self, store: &S) -> Result<(), ScheduleError> {
+        self.validate()?;
+        let order = self.order.as_ref().unwrap();
+
+        for (stage, systems) in &mut self.stages {
+            if *stage == Stage::Startup && self.tick > 0 {
+                continue;
+            }
+            for &index in &order[stage] {
+                let scheduled = &mut systems[index];
+                if scheduled.run_criteria.should_run(self.tick) {
+                    scheduled.system.run(store);
+                }
+            }
+        }
+
+        self.tick += 1;
+        Ok(())
+    }
+
+    /// Like [`tick`](Schedule::tick), but runs each stage on a pool of `threads` threads.
+    /// The schedule keeps its pool between ticks, and only restarts it if `threads` changes.
+    ///
+    /// A system starts once every system before it in run order that it must follow,
+    /// or whose access conflicts with its own, has finished. Stages still run one after another.
+    pub fn tick_parallel(&mut self, store: &S, threads: usize) -> Result<(), ScheduleError>
+    where
+        S: Sync,
+    {
+        self.validate()?;
+        let order = self.order.as_ref().unwrap();
+        if self
+            .pool
+            .as_ref()
+            .is_none_or(|pool| pool.threads() != threads.max(1))
+        {
+            self.pool = Some(ThreadPool::new(threads));
+        }
+        let pool = self.pool.as_ref().unwrap();
+
+        for (stage, systems) in &mut self.stages {
+            if *stage == Stage::Startup && self.tick > 0 {
+                continue;
+            }
+            let order = &order[stage];
+            let waits_for = order
+                .iter()
+                .enumerate()
+                .map(|(position, &index)| {
+                    let system = &systems[index];
+                    (0..position)
+                        .filter(|&earlier| {
+                            let earlier = &systems[order[earlier]];
+                            earlier.precedes(system)
+                                || earlier.access.conflicts_with(&system.access)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let tick = self.tick;
+            let mut to_run = systems
+                .iter_mut()
+                .map(|scheduled| {
+                    let system: &mut dyn System<S> = &mut *scheduled.system;
+                    scheduled.run_criteria.should_run(tick).then_some(system)
+                })
+                .collect::<Vec<_>>();
+            let to_run = order.iter().map(|&index| to_run[index].take()).collect();
+            executor::run_parallel(pool, to_run, &waits_for, store);
+        }
+
+        self.tick += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::skeleton_ecs::store::{Storable, SyncStore};
+
+    struct Record(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+    impl<S> System<S> for Record {
+        fn run(&mut self, _: &S) {
+            self.1.lock().unwrap().push(self.0);
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    fn schedule<S>() -> (Schedule<S>, Arc<Mutex<Vec<&'static str>>>) {
+        (Schedule::default(), Arc::new(Mutex::new(vec![])))
+    }
+
+    struct Position;
+
+    impl Storable for Position {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    /// How many readers and writers of `Position` are running, and whether they ever overlapped
+    #[derive(Default)]
+    struct Running {
+        readers: AtomicUsize,
+        writers: AtomicUsize,
+        most_readers: AtomicUsize,
+        overlapped: AtomicBool,
+    }
+
+    struct Probe {
+        writes: bool,
+        running: Arc<Running>,
+    }
+
+    impl System<SyncStore> for Probe {
+        fn run(&mut self, _: &SyncStore) {
+            let running = &self.running;
+            let (own, other) = match self.writes {
+                true => (&running.writers, &running.readers),
+                false => (&running.readers, &running.writers),
+            };
+            let count = own.fetch_add(1, Ordering::SeqCst) + 1;
+            if other.load(Ordering::SeqCst) > 0 || (self.writes && count > 1) {
+                running.overlapped.store(true, Ordering::SeqCst);
+            }
+            if !self.writes {
+                running.most_readers.fetch_max(count, Ordering::SeqCst);
+            }
+            thread::sleep(Duration::from_millis(10));
+            own.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        fn access(&self) -> Access {
+            match self.writes {
+                true => Access::none().write::<Position>(),
+                false => Access::none().read::<Position>(),
+            }
+        }
+    }
+
+    #[test]
+    fn stages_run_in_order() {
+        let (mut schedule, log) = schedule();
+        schedule.add_system(Stage::PostUpdate, Record("post", log.clone()));
+        schedule.add_system(Stage::Update, Record("update", log.clone()));
+        schedule.add_system(Stage::Startup, Record("startup", log.clone()));
+
+        let store = Store::default();
+        schedule.tick(&store).unwrap();
+        schedule.tick(&store).unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["startup", "update", "post", "update", "post"]
+        );
+        assert_eq!(schedule.tick_count(), 2);
+    }
+
+    #[test]
+    fn constraints_order_systems_within_a_stage() {
+        let (mut schedule, log) = schedule();
+        schedule
+            .add_system(Stage::Update, Record("c", log.clone()))
+            .label("c")
+            .after("b");
+        schedule
+            .add_system(Stage::Update, Record("a", log.clone()))
+            .label("a");
+        schedule
+            .add_system(Stage::Update, Record("b", log.clone()))
+            .label("b")
+            .after("a");
+        schedule
+            .add_system(Stage::Update, Record("first", log.clone()))
+            .before("a");
+
+        schedule.tick(&Store::default()).unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["first", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn conflicting_systems_never_overlap() {
+        let mut schedule = Schedule::default();
+        let running = Arc::new(Running::default());
+        for &writes in &[false, false, true, false, true, true, false, false, false] {
+            let running = running.clone();
+            schedule.add_system(Stage::Update, Probe { writes, running });
+        }
+
+        let store = SyncStore::default();
+        for _ in 0..3 {
+            schedule.tick_parallel(&store, 4).unwrap();
+        }
+        assert!(!running.overlapped.load(Ordering::SeqCst));
+        assert!(running.most_readers.load(Ordering::SeqCst) > 1);
+    }
+
+    #[test]
+    fn parallel_ticks_keep_constraints_and_criteria() {
+        let (mut schedule, log) = schedule::<SyncStore>();
+        schedule
+            .add_system(Stage::Update, Record("b", log.clone()))
+            .label("b")
+            .after("a");
+        schedule
+            .add_system(Stage::Update, Record("skipped", log.clone()))
+            .label("a")
+            .run_criteria(RunCriteria::Custom(Box::new(|_| false)));
+        schedule
+            .add_system(Stage::Update, Record("a", log.clone()))
+            .label("a");
+        schedule.add_system(Stage::Startup, Record("startup", log.clone()));
+
+        let store = SyncStore::default();
+        schedule.tick_parallel(&store, 3).unwrap();
+        schedule.tick_parallel(&store, 3).unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["startup", "a", "b", "a", "b"]);
+    }
+
+    struct RecordThread(Arc<Mutex<HashSet<thread::ThreadId>>>);
+
+    impl<S> System<S> for RecordThread {
+        fn run(&mut self, _: &S) {
+            self.0.lock().unwrap().insert(thread::current().id());
+        }
+
+        fn access(&self) -> Access {
+            Access::none()
+        }
+    }
+
+    #[test]
+    fn parallel_ticks_reuse_their_threads() {
+        let mut schedule = Schedule::<SyncStore>::default();
+        let threads = Arc::new(Mutex::new(HashSet::new()));
+        for _ in 0..4 {
+            schedule.add_system(Stage::Update, RecordThread(threads.clone()));
+        }
+
+        let store = SyncStore::default();
+        for _ in 0..5 {
+            schedule.tick_parallel(&store, 2).unwrap();
+        }
+        let used = threads.lock().unwrap();
+        assert!(used.len() <= 2);
+        assert!(!used.contains(&thread::current().id()));
+    }
+
+    struct Panic;
+
+    impl<S> System<S> for Panic {
+        fn run(&mut self, _: &S) {
+            panic!("System failed");
+        }
+    }
+
+    #[test]
+    fn panics_reach_the_ticking_thread() {
+        let (mut schedule, log) = schedule::<SyncStore>();
+        schedule.add_system(Stage::Update, Panic).label("panic");
+        schedule
+            .add_system(Stage::Update, Record("after", log.clone()))
+            .after("panic");
+
+        let store = SyncStore::default();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            schedule.tick_parallel(&store, 2).unwrap();
+        }));
+        assert!(result.is_err());
+        assert_eq!(*log.lock().unwrap(), vec!["after"]);
+
+        // The pool survives to run the next tick
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            schedule.tick_parallel(&store, 2).unwrap();
+        }));
+        assert!(result.is_err());
+        assert_eq!(log.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn run_criteria_skip_ticks() {
+        let (mut schedule, log) = schedule();
+        schedule
+            .add_system(Stage::Update, Record("every 3", log.clone()))
+            .run_criteria(RunCriteria::EveryNTicks(3));
+        schedule
+            .add_system(Stage::Update, Record("odd", log.clone()))
+            .run_criteria(RunCriteria::Custom(Box::new(|tick| tick % 2 == 1)));
+
+        let store = Store::default();
+        for _ in 0..5 {
+            schedule.tick(&store).unwrap();
+        }
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["every 3", "odd", "every 3", "odd"]
+        );
+    }
+
+    #[test]
+    fn invalid_constraints_are_errors() {
+        let (mut schedule, log) = schedule();
+        schedule
+            .add_system(Stage::Update, Record("a", log.clone()))
+            .label("a")
+            .after("b");
+        schedule
+            .add_system(Stage::PostUpdate, Record("b", log.clone()))
+            .label("b");
+        assert_eq!(
+            schedule.tick(&Store::default()),
+            Err(ScheduleError::UnknownLabel {
+                stage: Stage::Update,
+                system: "a",
+                label: "b"
+            })
+        );
+
+        schedule
+            .add_system(Stage::Update, Record("b", log.clone()))
+            .label("b")
+            .after("a");
+        assert_eq!(
+            schedule.validate(),
+            Err(ScheduleError::Cycle {
+                stage: Stage::Update,
+                systems: vec!["a", "b"]
+            })
+        );
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt::{self, Display};
+
+use super::Stage;
+
+/// Why the systems of a [`Schedule`](super::Schedule) can't be ordered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// A system is ordered against a label no system of its stage has
+    UnknownLabel {
+        stage: Stage,
+        system: &'static str,
+        label: &'static str,
+    },
+    /// The ordering constraints of these systems form a cycle
+    Cycle {
+        stage: Stage,
+        systems: Vec<&'static str>,
+    },
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::UnknownLabel {
+                stage,
+                system,
+                label,
+            } => write!(
+                f,
+                "{} is ordered against unknown label {:?} in the {} stage",
+                system,
+                label,
+                stage.name()
+            ),
+            ScheduleError::Cycle { stage, systems } => write!(
+                f,
+                "Ordering cycle between {} in the {} stage",
+                systems.join(", "),
+                stage.name()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
@@ -0,0 +1,21 @@
+/// A phase of a [`Schedule`](super::Schedule) tick. Stages run in declaration order,
+/// and every system of a stage finishes before the next stage starts.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Stage {
+    /// Runs on the first tick only
+    Startup,
+    Update,
+    PostUpdate,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 3] = [Stage::Startup, Stage::Update, Stage::PostUpdate];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Startup => "startup",
+            Stage::Update => "update",
+            Stage::PostUpdate => "post-update",
+        }
+    }
+}
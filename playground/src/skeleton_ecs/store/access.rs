@@ -0,0 +1,197 @@
+use std::{
+    any::TypeId,
+    cell::{Ref, RefMut},
+};
+
+use super::{Read, Storable, Write};
+
+/// The component storages a system reads and writes, so systems that don't conflict can run
+/// at the same time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    reads: Vec<TypeId>,
+    writes: Vec<TypeId>,
+    exclusive: bool,
+}
+
+impl Access {
+    /// Access to nothing
+    pub fn none() -> Self {
+        Default::default()
+    }
+
+    /// Access that conflicts with every other, for systems that don't declare theirs
+    pub fn exclusive() -> Self {
+        Access {
+            exclusive: true,
+            ..Default::default()
+        }
+    }
+
+    /// The access of iterating `Fields`, such as `(Read<Velocity>, Write<Position>)`
+    pub fn of<Fields>() -> Self
+    where
+        Fields: DeclareAccess,
+    {
+        let mut access = Access::none();
+        Fields::declare(&mut access);
+        access
+    }
+
+    pub fn read<T>(mut self) -> Self
+    where
+        T: Storable,
+    {
+        self.add_read(TypeId::of::<T>());
+        self
+    }
+
+    pub fn write<T>(mut self) -> Self
+    where
+        T: Storable,
+    {
+        self.add_write(TypeId::of::<T>());
+        self
+    }
+
+    /// Adds the access of another query of the same system
+    pub fn with(mut self, other: Access) -> Self {
+        self.exclusive |= other.exclusive;
+        other.reads.into_iter().for_each(|read| self.add_read(read));
+        other
+            .writes
+            .into_iter()
+            .for_each(|write| self.add_write(write));
+        self
+    }
+
+    fn add_read(&mut self, type_id: TypeId) {
+        if !self.reads.contains(&type_id) {
+            self.reads.push(type_id);
+        }
+    }
+
+    fn add_write(&mut self, type_id: TypeId) {
+        if !self.writes.contains(&type_id) {
+            self.writes.push(type_id);
+        }
+    }
+
+    /// Whether a system with this access can't run alongside one with `other`
+    pub fn conflicts_with(&self, other: &Access) -> bool {
+        let writes_any = |access: &Access, type_ids: &[TypeId]| {
+            type_ids
+                .iter()
+                .any(|type_id| access.writes.contains(type_id))
+        };
+        self.exclusive
+            || other.exclusive
+            || writes_any(self, &other.reads)
+            || writes_any(self, &other.writes)
+            || writes_any(other, &self.reads)
+    }
+}
+
+/// A field or tuple of fields whose [`Access`] is known from its type
+pub trait DeclareAccess {
+    fn declare(access: &mut Access);
+}
+
+impl<'a, T> DeclareAccess for Ref<'a, T>
+where
+    T: Storable,
+{
+    fn declare(access: &mut Access) {
+        access.add_read(TypeId::of::<T>());
+    }
+}
+
+impl<'a, T> DeclareAccess for RefMut<'a, T>
+where
+    T: Storable,
+{
+    fn declare(access: &mut Access) {
+        access.add_write(TypeId::of::<T>());
+    }
+}
+
+impl<'a, T> DeclareAccess for Read<'a, T>
+where
+    T: Storable,
+{
+    fn declare(access: &mut Access) {
+        access.add_read(TypeId::of::<T>());
+    }
+}
+
+impl<'a, T> DeclareAccess for Write<'a, T>
+where
+    T: Storable,
+{
+    fn declare(access: &mut Access) {
+        access.add_write(TypeId::of::<T>());
+    }
+}
+
+macro_rules! impl_declare_access {
+    ($($t:ident),+) => {
+        impl<$($t),+> DeclareAccess for ($($t,)+)
+        where
+            $($t: DeclareAccess,)+
+        {
+            fn declare(access: &mut Access) {
+                $($t::declare(access);)+
+            }
+        }
+    };
+}
+
+/// Implements [`DeclareAccess`] for tuples of every length up to the number of idents given
+macro_rules! impl_declare_access_tuples {
+    ($a:ident $(, $bs:ident)*) => {
+        impl_declare_access!($a $(, $bs)*);
+        impl_declare_access_tuples!($($bs),*);
+    };
+    () => {};
+}
+
+impl_declare_access_tuples!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    struct Position;
+    struct Velocity;
+
+    impl Storable for Position {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    impl Storable for Velocity {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    #[test]
+    fn only_writes_conflict() {
+        let integrate = Access::of::<(Read<Velocity>, Write<Position>)>();
+        let print = Access::of::<(Ref<Position>, Ref<Velocity>)>();
+        let read_velocity = Access::none().read::<Velocity>();
+
+        assert!(integrate.conflicts_with(&print));
+        assert!(print.conflicts_with(&integrate));
+        assert!(!integrate.conflicts_with(&read_velocity));
+        assert!(!print.conflicts_with(&read_velocity));
+        assert!(read_velocity.conflicts_with(&Access::none().write::<Velocity>()));
+        assert!(Access::exclusive().conflicts_with(&Access::none()));
+        assert!(!Access::none().conflicts_with(&Access::none()));
+        assert_eq!(
+            Access::none()
+                .read::<Velocity>()
+                .with(Access::none().write::<Position>()),
+            Access::of::<(Ref<Velocity>, RefMut<Position>)>()
+        );
+    }
+}
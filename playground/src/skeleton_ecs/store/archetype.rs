@@ -0,0 +1,86 @@
+use std::{
+    any::TypeId,
+    cell::{Ref, RefCell},
+    collections::HashMap,
+};
+
+use downcast_rs::{impl_downcast, Downcast};
+
+/// A column of components of one type, type-erased so an [`Archetype`] can move an entity's
+/// components without knowing their types
+pub(super) trait Column: Downcast {
+    /// An empty column for the same type
+    fn empty(&self) -> Box<dyn Column>;
+
+    /// Swap-removes the component at `row` and pushes it onto `to`, a column of the same type
+    fn move_row(&self, row: usize, to: &dyn Column);
+}
+impl_downcast!(Column);
+
+impl<T> Column for RefCell<Vec<T>>
+where
+    T: 'static,
+{
+    fn empty(&self) -> Box<dyn Column> {
+        Box::new(RefCell::new(Vec::<T>::new()))
+    }
+
+    fn move_row(&self, row: usize, to: &dyn Column) {
+        let value = self.borrow_mut().swap_remove(row);
+        to.downcast_ref::<RefCell<Vec<T>>>()
+            .unwrap()
+            .borrow_mut()
+            .push(value);
+    }
+}
+
+/// The entities with exactly the same component types, whose components are kept in one dense
+/// column per type, all in the same row order
+pub struct Archetype<K> {
+    /// Sorted, so archetypes can be compared by their types
+    pub(super) types: Vec<TypeId>,
+    pub(super) keys: RefCell<Vec<K>>,
+    pub(super) columns: HashMap<TypeId, Box<dyn Column>>,
+}
+
+impl<K> Archetype<K> {
+    pub(super) fn new(types: Vec<TypeId>, columns: HashMap<TypeId, Box<dyn Column>>) -> Self {
+        Archetype {
+            types,
+            keys: Default::default(),
+            columns,
+        }
+    }
+
+    /// The component types of the archetype's entities, sorted
+    pub fn types(&self) -> &[TypeId] {
+        &self.types
+    }
+
+    pub fn has(&self, type_id: TypeId) -> bool {
+        self.columns.contains_key(&type_id)
+    }
+
+    /// The keys of the archetype's entities, in row order
+    pub fn keys(&self) -> Ref<'_, [K]> {
+        Ref::map(self.keys.borrow(), Vec::as_slice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The column of the components of `T`, or `None` if the archetype doesn't have `T`
+    pub fn column<T>(&self) -> Option<&RefCell<Vec<T>>>
+    where
+        T: 'static,
+    {
+        self.columns
+            .get(&TypeId::of::<T>())
+            .map(|column| column.downcast_ref::<RefCell<Vec<T>>>().unwrap())
+    }
+}
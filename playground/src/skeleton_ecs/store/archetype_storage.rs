@@ -0,0 +1,65 @@
+use std::{
+    any::type_name,
+    cell::{Ref, RefMut},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use super::{ComponentLayout, Storable, Store};
+
+/// A [`Storable::Storage`] that keeps components in the [`Archetypes`](super::Archetypes) of
+/// the store, so entities with the same components share dense columns that
+/// [`IterArchetypes`](super::IterArchetypes) walks without looking keys up.
+///
+/// Components are added and removed through [`Store::insert`] and [`Store::remove`].
+/// [`SyncStore`](super::SyncStore) doesn't support it.
+pub struct ArchetypeStorage<K>(PhantomData<K>);
+
+impl<K> Default for ArchetypeStorage<K> {
+    fn default() -> Self {
+        ArchetypeStorage(PhantomData)
+    }
+}
+
+impl<K, T> ComponentLayout<T> for ArchetypeStorage<K>
+where
+    K: Eq + Hash + Clone + 'static,
+    T: Storable<Storage = Self>,
+{
+    type Key = K;
+
+    fn prepare(store: &mut Store) {
+        store.add_archetypes::<K>();
+    }
+
+    fn keys(store: &Store) -> Vec<K> {
+        store
+            .archetypes::<K>()
+            .map_or_else(Vec::new, |archetypes| archetypes.keys::<T>())
+    }
+
+    fn contains(store: &Store, key: &K) -> bool {
+        store
+            .archetypes::<K>()
+            .is_some_and(|archetypes| archetypes.contains::<T>(key))
+    }
+
+    fn fetch<'a>(store: &'a Store, key: &K) -> Option<Ref<'a, T>> {
+        store.archetypes::<K>()?.get(key)
+    }
+
+    fn fetch_mut<'a>(store: &'a Store, key: &K) -> Option<RefMut<'a, T>> {
+        store.archetypes::<K>()?.get_mut(key)
+    }
+
+    fn insert(store: &Store, key: K, value: T) -> Option<T> {
+        match store.archetypes::<K>() {
+            Some(archetypes) if store.has_storage_for::<T>() => archetypes.insert(key, value),
+            _ => panic!("No storage for {}", type_name::<T>()),
+        }
+    }
+
+    fn remove(store: &Store, key: &K) -> Option<T> {
+        store.archetypes::<K>()?.remove(key)
+    }
+}
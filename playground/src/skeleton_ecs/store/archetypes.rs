@@ -0,0 +1,357 @@
+use std::{
+    any::TypeId,
+    cell::{Ref, RefCell, RefMut},
+    collections::HashMap,
+    hash::Hash,
+    mem,
+    ptr::NonNull,
+};
+
+use super::{Archetype, Column};
+
+/// An append-only list whose items never move or drop until the list does, so references to
+/// them stay valid as more are pushed.
+///
+/// Check changes to it with `cargo +nightly miri test archetype`, which runs every test of
+/// archetype storage under Miri.
+struct Arena<T> {
+    /// Raw pointers rather than `Box`es, so moving them as the list grows doesn't assert
+    /// uniqueness over items already lent out
+    items: RefCell<Vec<NonNull<T>>>,
+}
+
+// The arena owns its items like a `Vec<Box<T>>` would
+unsafe impl<T> Send for Arena<T> where T: Send {}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena {
+            items: Default::default(),
+        }
+    }
+}
+
+impl<T> Arena<T> {
+    fn get(&self, index: usize) -> &T {
+        let item = self.items.borrow()[index];
+        // SAFETY: the item was leaked by `push` and is only freed when the arena drops,
+        // and nothing borrows it mutably
+        unsafe { item.as_ref() }
+    }
+
+    fn len(&self) -> usize {
+        self.items.borrow().len()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len()).map(move |index| self.get(index))
+    }
+
+    /// Adds `item`, returning its index
+    fn push(&self, item: T) -> usize {
+        let mut items = self.items.borrow_mut();
+        items.push(NonNull::from(Box::leak(Box::new(item))));
+        items.len() - 1
+    }
+}
+
+impl<T> Drop for Arena<T> {
+    fn drop(&mut self) {
+        for item in self.items.get_mut().drain(..) {
+            // SAFETY: each item was leaked by `push`, and no reference to it outlives the arena
+            drop(unsafe { Box::from_raw(item.as_ptr()) });
+        }
+    }
+}
+
+/// The [`Archetype`]s of the entities with components in an
+/// [`ArchetypeStorage`](super::ArchetypeStorage), which every type picking it with the same
+/// key shares.
+///
+/// Adding or removing a component moves the entity's other components to the archetype for its
+/// new set of types. Columns are borrowed like the storages of a [`Store`](super::Store), so
+/// moving an entity while a component of its archetypes is borrowed panics.
+pub struct Archetypes<K> {
+    /// Never removed, so each stays where it is as others are added
+    archetypes: Arena<Archetype<K>>,
+    /// The archetype and row of each key
+    locations: RefCell<HashMap<K, (usize, usize)>>,
+}
+
+impl<K> Default for Archetypes<K> {
+    fn default() -> Self {
+        Archetypes {
+            archetypes: Default::default(),
+            locations: Default::default(),
+        }
+    }
+}
+
+impl<K> Archetypes<K>
+where
+    K: Eq + Hash + Clone,
+{
+    fn archetype(&self, index: usize) -> &Archetype<K> {
+        self.archetypes.get(index)
+    }
+
+    fn location(&self, key: &K) -> Option<(usize, usize)> {
+        self.locations.borrow().get(key).copied()
+    }
+
+    /// The archetypes with every type of `types`, in the order they were created
+    pub fn matching(&self, types: &[TypeId]) -> Vec<&Archetype<K>> {
+        self.archetypes
+            .iter()
+            .filter(|archetype| types.iter().all(|&type_id| archetype.has(type_id)))
+            .collect()
+    }
+
+    /// The component types of `key`, sorted, or `None` if it has no components here
+    pub fn types_of(&self, key: &K) -> Option<&[TypeId]> {
+        let (archetype, _) = self.location(key)?;
+        Some(self.archetype(archetype).types())
+    }
+
+    /// The keys with a `T`, archetype by archetype
+    pub fn keys<T>(&self) -> Vec<K>
+    where
+        T: 'static,
+    {
+        self.matching(&[TypeId::of::<T>()])
+            .into_iter()
+            .flat_map(|archetype| archetype.keys().to_vec())
+            .collect()
+    }
+
+    pub fn contains<T>(&self, key: &K) -> bool
+    where
+        T: 'static,
+    {
+        self.location(key)
+            .is_some_and(|(archetype, _)| self.archetype(archetype).has(TypeId::of::<T>()))
+    }
+
+    /// Borrows the component under `key`, holding its column until dropped
+    pub fn get<T>(&self, key: &K) -> Option<Ref<'_, T>>
+    where
+        T: 'static,
+    {
+        let (archetype, row) = self.location(key)?;
+        let column = self.archetype(archetype).column::<T>()?;
+        Some(Ref::map(column.borrow(), |column| &column[row]))
+    }
+
+    pub fn get_mut<T>(&self, key: &K) -> Option<RefMut<'_, T>>
+    where
+        T: 'static,
+    {
+        let (archetype, row) = self.location(key)?;
+        let column = self.archetype(archetype).column::<T>()?;
+        Some(RefMut::map(column.borrow_mut(), |column| &mut column[row]))
+    }
+
+    /// Adds `value` to the components of `key`, moving them to the archetype that includes `T`
+    /// unless `key` already had one, which is replaced and returned
+    pub fn insert<T>(&self, key: K, value: T) -> Option<T>
+    where
+        T: 'static,
+    {
+        let location = self.location(&key);
+        let source = location.map(|(archetype, _)| self.archetype(archetype));
+        if let Some((source, (_, row))) = source.zip(location) {
+            if let Some(column) = source.column::<T>() {
+                return Some(mem::replace(&mut column.borrow_mut()[row], value));
+            }
+        }
+
+        let mut types = source.map_or_else(Vec::new, |source| source.types.clone());
+        types.push(TypeId::of::<T>());
+        types.sort_unstable();
+        let target = self.archetype_with(types, || {
+            let mut columns = source.map_or_else(HashMap::new, Self::empty_columns);
+            columns.insert(TypeId::of::<T>(), Box::new(RefCell::new(Vec::<T>::new())));
+            columns
+        });
+
+        self.relocate(key, location, Some(target));
+        let column = self.archetype(target).column::<T>().unwrap();
+        column.borrow_mut().push(value);
+        None
+    }
+
+    /// Removes the `T` of `key`, moving its other components to the archetype without `T`
+    pub fn remove<T>(&self, key: &K) -> Option<T>
+    where
+        T: 'static,
+    {
+        let (archetype, row) = self.location(key)?;
+        let source = self.archetype(archetype);
+        let value = source.column::<T>()?.borrow_mut().swap_remove(row);
+
+        let types = source
+            .types
+            .iter()
+            .copied()
+            .filter(|&type_id| type_id != TypeId::of::<T>())
+            .collect::<Vec<_>>();
+        let target = match types.is_empty() {
+            true => None,
+            false => Some(self.archetype_with(types, || {
+                let mut columns = Self::empty_columns(source);
+                columns.remove(&TypeId::of::<T>());
+                columns
+            })),
+        };
+
+        self.relocate(key.clone(), Some((archetype, row)), target);
+        Some(value)
+    }
+
+    fn empty_columns(archetype: &Archetype<K>) -> HashMap<TypeId, Box<dyn Column>> {
+        archetype
+            .columns
+            .iter()
+            .map(|(&type_id, column)| (type_id, column.empty()))
+            .collect()
+    }
+
+    /// The index of the archetype with exactly `types`, created with `columns` if there's none
+    fn archetype_with<F>(&self, types: Vec<TypeId>, columns: F) -> usize
+    where
+        F: FnOnce() -> HashMap<TypeId, Box<dyn Column>>,
+    {
+        let existing = self
+            .archetypes
+            .iter()
+            .position(|archetype| archetype.types == types);
+        existing.unwrap_or_else(|| self.archetypes.push(Archetype::new(types, columns())))
+    }
+
+    /// Moves the row of `key` from `from` to the end of the archetype `to`, or just removes it
+    /// if `to` is `None`. Components whose type isn't in both must already have been taken out
+    /// of `from`, and added to `to` after.
+    fn relocate(&self, key: K, from: Option<(usize, usize)>, to: Option<usize>) {
+        if let Some((archetype, row)) = from {
+            let source = self.archetype(archetype);
+            if let Some(target) = to.map(|to| self.archetype(to)) {
+                for (type_id, column) in &target.columns {
+                    if let Some(source_column) = source.columns.get(type_id) {
+                        source_column.move_row(row, &**column);
+                    }
+                }
+            }
+
+            let mut keys = source.keys.borrow_mut();
+            keys.swap_remove(row);
+            if let Some(moved) = keys.get(row) {
+                self.locations
+                    .borrow_mut()
+                    .insert(moved.clone(), (archetype, row));
+            }
+        }
+
+        match to {
+            Some(to) => {
+                let mut keys = self.archetype(to).keys.borrow_mut();
+                keys.push(key.clone());
+                self.locations
+                    .borrow_mut()
+                    .insert(key, (to, keys.len() - 1));
+            }
+            None => {
+                self.locations.borrow_mut().remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32);
+
+    #[derive(Debug, PartialEq)]
+    struct Frozen;
+
+    fn types(types: &[TypeId]) -> Vec<TypeId> {
+        let mut types = types.to_vec();
+        types.sort_unstable();
+        types
+    }
+
+    #[test]
+    fn components_move_between_archetypes() {
+        let archetypes = Archetypes::default();
+        for key in 0..4 {
+            archetypes.insert(key, Position(key));
+        }
+        archetypes.insert(1, Velocity(10));
+        archetypes.insert(3, Velocity(30));
+        assert_eq!(archetypes.insert(3, Velocity(31)), Some(Velocity(30)));
+        archetypes.insert(3, Frozen);
+
+        let (position, velocity) = (TypeId::of::<Position>(), TypeId::of::<Velocity>());
+        assert_eq!(archetypes.types_of(&0), Some(&[position][..]));
+        assert_eq!(
+            archetypes.types_of(&1),
+            Some(&types(&[position, velocity])[..])
+        );
+        assert_eq!(archetypes.matching(&[velocity]).len(), 2);
+        assert_eq!(archetypes.keys::<Velocity>(), vec![1, 3]);
+        assert_eq!(archetypes.keys::<Position>(), vec![0, 2, 1, 3]);
+
+        assert_eq!(archetypes.remove::<Position>(&1), Some(Position(1)));
+        assert_eq!(archetypes.remove::<Position>(&1), None);
+        assert_eq!(archetypes.types_of(&1), Some(&[velocity][..]));
+        assert_eq!(archetypes.remove::<Velocity>(&1), Some(Velocity(10)));
+        assert_eq!(archetypes.types_of(&1), None);
+
+        // Rows swapped into the gaps keep their components
+        assert_eq!(archetypes.remove::<Position>(&0), Some(Position(0)));
+        assert_eq!(
+            archetypes.get::<Position>(&2).as_deref(),
+            Some(&Position(2))
+        );
+        *archetypes.get_mut::<Velocity>(&3).unwrap() = Velocity(32);
+        assert_eq!(
+            archetypes.get::<Velocity>(&3).as_deref(),
+            Some(&Velocity(32))
+        );
+        assert_eq!(
+            archetypes.get::<Position>(&3).as_deref(),
+            Some(&Position(3))
+        );
+        assert!(archetypes.contains::<Frozen>(&3));
+        assert!(!archetypes.contains::<Frozen>(&2));
+        assert_eq!(archetypes.keys::<Position>(), vec![2, 3]);
+    }
+
+    struct Tag<const N: usize>;
+
+    #[test]
+    fn archetypes_stay_put_as_more_are_added() {
+        let archetypes = Archetypes::default();
+        archetypes.insert(0, Position(0));
+        let held = archetypes.matching(&[TypeId::of::<Position>()]);
+
+        // Enough new archetypes for the list of them to grow several times
+        archetypes.insert(1, Tag::<0>);
+        archetypes.insert(1, Tag::<1>);
+        archetypes.insert(1, Tag::<2>);
+        archetypes.insert(1, Tag::<3>);
+        archetypes.insert(1, Tag::<4>);
+        archetypes.insert(1, Tag::<5>);
+        archetypes.insert(1, Tag::<6>);
+        archetypes.insert(1, Tag::<7>);
+
+        assert_eq!(*held[0].keys(), [0]);
+        assert!(held[0].has(TypeId::of::<Position>()));
+        assert_eq!(archetypes.types_of(&1).map(<[_]>::len), Some(8));
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    cell::{Ref, RefMut},
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+};
+
+use super::{ComponentStorage, SparseIndex, SparseSet, Storable, Store};
+
+/// How the components of `T` are kept in a [`Store`], which the [`Storable::Storage`] that `T`
+/// picks decides.
+///
+/// Map storages keep each type's components on their own, while an
+/// [`ArchetypeStorage`](super::ArchetypeStorage) keeps them in the columns of the
+/// [`Archetypes`](super::Archetypes) shared by every type that picks it.
+pub trait ComponentLayout<T>: Sized {
+    type Key;
+
+    /// Sets up anything the layout needs when the storage of `T` is added to `store`
+    fn prepare(_store: &mut Store) {}
+
+    /// The keys with a `T`, or none if `T` has no storage
+    fn keys(store: &Store) -> Vec<Self::Key>;
+    fn contains(store: &Store, key: &Self::Key) -> bool;
+
+    /// Borrows the component under `key`, holding the storage it is in until dropped
+    fn fetch<'a>(store: &'a Store, key: &Self::Key) -> Option<Ref<'a, T>>;
+    fn fetch_mut<'a>(store: &'a Store, key: &Self::Key) -> Option<RefMut<'a, T>>;
+
+    /// Returns the previous component stored under `key`, if any
+    fn insert(store: &Store, key: Self::Key, value: T) -> Option<T>;
+    fn remove(store: &Store, key: &Self::Key) -> Option<T>;
+}
+
+/// Implements [`ComponentLayout`] for a map storage, which is borrowed whole from the store
+macro_rules! impl_map_layout {
+    (impl<$($generic:ident),+> for $storage:ty where $($bounds:tt)*) => {
+        impl<$($generic),+> ComponentLayout<T> for $storage
+        where
+            K: 'static,
+            T: Storable<Storage = Self>,
+            $($bounds)*
+        {
+            type Key = K;
+
+            fn keys(store: &Store) -> Vec<K> {
+                store
+                    .try_get_storage::<T>()
+                    .map_or_else(Vec::new, |storage| ComponentStorage::keys(&*storage.borrow()))
+            }
+
+            fn contains(store: &Store, key: &K) -> bool {
+                store
+                    .try_get_storage::<T>()
+                    .is_some_and(|storage| storage.borrow().contains_key(key))
+            }
+
+            fn fetch<'a>(store: &'a Store, key: &K) -> Option<Ref<'a, T>> {
+                Ref::filter_map(store.try_get_storage::<T>()?.borrow(), |storage| {
+                    storage.get(key)
+                })
+                .ok()
+            }
+
+            fn fetch_mut<'a>(store: &'a Store, key: &K) -> Option<RefMut<'a, T>> {
+                RefMut::filter_map(store.try_get_storage::<T>()?.borrow_mut(), |storage| {
+                    storage.get_mut(key)
+                })
+                .ok()
+            }
+
+            fn insert(store: &Store, key: K, value: T) -> Option<T> {
+                store.get_storage::<T>().borrow_mut().insert(key, value)
+            }
+
+            fn remove(store: &Store, key: &K) -> Option<T> {
+                store.try_get_storage::<T>()?.borrow_mut().remove(key)
+            }
+        }
+    };
+}
+
+impl_map_layout!(
+    impl<K, T, S> for HashMap<K, T, S> where K: Eq + Hash + Clone, S: BuildHasher + 'static
+);
+impl_map_layout!(impl<K, T> for BTreeMap<K, T> where K: Ord + Clone);
+impl_map_layout!(impl<K, T> for SparseSet<K, T> where K: SparseIndex + Clone);
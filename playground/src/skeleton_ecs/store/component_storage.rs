@@ -0,0 +1,72 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+};
+
+/// Keyed access to the components held by a [`Storable::Storage`](super::Storable::Storage),
+/// used to join storages when iterating a [`Store`](super::Store)
+pub trait ComponentStorage<K, T> {
+    /// Returns the previous component stored under `key`, if any
+    fn insert(&mut self, key: K, value: T) -> Option<T>;
+    fn get(&self, key: &K) -> Option<&T>;
+    fn get_mut(&mut self, key: &K) -> Option<&mut T>;
+    fn remove(&mut self, key: &K) -> Option<T>;
+
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The keys present, in order if the storage is ordered
+    fn keys(&self) -> Vec<K>;
+}
+
+impl<K, T, S> ComponentStorage<K, T> for HashMap<K, T, S>
+where
+    K: Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    fn insert(&mut self, key: K, value: T) -> Option<T> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&T> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut T> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<T> {
+        HashMap::remove(self, key)
+    }
+
+    fn keys(&self) -> Vec<K> {
+        HashMap::keys(self).cloned().collect()
+    }
+}
+
+impl<K, T> ComponentStorage<K, T> for BTreeMap<K, T>
+where
+    K: Ord + Clone,
+{
+    fn insert(&mut self, key: K, value: T) -> Option<T> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&T> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut T> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<T> {
+        BTreeMap::remove(self, key)
+    }
+
+    fn keys(&self) -> Vec<K> {
+        BTreeMap::keys(self).cloned().collect()
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+};
+
+use super::{Archetypes, ComponentLayout, Storable};
+
+/// The storages of any number of component types, each behind a `RefCell` so systems can
+/// borrow different components mutably at once.
+///
+/// Types whose storage is an [`ArchetypeStorage`](super::ArchetypeStorage) share the
+/// [`Archetypes`] of their key type instead.
+#[derive(Default)]
+pub struct Store {
+    storages: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Store {
+    /// Adds an empty storage for `T`, if it has none yet
+    pub fn add_storage_for<T>(&mut self)
+    where
+        T: Storable,
+        T::Storage: ComponentLayout<T>,
+    {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(RefCell::new(T::Storage::default())));
+        T::Storage::prepare(self);
+    }
+
+    /// Adds the archetypes of entities keyed by `K`, if there are none yet
+    pub(super) fn add_archetypes<K>(&mut self)
+    where
+        K: 'static,
+    {
+        self.storages
+            .entry(TypeId::of::<Archetypes<K>>())
+            .or_insert_with(|| Box::new(Archetypes::<K>::default()));
+    }
+
+    /// The archetypes of entities keyed by `K`, or `None` if no type with an
+    /// [`ArchetypeStorage`](super::ArchetypeStorage) keyed by `K` has been added
+    pub fn archetypes<K>(&self) -> Option<&Archetypes<K>>
+    where
+        K: 'static,
+    {
+        self.storages
+            .get(&TypeId::of::<Archetypes<K>>())
+            .map(|archetypes| archetypes.downcast_ref::<Archetypes<K>>().unwrap())
+    }
+
+    /// Adds `value` to the components of `key`, returning the one it replaced if any.
+    /// Panics if `T` has no storage
+    pub fn insert<T>(&self, key: <T::Storage as ComponentLayout<T>>::Key, value: T) -> Option<T>
+    where
+        T: Storable,
+        T::Storage: ComponentLayout<T>,
+    {
+        T::Storage::insert(self, key, value)
+    }
+
+    pub fn remove<T>(&self, key: &<T::Storage as ComponentLayout<T>>::Key) -> Option<T>
+    where
+        T: Storable,
+        T::Storage: ComponentLayout<T>,
+    {
+        T::Storage::remove(self, key)
+    }
+
+    pub fn has_storage_for<T>(&self) -> bool
+    where
+        T: Storable,
+    {
+        self.storages.contains_key(&TypeId::of::<T>())
+    }
+
+    /// The storage of `T`, or `None` if it hasn't been added
+    pub fn try_get_storage<T>(&self) -> Option<&RefCell<T::Storage>>
+    where
+        T: Storable,
+    {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|storage| storage.downcast_ref::<RefCell<T::Storage>>().unwrap())
+    }
+
+    /// The storage of `T`. Panics if it hasn't been added
+    pub fn get_storage<T>(&self) -> &RefCell<T::Storage>
+    where
+        T: Storable,
+    {
+        self.try_get_storage::<T>()
+            .unwrap_or_else(|| panic!("No storage for {}", type_name::<T>()))
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    any::TypeId,
+    cell::{Ref, RefMut},
+    hash::Hash,
+};
+
+use super::{Archetype, ArchetypeStorage, Storable, Store};
+
+/// A borrow of one component of an entity in an [`Archetype`], which [`IterArchetypes`] takes
+/// row by row from its column
+pub trait ArchetypeField<'a, K>: Sized {
+    /// The rows of the column not taken yet
+    type Rows;
+
+    fn type_id() -> TypeId;
+
+    /// Borrows the whole column of an archetype that has the field's type
+    fn rows(archetype: &'a Archetype<K>) -> Self::Rows;
+
+    /// Splits the first row off `rows`
+    fn take_first(rows: &mut Option<Self::Rows>) -> Self;
+}
+
+impl<'a, K, T> ArchetypeField<'a, K> for Ref<'a, T>
+where
+    T: Storable<Storage = ArchetypeStorage<K>>,
+{
+    type Rows = Ref<'a, [T]>;
+
+    fn type_id() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn rows(archetype: &'a Archetype<K>) -> Self::Rows {
+        Ref::map(archetype.column::<T>().unwrap().borrow(), Vec::as_slice)
+    }
+
+    fn take_first(rows: &mut Option<Self::Rows>) -> Self {
+        let (first, rest) =
+            Ref::map_split(rows.take().unwrap(), |rows| rows.split_first().unwrap());
+        *rows = Some(rest);
+        first
+    }
+}
+
+impl<'a, K, T> ArchetypeField<'a, K> for RefMut<'a, T>
+where
+    T: Storable<Storage = ArchetypeStorage<K>>,
+{
+    type Rows = RefMut<'a, [T]>;
+
+    fn type_id() -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn rows(archetype: &'a Archetype<K>) -> Self::Rows {
+        RefMut::map(
+            archetype.column::<T>().unwrap().borrow_mut(),
+            Vec::as_mut_slice,
+        )
+    }
+
+    fn take_first(rows: &mut Option<Self::Rows>) -> Self {
+        let (first, rest) =
+            RefMut::map_split(rows.take().unwrap(), |rows| rows.split_first_mut().unwrap());
+        *rows = Some(rest);
+        first
+    }
+}
+
+/// Iterates the entities of a [`Store`] that have every component of a tuple of
+/// [`ArchetypeField`]s, such as `(Ref<Velocity>, RefMut<Position>)` for components in an
+/// [`ArchetypeStorage`], walking the columns of each matching archetype in turn.
+///
+/// The columns of an archetype stay borrowed until its last entity has been visited.
+pub trait IterArchetypes<'a, K, Fields> {
+    /// Every key with all the fields, archetype by archetype in row order
+    fn iter_archetypes(&'a self) -> Box<dyn Iterator<Item = (K, Fields)> + 'a>;
+}
+
+macro_rules! impl_iter_archetypes {
+    ($($field:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<'a, K, $($field),+> IterArchetypes<'a, K, ($($field,)+)> for Store
+        where
+            K: Eq + Hash + Clone + 'static,
+            $($field: ArchetypeField<'a, K> + 'a,)+
+        {
+            fn iter_archetypes(&'a self) -> Box<dyn Iterator<Item = (K, ($($field,)+))> + 'a> {
+                let archetypes = match self.archetypes::<K>() {
+                    Some(archetypes) => archetypes.matching(&[$($field::type_id()),+]),
+                    None => vec![],
+                };
+
+                Box::new(archetypes.into_iter().flat_map(|archetype| {
+                    let keys = archetype.keys().to_vec();
+                    let ($(mut $field,)+) = ($(Some($field::rows(archetype)),)+);
+                    keys.into_iter()
+                        .map(move |key| (key, ($($field::take_first(&mut $field),)+)))
+                }))
+            }
+        }
+    };
+}
+
+/// Implements [`IterArchetypes`] for tuples of every length up to the number of idents given
+macro_rules! impl_iter_archetypes_tuples {
+    ($a:ident $(, $bs:ident)*) => {
+        impl_iter_archetypes!($a $(, $bs)*);
+        impl_iter_archetypes_tuples!($($bs),*);
+    };
+    () => {};
+}
+
+impl_iter_archetypes_tuples!(A, B, C, D, E, F, G, H, I, J, K0, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::skeleton_ecs::store::IterStoreFields;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+
+    impl Storable for Position {
+        type Storage = ArchetypeStorage<u32>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32);
+
+    impl Storable for Velocity {
+        type Storage = ArchetypeStorage<u32>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Storable for Name {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    fn store() -> Store {
+        let mut store = Store::default();
+        store.add_storage_for::<Position>();
+        store.add_storage_for::<Velocity>();
+        store.add_storage_for::<Name>();
+        for key in 0..6 {
+            store.insert(key, Position(0));
+            if key % 2 == 1 {
+                store.insert(key, Velocity(key as i32));
+            }
+        }
+        store.insert(3, Name("three"));
+        store
+    }
+
+    #[test]
+    fn matching_archetypes_are_walked_in_row_order() {
+        let store = store();
+        store.remove::<Position>(&5);
+        store.insert(5, Position(100));
+
+        IterArchetypes::<u32, (Ref<Velocity>, RefMut<Position>)>::iter_archetypes(&store)
+            .for_each(|(_, (velocity, mut position))| position.0 += velocity.0);
+
+        let positions = IterArchetypes::<u32, (Ref<Position>,)>::iter_archetypes(&store)
+            .map(|(key, (position,))| (key, position.0))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            vec![(0, 0), (2, 0), (4, 0), (1, 1), (3, 3), (5, 105)]
+        );
+
+        // Keyed iteration joins archetype components with other storages
+        let named = IterStoreFields::<u32, (Ref<Name>, Ref<Position>)>::iter(&store)
+            .map(|(key, (name, position))| (key, name.0, position.0))
+            .collect::<Vec<_>>();
+        assert_eq!(named, vec![(3, "three", 3)]);
+    }
+
+    #[test]
+    fn rows_can_be_held_across_items() {
+        let store = store();
+        let mut velocities = IterArchetypes::<u32, (RefMut<Velocity>,)>::iter_archetypes(&store)
+            .map(|(_, (velocity,))| velocity)
+            .collect::<Vec<_>>();
+        velocities.iter_mut().for_each(|velocity| velocity.0 *= 2);
+        drop(velocities);
+
+        assert_eq!(
+            store
+                .archetypes::<u32>()
+                .unwrap()
+                .get::<Velocity>(&5)
+                .as_deref(),
+            Some(&Velocity(10))
+        );
+        assert_eq!(
+            IterArchetypes::<u32, (Ref<Velocity>,)>::iter_archetypes(&store).count(),
+            3
+        );
+    }
+}
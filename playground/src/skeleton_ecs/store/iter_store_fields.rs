@@ -0,0 +1,209 @@
+use std::cell::{Ref, RefMut};
+
+use super::{ComponentLayout, Storable, Store, SyncStore};
+
+/// A borrow of one component of an entity in a store of type `S`, which [`IterStoreFields`]
+/// joins on
+pub trait StoreField<'a, K, S>: Sized {
+    /// The keys with this component, or none if its type has no storage
+    fn keys(store: &'a S) -> Vec<K>;
+    fn contains(store: &'a S, key: &K) -> bool;
+
+    /// Borrows the component under `key`, holding its whole storage until dropped
+    fn fetch(store: &'a S, key: &K) -> Option<Self>;
+}
+
+impl<'a, K, T> StoreField<'a, K, Store> for Ref<'a, T>
+where
+    T: Storable,
+    T::Storage: ComponentLayout<T, Key = K>,
+{
+    fn keys(store: &'a Store) -> Vec<K> {
+        T::Storage::keys(store)
+    }
+
+    fn contains(store: &'a Store, key: &K) -> bool {
+        T::Storage::contains(store, key)
+    }
+
+    fn fetch(store: &'a Store, key: &K) -> Option<Self> {
+        T::Storage::fetch(store, key)
+    }
+}
+
+impl<'a, K, T> StoreField<'a, K, Store> for RefMut<'a, T>
+where
+    T: Storable,
+    T::Storage: ComponentLayout<T, Key = K>,
+{
+    fn keys(store: &'a Store) -> Vec<K> {
+        T::Storage::keys(store)
+    }
+
+    fn contains(store: &'a Store, key: &K) -> bool {
+        T::Storage::contains(store, key)
+    }
+
+    fn fetch(store: &'a Store, key: &K) -> Option<Self> {
+        T::Storage::fetch_mut(store, key)
+    }
+}
+
+/// Iterates the entities of a store that have every component of a tuple of [`StoreField`]s,
+/// such as `(Ref<Velocity>, RefMut<Position>)` for a [`Store`], or
+/// `(Read<Velocity>, Write<Position>)` for a [`SyncStore`](super::SyncStore).
+///
+/// Matching keys are found before anything is borrowed, then each item borrows the storages of
+/// its fields, so items with a `RefMut` must be dropped before the next is taken.
+pub trait IterStoreFields<'a, K, Fields> {
+    /// Every key with all the fields, in the order of the first field's storage
+    fn iter(&'a self) -> Box<dyn Iterator<Item = (K, Fields)> + 'a>;
+
+    /// The keys of `keys` with all the fields, in the order given
+    fn iter_keys<I>(&'a self, keys: I) -> Box<dyn Iterator<Item = (K, Fields)> + 'a>
+    where
+        I: IntoIterator<Item = K>;
+}
+
+macro_rules! impl_iter_store_fields {
+    ($store:ty; $first:ident $(, $rest:ident)*) => {
+        #[allow(non_snake_case)]
+        impl<'a, K, $first $(, $rest)*> IterStoreFields<'a, K, ($first, $($rest,)*)> for $store
+        where
+            K: 'a,
+            $first: StoreField<'a, K, $store> + 'a,
+            $($rest: StoreField<'a, K, $store> + 'a,)*
+        {
+            fn iter(&'a self) -> Box<dyn Iterator<Item = (K, ($first, $($rest,)*))> + 'a> {
+                IterStoreFields::<K, ($first, $($rest,)*)>::iter_keys(self, $first::keys(self))
+            }
+
+            fn iter_keys<I>(&'a self, keys: I) -> Box<dyn Iterator<Item = (K, ($first, $($rest,)*))> + 'a>
+            where
+                I: IntoIterator<Item = K>,
+            {
+                let keys = keys
+                    .into_iter()
+                    .filter(|key| $first::contains(self, key) $(&& $rest::contains(self, key))*)
+                    .collect::<Vec<_>>();
+
+                // Components removed through the storages between items are skipped
+                Box::new(keys.into_iter().filter_map(move |key| {
+                    let fields = ($first::fetch(self, &key)?, $($rest::fetch(self, &key)?,)*);
+                    Some((key, fields))
+                }))
+            }
+        }
+    };
+}
+
+/// Implements [`IterStoreFields`] for both stores and tuples of every length up to the number of
+/// idents given
+macro_rules! impl_iter_store_fields_tuples {
+    ($a:ident $(, $bs:ident)*) => {
+        impl_iter_store_fields!(Store; $a $(, $bs)*);
+        impl_iter_store_fields!(SyncStore; $a $(, $bs)*);
+        impl_iter_store_fields_tuples!($($bs),*);
+    };
+    () => {};
+}
+
+impl_iter_store_fields_tuples!(A, B, C, D, E, F, G, H, I0, J, K0, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Storable for Health {
+        type Storage = BTreeMap<u32, Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    impl Storable for Name {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Poisoned;
+
+    impl Storable for Poisoned {
+        type Storage = BTreeMap<u32, Self>;
+    }
+
+    fn store() -> Store {
+        let mut store = Store::default();
+        store.add_storage_for::<Health>();
+        store.add_storage_for::<Name>();
+        store.add_storage_for::<Poisoned>();
+
+        let mut health = store.get_storage::<Health>().borrow_mut();
+        let mut names = store.get_storage::<Name>().borrow_mut();
+        for (key, name) in [(0, "a"), (1, "b"), (2, "c"), (3, "d")].iter() {
+            health.insert(*key, Health(10));
+            if *key != 2 {
+                names.insert(*key, Name(name));
+            }
+        }
+        drop((health, names));
+        store
+            .get_storage::<Poisoned>()
+            .borrow_mut()
+            .insert(3, Poisoned);
+        store
+            .get_storage::<Poisoned>()
+            .borrow_mut()
+            .insert(1, Poisoned);
+        store
+    }
+
+    #[test]
+    fn fields_are_joined_on_keys() {
+        let store = store();
+        IterStoreFields::<u32, (Ref<Poisoned>, RefMut<Health>)>::iter(&store)
+            .for_each(|(_, (_, mut health))| health.0 -= 3);
+
+        let health = IterStoreFields::<u32, (Ref<Health>,)>::iter(&store)
+            .map(|(key, (health,))| (key, health.0))
+            .collect::<Vec<_>>();
+        assert_eq!(health, vec![(0, 10), (1, 7), (2, 10), (3, 7)]);
+
+        let mut named =
+            IterStoreFields::<u32, (Ref<Name>, Ref<Health>, Ref<Poisoned>)>::iter(&store)
+                .map(|(key, (name, _, _))| (key, name.0))
+                .collect::<Vec<_>>();
+        named.sort_unstable();
+        assert_eq!(named, vec![(1, "b"), (3, "d")]);
+    }
+
+    #[test]
+    fn given_keys_are_visited_in_order() {
+        let store = store();
+        let keys = IterStoreFields::<u32, (Ref<Name>,)>::iter_keys(&store, vec![3, 2, 9, 0, 3])
+            .map(|(key, (name,))| (key, name.0))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![(3, "d"), (0, "a"), (3, "d")]);
+    }
+
+    #[test]
+    fn missing_storages_match_nothing() {
+        #[derive(Debug)]
+        struct Unused;
+
+        impl Storable for Unused {
+            type Storage = HashMap<u32, Self>;
+        }
+
+        let store = store();
+        assert_eq!(
+            IterStoreFields::<u32, (Ref<Health>, RefMut<Unused>)>::iter(&store).count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,29 @@
+mod access;
+mod archetype;
+mod archetype_storage;
+mod archetypes;
+mod component_layout;
+mod component_storage;
+mod component_store;
+mod iter_archetypes;
+mod iter_store_fields;
+mod sparse_index;
+mod sparse_set;
+mod storable;
+mod sync_field;
+mod sync_store;
+
+pub use access::*;
+pub use archetype::*;
+pub use archetype_storage::*;
+pub use archetypes::*;
+pub use component_layout::*;
+pub use component_storage::*;
+pub use component_store::*;
+pub use iter_archetypes::*;
+pub use iter_store_fields::*;
+pub use sparse_index::*;
+pub use sparse_set::*;
+pub use storable::*;
+pub use sync_field::*;
+pub use sync_store::*;
@@ -0,0 +1,23 @@
+/// A key that maps to a small index, so a [`SparseSet`](super::SparseSet) can find its
+/// component without hashing
+pub trait SparseIndex {
+    fn sparse_index(&self) -> usize;
+}
+
+impl SparseIndex for usize {
+    fn sparse_index(&self) -> usize {
+        *self
+    }
+}
+
+impl SparseIndex for u32 {
+    fn sparse_index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl SparseIndex for u64 {
+    fn sparse_index(&self) -> usize {
+        *self as usize
+    }
+}
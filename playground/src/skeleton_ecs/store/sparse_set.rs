@@ -0,0 +1,143 @@
+use super::{ComponentStorage, SparseIndex};
+
+/// A storage that keeps components packed in insertion order, found through a vector indexed
+/// by [`SparseIndex`], so lookups don't hash and iteration is linear.
+///
+/// Removal swaps the last component into the gap, so order isn't kept across removals.
+#[derive(Debug, Clone)]
+pub struct SparseSet<K, T> {
+    /// Position in `keys` and `values` of each sparse index present
+    sparse: Vec<Option<usize>>,
+    keys: Vec<K>,
+    values: Vec<T>,
+}
+
+impl<K, T> Default for SparseSet<K, T> {
+    fn default() -> Self {
+        SparseSet {
+            sparse: vec![],
+            keys: vec![],
+            values: vec![],
+        }
+    }
+}
+
+impl<K, T> SparseSet<K, T>
+where
+    K: SparseIndex,
+{
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    fn position(&self, key: &K) -> Option<usize> {
+        self.sparse.get(key.sparse_index()).copied().flatten()
+    }
+
+    /// Returns the previous component stored under `key`, if any
+    pub fn insert(&mut self, key: K, value: T) -> Option<T> {
+        if let Some(position) = self.position(&key) {
+            return Some(std::mem::replace(&mut self.values[position], value));
+        }
+
+        let index = key.sparse_index();
+        if index >= self.sparse.len() {
+            self.sparse.resize(index + 1, None);
+        }
+        self.sparse[index] = Some(self.values.len());
+        self.keys.push(key);
+        self.values.push(value);
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&T> {
+        self.position(key).map(|position| &self.values[position])
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut T> {
+        self.position(key)
+            .map(move |position| &mut self.values[position])
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<T> {
+        let position = self.position(key)?;
+        self.sparse[key.sparse_index()] = None;
+        self.keys.swap_remove(position);
+        if let Some(moved) = self.keys.get(position) {
+            self.sparse[moved.sparse_index()] = Some(position);
+        }
+        Some(self.values.swap_remove(position))
+    }
+
+    /// The keys present, in the order their components are packed
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+}
+
+impl<K, T> ComponentStorage<K, T> for SparseSet<K, T>
+where
+    K: SparseIndex + Clone,
+{
+    fn insert(&mut self, key: K, value: T) -> Option<T> {
+        SparseSet::insert(self, key, value)
+    }
+
+    fn get(&self, key: &K) -> Option<&T> {
+        SparseSet::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut T> {
+        SparseSet::get_mut(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<T> {
+        SparseSet::remove(self, key)
+    }
+
+    fn keys(&self) -> Vec<K> {
+        self.keys.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removal_keeps_components_packed() {
+        let mut set = SparseSet::new();
+        for key in [4_usize, 0, 9, 2].iter() {
+            assert_eq!(set.insert(*key, *key * 10), None);
+        }
+        assert_eq!(set.insert(9, 91), Some(90));
+
+        assert_eq!(set.remove(&0), Some(0));
+        assert_eq!(set.remove(&0), None);
+        assert_eq!(set.remove(&7), None);
+        assert_eq!(set.keys(), &[4, 2, 9]);
+        assert_eq!(set.values(), &[40, 20, 91]);
+
+        *set.get_mut(&2).unwrap() += 1;
+        assert_eq!(set.get(&2), Some(&21));
+        assert_eq!(set.get(&0), None);
+        assert_eq!(set.get(&100), None);
+        assert_eq!(set.len(), 3);
+    }
+}
@@ -0,0 +1,4 @@
+/// A component type, which picks the storage its values are kept in
+pub trait Storable: Sized + 'static {
+    type Storage: Default + 'static;
+}
@@ -0,0 +1,191 @@
+use std::{
+    fmt::{self, Debug},
+    ops::{Deref, DerefMut},
+    sync::{RwLockReadGuard, RwLockWriteGuard},
+};
+
+use super::{ComponentStorage, Storable, StoreField, SyncStore};
+
+/// A component borrowed from a [`SyncStore`], holding a read lock on its storage
+pub struct Read<'a, T>
+where
+    T: Storable,
+{
+    _guard: RwLockReadGuard<'a, T::Storage>,
+    value: *const T,
+}
+
+impl<'a, T> Deref for Read<'a, T>
+where
+    T: Storable,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // The storage can't be modified while its read guard is held,
+        // so the component stays where it was found
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T> Debug for Read<'a, T>
+where
+    T: Storable + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+/// A component mutably borrowed from a [`SyncStore`], holding a write lock on its storage
+pub struct Write<'a, T>
+where
+    T: Storable,
+{
+    _guard: RwLockWriteGuard<'a, T::Storage>,
+    value: *mut T,
+}
+
+impl<'a, T> Deref for Write<'a, T>
+where
+    T: Storable,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // The guard is exclusive, so this is the only borrow of the storage
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T> DerefMut for Write<'a, T>
+where
+    T: Storable,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.value }
+    }
+}
+
+impl<'a, T> Debug for Write<'a, T>
+where
+    T: Storable + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<'a, K, T> StoreField<'a, K, SyncStore> for Read<'a, T>
+where
+    T: Storable,
+    T::Storage: ComponentStorage<K, T>,
+{
+    fn keys(store: &'a SyncStore) -> Vec<K> {
+        store
+            .try_get_storage::<T>()
+            .map_or_else(Vec::new, |storage| storage.read().unwrap().keys())
+    }
+
+    fn contains(store: &'a SyncStore, key: &K) -> bool {
+        store
+            .try_get_storage::<T>()
+            .is_some_and(|storage| storage.read().unwrap().contains_key(key))
+    }
+
+    fn fetch(store: &'a SyncStore, key: &K) -> Option<Self> {
+        let guard = store.try_get_storage::<T>()?.read().unwrap();
+        let value: *const T = guard.get(key)?;
+        Some(Read {
+            _guard: guard,
+            value,
+        })
+    }
+}
+
+impl<'a, K, T> StoreField<'a, K, SyncStore> for Write<'a, T>
+where
+    T: Storable,
+    T::Storage: ComponentStorage<K, T>,
+{
+    fn keys(store: &'a SyncStore) -> Vec<K> {
+        Read::<T>::keys(store)
+    }
+
+    fn contains(store: &'a SyncStore, key: &K) -> bool {
+        Read::<T>::contains(store, key)
+    }
+
+    fn fetch(store: &'a SyncStore, key: &K) -> Option<Self> {
+        let mut guard = store.try_get_storage::<T>()?.write().unwrap();
+        let value: *mut T = guard.get_mut(key)?;
+        Some(Write {
+            _guard: guard,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, thread};
+
+    use super::*;
+    use crate::skeleton_ecs::store::IterStoreFields;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i64);
+
+    impl Storable for Position {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i64);
+
+    impl Storable for Velocity {
+        type Storage = HashMap<u32, Self>;
+    }
+
+    #[test]
+    fn fields_are_shared_between_threads() {
+        let mut store = SyncStore::default();
+        store.add_storage_for::<Position>();
+        store.add_storage_for::<Velocity>();
+        for key in 0..4 {
+            store
+                .get_storage::<Position>()
+                .write()
+                .unwrap()
+                .insert(key, Position(0));
+        }
+        for key in 1..3 {
+            store
+                .get_storage::<Velocity>()
+                .write()
+                .unwrap()
+                .insert(key, Velocity(key as i64));
+        }
+
+        thread::scope(|scope| {
+            for _ in 0..3 {
+                scope.spawn(|| {
+                    IterStoreFields::<u32, (Read<Velocity>, Write<Position>)>::iter(&store)
+                        .for_each(|(_, (velocity, mut position))| position.0 += velocity.0);
+                });
+            }
+        });
+
+        let mut positions = IterStoreFields::<u32, (Read<Position>,)>::iter(&store)
+            .map(|(key, (position,))| (key, position.0))
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+        assert_eq!(positions, vec![(0, 0), (1, 3), (2, 6), (3, 0)]);
+        assert_eq!(
+            IterStoreFields::<u32, (Write<Velocity>, Read<Position>)>::iter_keys(&store, vec![2])
+                .map(|(_, (velocity, _))| format!("{:?}", velocity))
+                .collect::<Vec<_>>(),
+            vec!["Velocity(2)"]
+        );
+    }
+}
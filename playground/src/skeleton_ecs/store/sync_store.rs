@@ -0,0 +1,53 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    sync::RwLock,
+};
+
+use super::Storable;
+
+/// A [`Store`](super::Store) that can be shared between threads, with each storage behind a
+/// `RwLock` instead of a `RefCell`
+#[derive(Default)]
+pub struct SyncStore {
+    storages: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl SyncStore {
+    /// Adds an empty storage for `T`, if it has none yet
+    pub fn add_storage_for<T>(&mut self)
+    where
+        T: Storable,
+        T::Storage: Send + Sync,
+    {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(RwLock::new(T::Storage::default())));
+    }
+
+    pub fn has_storage_for<T>(&self) -> bool
+    where
+        T: Storable,
+    {
+        self.storages.contains_key(&TypeId::of::<T>())
+    }
+
+    /// The storage of `T`, or `None` if it hasn't been added
+    pub fn try_get_storage<T>(&self) -> Option<&RwLock<T::Storage>>
+    where
+        T: Storable,
+    {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .map(|storage| storage.downcast_ref::<RwLock<T::Storage>>().unwrap())
+    }
+
+    /// The storage of `T`. Panics if it hasn't been added
+    pub fn get_storage<T>(&self) -> &RwLock<T::Storage>
+    where
+        T: Storable,
+    {
+        self.try_get_storage::<T>()
+            .unwrap_or_else(|| panic!("No storage for {}", type_name::<T>()))
+    }
+}
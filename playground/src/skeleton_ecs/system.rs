@@ -0,0 +1,22 @@
+use std::any::type_name;
+
+use super::store::{Access, Store};
+
+/// Logic run over the components of a store each time a [`Schedule`](super::Schedule) ticks.
+///
+/// Systems run against a [`Store`] by default, or against a
+/// [`SyncStore`](super::store::SyncStore) to be run in parallel.
+pub trait System<S = Store>: Send {
+    fn run(&mut self, store: &S);
+
+    /// The storages the system reads and writes, usually `Access::of` the fields it iterates.
+    /// Systems that don't declare it conflict with every other.
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+
+    /// Name used in diagnostics
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+}